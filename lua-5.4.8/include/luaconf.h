@@ -163,6 +163,16 @@
 
 #endif				/* } */
 
+#if defined(LUNKA_INT_TYPE)
+#undef LUA_INT_TYPE
+#define LUA_INT_TYPE	LUNKA_INT_TYPE
+#endif
+
+#if defined(LUNKA_FLOAT_TYPE)
+#undef LUA_FLOAT_TYPE
+#define LUA_FLOAT_TYPE	LUNKA_FLOAT_TYPE
+#endif
+
 
 /* }================================================================== */
 
@@ -805,4 +815,95 @@
 #define LUA_IDSIZE LUNKA_IDSIZE
 #endif
 
+#if defined(LUNKA_MAXSTACK)
+#undef LUAI_MAXSTACK
+#define LUAI_MAXSTACK LUNKA_MAXSTACK
+#endif
+
+#if defined(LUNKA_MAXCCALLS)
+#define LUAI_MAXCCALLS LUNKA_MAXCCALLS
+#endif
+
+#if defined(LUNKA_MAXSHORTLEN)
+#define LUAI_MAXSHORTLEN LUNKA_MAXSHORTLEN
+#endif
+
+#if defined(LUNKA_MINSTRTABSIZE)
+#define MINSTRTABSIZE LUNKA_MINSTRTABSIZE
+#endif
+
+#if defined(LUNKA_STRCACHE_N)
+#define STRCACHE_N LUNKA_STRCACHE_N
+#endif
+
+#if defined(LUNKA_STRCACHE_M)
+#define STRCACHE_M LUNKA_STRCACHE_M
+#endif
+
+#if defined(LUNKA_KCONTEXT)
+#undef LUA_KCONTEXT
+#define LUA_KCONTEXT LUNKA_KCONTEXT
+#endif
+
+#if defined(LUNKA_FIXEDSEED)
+#undef luai_makeseed
+#define luai_makeseed(L) ((void)(L), (unsigned int)(LUNKA_FIXEDSEED))
+#endif
+
+#if defined(LUNKA_BUFFERSIZE)
+#undef LUAL_BUFFERSIZE
+#define LUAL_BUFFERSIZE LUNKA_BUFFERSIZE
+#endif
+
+#if defined(LUNKA_MAXALIGN)
+#undef LUAI_MAXALIGN
+#define LUAI_MAXALIGN LUNKA_MAXALIGN
+#endif
+
+#if defined(LUNKA_NODLOPEN)
+#undef LUA_USE_DLOPEN
+#undef LUA_DL_DLL
+#endif
+
+#if defined(LUNKA_NOSYSTEM)
+#define l_system(cmd) ((cmd) == NULL ? 0 : -1)
+#endif
+
+#if defined(LUNKA_NOTMPNAM)
+#define LUA_TMPNAMBUFSIZE 32
+#define lua_tmpnam(b,e) { (void)(b); e = 1; }
+#endif
+
+#if defined(LUNKA_NOPOPEN)
+#define l_popen(L,c,m) ((void)(c), (void)(m), luaL_error(L, "'popen' not supported"), (FILE *)NULL)
+#define l_pclose(L,file) ((void)(L), (void)(file), -1)
+#endif
+
+#if defined(LUNKA_NOLOCALE)
+#undef lua_getlocaledecpoint
+#define lua_getlocaledecpoint() '.'
+#define setlocale(c,l) ((void)(c), (void)(l), (char *)NULL)
+#endif
+
+#if defined(LUNKA_HIDDEN_SYMBOLS) && defined(__GNUC__) && !defined(_WIN32)
+#undef LUA_API
+#define LUA_API __attribute__((visibility("default"))) extern
+#undef LUAI_FUNC
+#define LUAI_FUNC __attribute__((visibility("hidden"))) extern
+#endif
+
+#if defined(LUNKA_FUNC)
+#undef LUAI_FUNC
+#define LUAI_FUNC LUNKA_FUNC
+#endif
+
+#if defined(LUNKA_DDEC)
+#undef LUAI_DDEC
+#define LUAI_DDEC(dec) LUNKA_DDEC dec
+#endif
+
+#if defined(LUNKA_LOCAL_H)
+#include LUNKA_LOCAL_H
+#endif
+
 #endif
\ No newline at end of file
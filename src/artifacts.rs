@@ -0,0 +1,89 @@
+use ::std::{
+	env::join_paths,
+	ffi::OsString,
+	path::PathBuf,
+	process::Command,
+};
+
+use crate::conf_gen::Define;
+
+/// Structure that describes the outputs of a [`Build`](crate::Build),
+/// for handing off to dependent crates or child processes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Artifacts {
+	/// Name of the compiled library, as passed to the linker.
+	pub lib_name: String,
+	/// Directory containing the compiled library.
+	pub lib_dir: PathBuf,
+	/// Path to the compiled library, which was checked to exist after compiling,
+	/// or `None` for a prebuilt [`External`](crate::External) library.
+	pub lib_path: Option<PathBuf>,
+	/// Directories containing the headers that the library was compiled with.
+	pub include_dirs: Vec<PathBuf>,
+	/// Definitions that the library was compiled with on the command line.
+	pub defines: Vec<Define>,
+	/// Libraries that must be linked along with the library, such as `m`,
+	/// as given by [`Platform::link_libs`](crate::platforms::Platform::link_libs).
+	pub link_libs: Vec<String>,
+	/// Arguments that must be passed to the linker when linking to the library,
+	/// such as the ones that pull in sanitizer runtimes.
+	pub link_args: Vec<String>,
+}
+
+impl Artifacts {
+	/// Return the environment variables that describe these artifacts,
+	/// with names starting with `prefix`:
+	/// - `{prefix}_INCLUDE` for [`Artifacts::include_dirs`], joined like `PATH`;
+	/// - `{prefix}_LIB` for [`Artifacts::lib_dir`];
+	/// - `{prefix}_LIB_NAME` for [`Artifacts::lib_name`];
+	/// - `{prefix}_DEFINES` for [`Artifacts::defines`], as space-separated `NAME` or `NAME=VALUE` items.
+	pub fn env_vars(&self, prefix: &str) -> Vec<(String, OsString)> {
+		let defines = self.defines.iter().map(move |(name, value)| match value {
+			Some(value) => format!("{name}={value}"),
+			None => name.clone(),
+		}).collect::<Vec<_>>().join(" ");
+		vec![
+			(format!("{prefix}_INCLUDE"), join_paths(&self.include_dirs).unwrap_or_default()),
+			(format!("{prefix}_LIB"), self.lib_dir.clone().into()),
+			(format!("{prefix}_LIB_NAME"), self.lib_name.clone().into()),
+			(format!("{prefix}_DEFINES"), defines.into()),
+		]
+	}
+
+	/// Set the environment variables from [`Artifacts::env_vars`] for `command`.
+	pub fn apply_env<'a>(&self, command: &'a mut Command, prefix: &str) -> &'a mut Command {
+		command.envs(self.env_vars(prefix))
+	}
+
+	/// Print [`Artifacts::link_libs`] as `cargo:rustc-link-lib` and [`Artifacts::link_args`] as `cargo:rustc-link-arg`.
+	pub fn emit_link_args(&self) {
+		for lib in self.link_libs.iter() {
+			println!("cargo:rustc-link-lib={lib}");
+		}
+		for arg in self.link_args.iter() {
+			println!("cargo:rustc-link-arg={arg}");
+		}
+	}
+
+	/// Print these artifacts as metadata for dependent crates,
+	/// which is only passed on to them if the manifest of the crate being built has a `links` key.
+	/// 
+	/// With `links = "lua54"`, dependent crates can read the following variables in their build scripts:
+	/// - `DEP_LUA54_INCLUDE` for [`Artifacts::include_dirs`], joined like `PATH`;
+	/// - `DEP_LUA54_LIB` for [`Artifacts::lib_dir`];
+	/// - `DEP_LUA54_LIB_NAME` for [`Artifacts::lib_name`];
+	/// - `DEP_LUA54_DEFINES` for [`Artifacts::defines`], as space-separated `NAME` or `NAME=VALUE` items.
+	pub fn emit_cargo_metadata(&self) {
+		for (name, value) in self.env_vars("") {
+			println!("cargo:{}={}", name[1..].to_ascii_lowercase(), value.to_string_lossy());
+		}
+	}
+
+	/// Print the environment variables from [`Artifacts::env_vars`] as `cargo:rustc-env`,
+	/// which makes them available to the crate being built through `env!`.
+	pub fn export_env(&self, prefix: &str) {
+		for (name, value) in self.env_vars(prefix) {
+			println!("cargo:rustc-env={name}={}", value.to_string_lossy());
+		}
+	}
+}
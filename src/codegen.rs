@@ -0,0 +1,58 @@
+/// Amount of debug information emitted by GCC-like compilers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugLevel {
+	/// Line tables and enough information for backtraces (`-g1`).
+	Minimal,
+	/// Regular debug information (`-g2`).
+	Full,
+	/// Regular debug information and macro definitions (`-g3`),
+	/// which allows for debuggers to expand macros from `luaconf.h` and the like.
+	Macros,
+}
+
+impl DebugLevel {
+	/// Return the flag for this level.
+	pub const fn flag(self) -> &'static str {
+		match self {
+			Self::Minimal => "-g1",
+			Self::Full => "-g2",
+			Self::Macros => "-g3",
+		}
+	}
+}
+
+/// Runtime error detector that code can be instrumented with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sanitizer {
+	/// AddressSanitizer, for out-of-bounds accesses, use-after-free and such.
+	Address,
+	/// UndefinedBehaviorSanitizer.
+	Undefined,
+	/// MemorySanitizer, for reads of uninitialized memory.
+	Memory,
+	/// ThreadSanitizer, for data races.
+	Thread,
+}
+
+impl Sanitizer {
+	/// Return the name of this sanitizer, as used in `-fsanitize=`.
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Address => "address",
+			Self::Undefined => "undefined",
+			Self::Memory => "memory",
+			Self::Thread => "thread",
+		}
+	}
+}
+
+/// Kind of link-time optimization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lto {
+	/// ThinLTO, which scales better with the number of cores (`-flto=thin`).
+	/// 
+	/// GCC doesn't implement it, and uses regular LTO instead.
+	Thin,
+	/// Regular LTO over the whole library (`-flto`).
+	Fat,
+}
@@ -0,0 +1,103 @@
+use ::cc::Tool;
+
+/// Enumeration of the kinds of C compilers that a [`Build`](crate::Build) distinguishes between.
+/// 
+/// These correspond to the fields of [`Standards`](crate::platforms::Standards).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompilerFamily {
+	/// GCC and compatible compilers.
+	Gnu,
+	/// Clang with its GCC-like driver.
+	Clang,
+	/// Microsoft Visual C++.
+	Msvc,
+	/// Clang with its MSVC-like driver, `clang-cl`.
+	ClangCl,
+}
+
+impl CompilerFamily {
+	/// Return the family of `tool`, if it's one of the known ones.
+	pub fn of(tool: &Tool) -> Option<Self> {
+		if tool.is_like_clang_cl() {
+			Some(Self::ClangCl)
+		} else if tool.is_like_msvc() {
+			Some(Self::Msvc)
+		} else if tool.is_like_clang() {
+			Some(Self::Clang)
+		} else if tool.is_like_gnu() {
+			Some(Self::Gnu)
+		} else {
+			None
+		}
+	}
+}
+
+/// Return the value of `/std:` that MSVC and `clang-cl` understand for the C standard `std`,
+/// such as `c17` for `gnu17`,
+/// or [`None`] for C89 and C99, which have no such value and are covered by the default mode.
+/// 
+/// The GNU dialects are mapped to the ISO standards, as these compilers don't have them.
+/// Other values, such as `clatest`, are passed through as is.
+pub(crate) fn msvc_std(std: &str) -> Option<&str> {
+	let version = std.strip_prefix("gnu").or_else(move || std.strip_prefix('c')).unwrap_or(std);
+	match version {
+		"89" | "90" | "99" => None,
+		"11" => Some("c11"),
+		"17" | "18" => Some("c17"),
+		"2x" | "23" => Some("clatest"),
+		_ => Some(std),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::platforms::Standards;
+
+	/// Return the tool that `cc` detects for `compiler` when targeting `target`,
+	/// panicking if it can't be detected.
+	fn tool(compiler: &str, target: &str) -> Tool {
+		::cc::Build::new()
+			.compiler(compiler)
+			.target(target)
+			.host(target)
+			.opt_level(0)
+			.debug(false)
+			.cargo_metadata(false)
+			.try_get_compiler()
+			.unwrap_or_else(move |e| panic!("couldn't detect {compiler}: {e}"))
+	}
+
+	#[test]
+	fn msvc_std_for_each_standard() {
+		for (std, expected) in [
+			("c89", None), ("gnu89", None), ("c90", None), ("c99", None), ("gnu99", None),
+			("c11", Some("c11")), ("gnu11", Some("c11")),
+			("c17", Some("c17")), ("gnu17", Some("c17")), ("c18", Some("c17")), ("gnu18", Some("c17")),
+			("c2x", Some("clatest")), ("gnu2x", Some("clatest")), ("c23", Some("clatest")), ("gnu23", Some("clatest")),
+			("clatest", Some("clatest")),
+		] {
+			assert_eq!(msvc_std(std), expected, "for {std:?}");
+		}
+	}
+
+	#[test]
+	#[ignore = "needs gcc"]
+	fn family_of_gcc() {
+		assert_eq!(CompilerFamily::of(&tool("gcc", "x86_64-unknown-linux-gnu")), Some(CompilerFamily::Gnu));
+	}
+
+	#[test]
+	#[ignore = "needs clang"]
+	fn family_of_clang_for_msvc() {
+		// The GCC-like driver keeps its flags even when targeting the MSVC ABI.
+		let family = CompilerFamily::of(&tool("clang", "x86_64-pc-windows-msvc"));
+		assert_eq!(family, Some(CompilerFamily::Clang));
+		let standards = Standards {
+			clang: Some("gnu11"),
+			msvc: Some("c11"),
+			..Standards::default()
+		};
+		assert_eq!(family.and_then(move |family| standards.select(family)), Some("gnu11"));
+	}
+}
@@ -0,0 +1,119 @@
+//! Generation of a finalized `luaconf.h` with all settings of a build baked in.
+
+use ::std::fmt::Write;
+
+use crate::header::object_define;
+
+/// Definition of a C macro, where a value of `None` defines it as `1`,
+/// like a command-line definition would.
+pub type Define = (String, Option<String>);
+
+/// Render a finalized `luaconf.h` based on the `original` contents.
+/// 
+/// `defines` are placed at the start of the header,
+/// as if they were defined on the command line.
+/// `overrides` replace every unconditional object-like definition of the same macros in the header,
+/// and the ones that aren't defined there are appended to the end of it.
+pub fn render(original: &str, defines: &[Define], overrides: &[Define]) -> String {
+	let mut out = String::with_capacity(original.len() + 1024);
+	out.push_str("/* Generated by lunka-src. */\n");
+
+	let defines = last_of_each(defines);
+	let overrides = last_of_each(overrides);
+	let lines: Vec<&str> = original.lines().collect();
+	let guard = lines.iter().position(move |line| matches!(object_define(line), Some(("luaconf_h", _))));
+	let guard_end = lines.iter().rposition(move |line| line.trim_start().starts_with("#endif"));
+
+	let mut overridden = vec![false; overrides.len()];
+	let mut continued = false;
+	for (i, line) in lines.iter().enumerate() {
+		if guard.is_none() && i == 0 {
+			push_defines(&mut out, &defines);
+		}
+		if guard_end == Some(i) {
+			push_epilogue(&mut out, &overrides, &overridden);
+		}
+
+		if continued {
+			continued = line.ends_with('\\');
+			continue
+		}
+
+		let index = object_define(line).and_then(|(name, _)| overrides.iter().position(move |(o, _)| o == name));
+		if let Some(index) = index {
+			overridden[index] = true;
+			push_define(&mut out, overrides[index]);
+			continued = line.ends_with('\\');
+		} else {
+			out.push_str(line);
+			out.push('\n');
+		}
+
+		if guard == Some(i) {
+			push_defines(&mut out, &defines);
+		}
+	}
+	if guard_end.is_none() {
+		push_epilogue(&mut out, &overrides, &overridden);
+	}
+
+	out
+}
+
+/// Splice the contents of a `local` configuration header into a `rendered` `luaconf.h`,
+/// right before the end of its include guard,
+/// so that they come after every other definition in it.
+pub fn splice_local(rendered: &str, local: &str) -> String {
+	let lines: Vec<&str> = rendered.lines().collect();
+	let guard_end = lines.iter().rposition(move |line| line.trim_start().starts_with("#endif")).unwrap_or(lines.len());
+	let mut out = String::with_capacity(rendered.len() + local.len() + 64);
+	for line in lines[..guard_end].iter() {
+		out.push_str(line);
+		out.push('\n');
+	}
+	out.push_str("\n/* Local configuration. */\n");
+	out.push_str(local);
+	if !local.ends_with('\n') {
+		out.push('\n');
+	}
+	out.push('\n');
+	for line in lines[guard_end..].iter() {
+		out.push_str(line);
+		out.push('\n');
+	}
+	out
+}
+
+/// Collect the last definition of each macro in `defines`,
+/// since later definitions take precedence.
+fn last_of_each(defines: &[Define]) -> Vec<&Define> {
+	defines.iter().enumerate()
+		.filter(move |(i, (name, _))| defines[i + 1..].iter().all(move |(later, _)| later != name))
+		.map(move |(_, define)| define)
+		.collect()
+}
+
+fn push_define(out: &mut String, (name, value): &Define) {
+	let _ = writeln!(out, "#define {name} {}", value.as_deref().unwrap_or("1"));
+}
+
+fn push_defines(out: &mut String, defines: &[&Define]) {
+	out.push_str("\n/* Definitions set by lunka-src. */\n");
+	for define in defines {
+		push_define(out, define);
+	}
+	out.push('\n');
+}
+
+fn push_epilogue(out: &mut String, overrides: &[&Define], overridden: &[bool]) {
+	if overridden.iter().all(move |&o| o) {
+		return
+	}
+	out.push_str("\n/* Overrides set by lunka-src. */\n");
+	for (define, _) in overrides.iter().zip(overridden).filter(move |(_, o)| !**o) {
+		let name = define.0.split_once('(').map_or(define.0.as_str(), move |(name, _)| name);
+		let _ = writeln!(out, "#undef {name}");
+		push_define(out, define);
+	}
+	out.push('\n');
+}
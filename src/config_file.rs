@@ -0,0 +1,167 @@
+use ::serde::Deserialize;
+use ::std::{
+	collections::BTreeMap,
+	fmt::Display,
+	io::{
+		Error as IoError, ErrorKind as IoErrorKind,
+	},
+	path::Path,
+};
+use ::toml::{
+	Table, Value,
+};
+
+use crate::{
+	Build, CcError, LuaConf,
+	platforms::{
+		from_current_triple, from_name,
+	},
+};
+
+/// Contents of a configuration file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+	platform: Option<String>,
+	#[serde(default)]
+	defines: BTreeMap<String, DefineValue>,
+	#[serde(default)]
+	compat: Compat,
+	#[serde(default)]
+	opt: Opt,
+	/// Fields of [`LuaConf`], which are converted to strings if they're integers before being deserialized.
+	lua_conf: Option<Table>,
+}
+
+/// Value of a define in the `[defines]` table.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DefineValue {
+	Flag(bool),
+	String(String),
+	Integer(i64),
+}
+
+/// Settings of the `[compat]` table.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Compat {
+	lua_5_3: bool,
+	math_lib: bool,
+	lt_le: bool,
+}
+
+/// Settings of the `[opt]` table.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Opt {
+	level: Option<u32>,
+	debug_info: Option<bool>,
+}
+
+/// Set up a [`Build`] as described by the configuration file `text` at `path`.
+pub(crate) fn build_from(path: &Path, text: &str) -> Result<Build, CcError> {
+	let invalid = move |message: &dyn Display| -> IoError {
+		IoError::new(IoErrorKind::InvalidData, format!("{}: {message}", path.display()))
+	};
+	let config: ConfigFile = ::toml::from_str(text).map_err(move |e| invalid(&e))?;
+
+	let mut build = match config.platform.as_deref() {
+		Some(name) => match from_name(name) {
+			Some(platform) => Build::try_new(platform)?,
+			None => return Err(invalid(&format!("unknown platform {name:?}")).into()),
+		},
+		None => match from_current_triple() {
+			Ok(platform) => Build::try_new(platform)?,
+			Err(e) => return Err(IoError::new(IoErrorKind::Unsupported, e.to_string()).into()),
+		},
+	};
+
+	for (name, value) in config.defines.iter() {
+		match value {
+			DefineValue::Flag(true) => {
+				build.define(name);
+			}
+			DefineValue::Flag(false) => {}
+			DefineValue::String(value) => {
+				build.define_value(name, value);
+			}
+			DefineValue::Integer(value) => {
+				build.define_value(name, &value.to_string());
+			}
+		}
+	}
+	if config.compat.lua_5_3 {
+		build.compat_lua_5_3();
+	}
+	if config.compat.math_lib {
+		build.compat_math_lib();
+	}
+	if config.compat.lt_le {
+		build.compat_lt_le();
+	}
+	match config.opt.level {
+		Some(level @ 0..=3) => {
+			build.opt_level(level);
+		}
+		Some(_) => return Err(invalid(&"opt.level must be an integer from 0 to 3").into()),
+		None => {}
+	}
+	if let Some(debug_info) = config.opt.debug_info {
+		build.debug_info(debug_info);
+	}
+
+	if let Some(mut table) = config.lua_conf {
+		for (_, value) in table.iter_mut() {
+			if let Value::Integer(n) = value {
+				*value = Value::String(n.to_string());
+			}
+		}
+		let lua_conf: LuaConf<String> = Value::Table(table).try_into()
+			.map_err(move |e| invalid(&format!("in lua_conf: {e}")))?;
+		build.try_lua_conf(&lua_conf)?;
+	}
+	Ok(build)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn build(text: &str) -> Result<Build, CcError> {
+		build_from(Path::new("lunka.toml"), text)
+	}
+
+	#[test]
+	fn accepts_every_table() {
+		let text = "\
+			platform = \"Linux\"\n\
+			[defines]\n\
+			LUA_USE_APICHECK = true\n\
+			LUA_IDSIZE = 80\n\
+			[compat]\n\
+			lua_5_3 = true\n\
+			[opt]\n\
+			level = 2\n\
+			[lua_conf]\n\
+			buffer_size = 256\n\
+			kcontext = \"ptrdiff_t\"\n\
+			no_number_to_string = true\n\
+			integer_type = \"long long\"\n\
+		";
+		assert!(build(text).is_ok());
+	}
+
+	#[test]
+	fn rejects_unknown_settings() {
+		for text in ["colour = 1", "[compat]\nlua_5_2 = true", "[lua_conf]\nbuffer = 256", "[opt]\nlevel = 4"] {
+			let error = build(&format!("platform = \"Linux\"\n{text}")).err().unwrap_or_else(|| panic!("{text:?} was accepted"));
+			assert!(error.to_string().contains("lunka.toml"), "{error}");
+		}
+	}
+
+	#[test]
+	fn rejects_unknown_platforms() {
+		assert!(build("platform = \"Plan 9\"").is_err());
+	}
+}
@@ -0,0 +1,66 @@
+use ::std::{
+	env::{
+		var, var_os, vars_os,
+	},
+	ffi::OsString,
+};
+
+/// Policy for compiler flags given through the environment,
+/// such as `CFLAGS`, `TARGET_CFLAGS` or `CFLAGS_<target>`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EnvFlagsPolicy {
+	/// Pass the flags after all others, as `cc` does by default,
+	/// so that they override anything set by the builder.
+	#[default]
+	Respect,
+	/// Don't pass the flags at all.
+	Ignore,
+	/// Pass the flags before the ones that the builder adds when compiling,
+	/// instead of after them,
+	/// so that settings such as the debug level or visibility take precedence over them.
+	Prepend,
+}
+
+/// Return `true` if `name` is one of the variables that `cc` reads compiler flags from.
+pub fn is_flags_var(name: &str) -> bool {
+	["CFLAGS", "CXXFLAGS"].into_iter().any(move |base| {
+		name == base
+			|| name.strip_prefix(base).is_some_and(move |rest| rest.starts_with('_'))
+			|| name.strip_suffix(base).is_some_and(move |rest| rest == "TARGET_" || rest == "HOST_")
+	})
+}
+
+/// Return the environment without the variables for which [`is_flags_var`] returns `true`.
+pub fn vars_without_flags() -> Vec<(OsString, OsString)> {
+	vars_os().filter(move |(name, _)| !name.to_str().is_some_and(is_flags_var)).collect()
+}
+
+/// Return the compiler flags from the environment,
+/// which are read from `CXXFLAGS` if `cpp` is `true` and from `CFLAGS` otherwise.
+/// 
+/// Like with the other variables that `cc` looks up,
+/// only the first of the variables of [`target_var_names`] that is set is used.
+pub fn compiler_flags(cpp: bool) -> Vec<String> {
+	let base = if cpp { "CXXFLAGS" } else { "CFLAGS" };
+	target_var(base)
+		.map(move |flags| flags.split_ascii_whitespace().map(move |s| s.to_owned()).collect())
+		.unwrap_or_default()
+}
+
+/// Return the names of the variables that `cc` looks up for `base`, such as `CFLAGS`,
+/// from the most specific to the least specific one.
+pub fn target_var_names(base: &str) -> [String; 4] {
+	let target = var("TARGET").unwrap_or_default();
+	let kind = if var_os("HOST") == var_os("TARGET") { "HOST" } else { "TARGET" };
+	[
+		format!("{base}_{target}"),
+		format!("{base}_{}", target.replace(['-', '.'], "_")),
+		format!("{kind}_{base}"),
+		base.to_owned(),
+	]
+}
+
+/// Return the value of the first of the variables of [`target_var_names`] that is set.
+pub fn target_var(base: &str) -> Option<String> {
+	target_var_names(base).iter().find_map(move |name| var(name).ok())
+}
@@ -0,0 +1,51 @@
+use ::std::{
+	env::var_os,
+	path::PathBuf,
+};
+
+/// Structure that describes a prebuilt Lua library which can be linked to
+/// instead of compiling Lua from source.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct External {
+	/// Directory containing the prebuilt library.
+	pub lib_dir: PathBuf,
+	/// Directory containing the headers for the prebuilt library.
+	pub include_dir: PathBuf,
+	/// Name of the library to link to,
+	/// or `None` to use the name that was passed to [`Build::compile`](crate::Build::compile).
+	pub lib_name: Option<String>,
+}
+
+impl External {
+	/// Environment variable for [`External::lib_dir`].
+	pub const LIB_DIR_VAR: &str = "LUA_LIB_DIR";
+	/// Environment variable for [`External::include_dir`].
+	pub const INCLUDE_DIR_VAR: &str = "LUA_INCLUDE_DIR";
+	/// Environment variable for [`External::lib_name`].
+	pub const LIB_NAME_VAR: &str = "LUA_LIB_NAME";
+
+	/// Describe a prebuilt Lua library with environment variables,
+	/// returning `None` if either [`External::LIB_DIR_VAR`] or [`External::INCLUDE_DIR_VAR`] isn't set.
+	pub fn from_env() -> Option<Self> {
+		let lib_dir = var_os(Self::LIB_DIR_VAR)?;
+		let include_dir = var_os(Self::INCLUDE_DIR_VAR)?;
+		let lib_name = var_os(Self::LIB_NAME_VAR).and_then(move |s| s.into_string().ok());
+		Some(Self {
+			lib_dir: lib_dir.into(),
+			include_dir: include_dir.into(),
+			lib_name,
+		})
+	}
+
+	/// Print the Cargo metadata required to link to this library,
+	/// using `default_name` if [`External::lib_name`] is `None`.
+	/// 
+	/// The include directory is emitted as `cargo:include`,
+	/// which is available to dependent crates if the package has a `links` key.
+	pub fn emit_cargo_metadata(&self, default_name: &str) {
+		let name = self.lib_name.as_deref().unwrap_or(default_name);
+		println!("cargo:rustc-link-search=native={}", self.lib_dir.display());
+		println!("cargo:rustc-link-lib={name}");
+		println!("cargo:include={}", self.include_dir.display());
+	}
+}
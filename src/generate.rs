@@ -0,0 +1,827 @@
+use ::cc::Tool;
+use ::std::{
+	ffi::OsStr,
+	fmt::Write,
+	fs::{
+		read, write,
+	},
+	io::Error as IoError,
+	path::{
+		Path, PathBuf,
+	},
+};
+
+use crate::{
+	LockImpl, StdLib, TimeHooks, symbols,
+};
+
+/// Definition of `LUNKA_C`, which marks declarations of functions that are defined elsewhere by the user,
+/// so that they keep C linkage when the sources are compiled as C++.
+const C_LINKAGE: &str = "\
+	#if !defined(LUNKA_C)\n\
+	#if defined(__cplusplus)\n\
+	#define LUNKA_C extern \"C\"\n\
+	#else\n\
+	#define LUNKA_C extern\n\
+	#endif\n\
+	#endif\n";
+
+/// Write a generated file named `name` with `contents` into `dir`,
+/// leaving it untouched if it already has the same contents so that its modification time is preserved.
+pub fn write_file<C: AsRef<[u8]>>(dir: &Path, name: &str, contents: C) -> Result<PathBuf, IoError> {
+	let path = dir.join(name);
+	let contents = contents.as_ref();
+	if read(&path).ok().as_deref() != Some(contents) {
+		write(&path, contents)?;
+	}
+	Ok(path)
+}
+
+/// Render a header that makes `lua_lock` and `lua_unlock` call the functions of [`lock_source`].
+pub fn lock_header() -> String {
+	String::from(
+		"/* Generated by lunka-src. */\n\
+		#ifndef LUNKA_LOCK_H\n\
+		#define LUNKA_LOCK_H\n\
+		\n\
+		#if defined(__GNUC__) && !defined(_WIN32) && !defined(__CYGWIN__)\n\
+		#define LUNKA_LOCK_API __attribute__((visibility(\"hidden\"))) extern\n\
+		#else\n\
+		#define LUNKA_LOCK_API extern\n\
+		#endif\n\
+		\n\
+		struct lua_State;\n\
+		LUNKA_LOCK_API void lunka_lock (struct lua_State *L);\n\
+		LUNKA_LOCK_API void lunka_unlock (struct lua_State *L);\n\
+		\n\
+		#define lua_lock(L) lunka_lock(L)\n\
+		#define lua_unlock(L) lunka_unlock(L)\n\
+		\n\
+		#endif\n"
+	)
+}
+
+/// Render a C source file that implements the functions of [`lock_header`] with one global `lock`.
+pub fn lock_source(lock: LockImpl) -> String {
+	let implementation = match lock {
+		LockImpl::Pthread => "\
+			#include <pthread.h>\n\
+			\n\
+			static pthread_mutex_t lunka_mutex = PTHREAD_MUTEX_INITIALIZER;\n\
+			\n\
+			void lunka_lock (struct lua_State *L) {\n\
+			\t(void)L;\n\
+			\tpthread_mutex_lock(&lunka_mutex);\n\
+			}\n\
+			\n\
+			void lunka_unlock (struct lua_State *L) {\n\
+			\t(void)L;\n\
+			\tpthread_mutex_unlock(&lunka_mutex);\n\
+			}\n",
+		LockImpl::SrwLock => "\
+			#include <windows.h>\n\
+			\n\
+			static SRWLOCK lunka_srwlock = SRWLOCK_INIT;\n\
+			\n\
+			void lunka_lock (struct lua_State *L) {\n\
+			\t(void)L;\n\
+			\tAcquireSRWLockExclusive(&lunka_srwlock);\n\
+			}\n\
+			\n\
+			void lunka_unlock (struct lua_State *L) {\n\
+			\t(void)L;\n\
+			\tReleaseSRWLockExclusive(&lunka_srwlock);\n\
+			}\n",
+		LockImpl::CriticalSection => "\
+			#include <windows.h>\n\
+			\n\
+			static CRITICAL_SECTION lunka_section;\n\
+			static volatile LONG lunka_section_state = 0;\n\
+			\n\
+			void lunka_lock (struct lua_State *L) {\n\
+			\t(void)L;\n\
+			\t/* 0 is uninitialized, 1 is being initialized, 2 is ready */\n\
+			\tif (InterlockedCompareExchange(&lunka_section_state, 1, 0) == 0) {\n\
+			\t\tInitializeCriticalSection(&lunka_section);\n\
+			\t\tInterlockedExchange(&lunka_section_state, 2);\n\
+			\t}\n\
+			\twhile (lunka_section_state != 2)\n\
+			\t\tSleep(0);\n\
+			\tEnterCriticalSection(&lunka_section);\n\
+			}\n\
+			\n\
+			void lunka_unlock (struct lua_State *L) {\n\
+			\t(void)L;\n\
+			\tLeaveCriticalSection(&lunka_section);\n\
+			}\n",
+	};
+	format!("/* Generated by lunka-src. */\n{implementation}")
+}
+
+/// Render a header that gives the API of Lua C linkage when its sources are compiled as C++,
+/// as `lua.hpp` does for C++ code that uses it,
+/// and that makes Lua catch only the exceptions that it throws itself.
+pub fn cpp_header() -> String {
+	String::from(
+		"/* Generated by lunka-src. */\n\
+		#ifndef LUNKA_CPP_H\n\
+		#define LUNKA_CPP_H\n\
+		\n\
+		#include \"lprefix.h\"\n\
+		\n\
+		/* every source that this is included in is part of Lua, but only says so after it */\n\
+		#define LUA_LIB\n\
+		\n\
+		extern \"C\" {\n\
+		#include \"lua.h\"\n\
+		#include \"lualib.h\"\n\
+		#include \"lauxlib.h\"\n\
+		}\n\
+		\n\
+		/* unlike the default, let foreign exceptions such as Rust panics through */\n\
+		#if !defined(LUAI_THROW) && !defined(LUA_USE_LONGJMP)\n\
+		#define LUAI_THROW(L,c) throw(c)\n\
+		#define LUAI_TRY(L,c,a) \\\n\
+		\ttry { a } catch(struct lua_longjmp *) { if ((c)->status == 0) (c)->status = -1; }\n\
+		#define luai_jmpbuf int\n\
+		#endif\n\
+		\n\
+		#endif\n"
+	)
+}
+
+/// Render a header that makes Lua write its output with `write` and its error messages with `write_error`,
+/// both of which are C functions `void f(const char *s, size_t len)`.
+pub fn output_header(write: Option<&str>, write_error: Option<&str>) -> String {
+	let mut out = String::from(
+		"/* Generated by lunka-src. */\n\
+		#ifndef LUNKA_OUTPUT_H\n\
+		#define LUNKA_OUTPUT_H\n\
+		\n\
+		#include <stddef.h>\n"
+	);
+	out.push_str(C_LINKAGE);
+	if let Some(write) = write {
+		let _ = write!(
+			out,
+			"\n\
+			LUNKA_C void {write} (const char *s, size_t len);\n\
+			#define lua_writestring(s,l) {write}((s), (l))\n\
+			#define lua_writeline() {write}(\"\\n\", 1)\n"
+		);
+	}
+	if let Some(write_error) = write_error {
+		let _ = write!(
+			out,
+			"\n\
+			#if defined(__GNUC__) && !defined(_WIN32) && !defined(__CYGWIN__)\n\
+			#define LUNKA_OUTPUT_API __attribute__((visibility(\"hidden\"))) extern\n\
+			#else\n\
+			#define LUNKA_OUTPUT_API extern\n\
+			#endif\n\
+			\n\
+			LUNKA_C void {write_error} (const char *s, size_t len);\n\
+			LUNKA_OUTPUT_API void lunka_writestringerror (const char *fmt, const char *p);\n\
+			#define lua_writestringerror(s,p) lunka_writestringerror((s), (p))\n"
+		);
+	}
+	out.push_str("\n#endif\n");
+	out
+}
+
+/// Render a C source file that implements `lunka_writestringerror` of [`output_header`],
+/// which formats the `%s` in its messages itself and passes the result to `write_error` in pieces.
+pub fn output_source(write_error: &str) -> String {
+	format!(
+		"/* Generated by lunka-src. */\n\
+		#include <string.h>\n\
+		\n\
+		void lunka_writestringerror (const char *fmt, const char *p) {{\n\
+		\tconst char *run = fmt;\n\
+		\twhile (*fmt != '\\0') {{\n\
+		\t\tif (fmt[0] == '%' && (fmt[1] == 's' || fmt[1] == '%')) {{\n\
+		\t\t\tif (fmt > run)\n\
+		\t\t\t\t{write_error}(run, (size_t)(fmt - run));\n\
+		\t\t\tif (fmt[1] == 's')\n\
+		\t\t\t\t{write_error}(p, strlen(p));\n\
+		\t\t\telse\n\
+		\t\t\t\t{write_error}(\"%\", 1);\n\
+		\t\t\tfmt += 2;\n\
+		\t\t\trun = fmt;\n\
+		\t\t}}\n\
+		\t\telse\n\
+		\t\t\tfmt++;\n\
+		\t}}\n\
+		\tif (fmt > run)\n\
+		\t\t{write_error}(run, (size_t)(fmt - run));\n\
+		}}\n"
+	)
+}
+
+/// Render a header that renames every public API symbol to have `prefix` prepended to it.
+pub fn prefix_header(prefix: &str) -> String {
+	let mut out = String::from("/* Generated by lunka-src. */\n#ifndef LUNKA_PREFIX_H\n#define LUNKA_PREFIX_H\n\n");
+	for symbol in symbols::all() {
+		let _ = writeln!(out, "#define {symbol} {prefix}{symbol}");
+	}
+	out.push_str("\n#endif\n");
+	out
+}
+
+/// Render a header that defines each of `defines` as an object-like macro.
+pub fn defines_header(defines: &[(&str, &str)]) -> String {
+	let mut out = String::from("/* Generated by lunka-src. */\n");
+	for (name, value) in defines {
+		let _ = writeln!(out, "#undef {name}\n#define {name} {value}");
+	}
+	out
+}
+
+/// Render a C source file that compiles the source file at `wrapped`
+/// and defines a `lunka_provenance` string with the contents `note`,
+/// which is kept even if it's unreferenced where the compiler supports it.
+pub fn provenance_source(wrapped: &Path, note: &str) -> String {
+	let path = wrapped.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+	format!(
+		"/* Generated by lunka-src. */\n\
+		#include \"{path}\"\n\
+		\n\
+		#if defined(__has_attribute)\n\
+		#if __has_attribute(retain)\n\
+		#define LUNKA_RETAIN __attribute__((used, retain))\n\
+		#endif\n\
+		#endif\n\
+		#if !defined(LUNKA_RETAIN) && defined(__GNUC__)\n\
+		#define LUNKA_RETAIN __attribute__((used))\n\
+		#elif !defined(LUNKA_RETAIN)\n\
+		#define LUNKA_RETAIN\n\
+		#endif\n\
+		\n\
+		LUNKA_RETAIN const char lunka_provenance[] = \"{note}\";\n"
+	)
+}
+
+/// Render a C source file that compiles the source file at `wrapped`, which should be `lstate.c`,
+/// with the default parameters of the garbage collector from `lgc.h` replaced by `params`.
+pub fn gc_params_source(wrapped: &Path, params: &[(&str, String)]) -> String {
+	let path = wrapped.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+	let mut out = String::from(
+		"/* Generated by lunka-src. */\n\
+		#define lstate_c\n\
+		#define LUA_CORE\n\
+		#include \"lprefix.h\"\n\
+		#include \"lgc.h\"\n\
+		\n"
+	);
+	for (name, value) in params {
+		let _ = writeln!(out, "#undef {name}\n#define {name} {value}");
+	}
+	let _ = writeln!(out, "\n#include \"{path}\"");
+	out
+}
+
+/// Functions of the `debug` library that are replaced by [`safe_debug_source`],
+/// as they can break the invariants of other code or reach values that are meant to be hidden.
+const UNSAFE_DEBUG_FUNCTIONS: &[&str] = &[
+	"debug", "getlocal", "getmetatable", "getregistry", "getupvalue", "getuservalue", "sethook",
+	"setlocal", "setmetatable", "setupvalue", "setuservalue", "upvalueid", "upvaluejoin",
+];
+
+/// Render a C source file that compiles the `debug` library at `wrapped`
+/// with the functions in [`UNSAFE_DEBUG_FUNCTIONS`] replaced by ones that raise errors.
+pub fn safe_debug_source(wrapped: &Path) -> String {
+	let path = wrapped.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+	let mut names = String::new();
+	for name in UNSAFE_DEBUG_FUNCTIONS {
+		let _ = write!(names, "\"{name}\", ");
+	}
+	format!(
+		"/* Generated by lunka-src. */\n\
+		#define ldblib_c\n\
+		#define LUA_LIB\n\
+		#include \"lprefix.h\"\n\
+		#include \"lua.h\"\n\
+		#include \"lauxlib.h\"\n\
+		\n\
+		static int lunka_db_disabled (lua_State *L) {{\n\
+		\treturn luaL_error(L, \"debug.%s is disabled in this build\", lua_tostring(L, lua_upvalueindex(1)));\n\
+		}}\n\
+		\n\
+		static void lunka_newlib_safe (lua_State *L, const luaL_Reg *l) {{\n\
+		\tstatic const char *const disabled[] = {{{names}NULL}};\n\
+		\tconst char *const *name;\n\
+		\tluaL_checkversion(L);\n\
+		\tlua_newtable(L);\n\
+		\tluaL_setfuncs(L, l, 0);\n\
+		\tfor (name = disabled; *name != NULL; name++) {{\n\
+		\t\tlua_pushstring(L, *name);\n\
+		\t\tlua_pushcclosure(L, lunka_db_disabled, 1);\n\
+		\t\tlua_setfield(L, -2, *name);\n\
+		\t}}\n\
+		}}\n\
+		\n\
+		#undef luaL_newlib\n\
+		#define luaL_newlib(L, l) lunka_newlib_safe(L, l)\n\
+		#include \"{path}\"\n"
+	)
+}
+
+/// Render a C source file that compiles the `package` library at `wrapped`
+/// with C modules loaded by `LoadPackagedLibrary`, for Universal Windows Platform apps,
+/// and with the environment variables for the search paths ignored, as apps have no environment.
+pub fn uwp_loadlib_source(wrapped: &Path) -> String {
+	let path = wrapped.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+	format!(
+		"/* Generated by lunka-src. */\n\
+		#define loadlib_c\n\
+		#define LUA_LIB\n\
+		#include \"lprefix.h\"\n\
+		#include <stdlib.h>\n\
+		#include <string.h>\n\
+		#include <windows.h>\n\
+		\n\
+		static HMODULE lunka_load_packaged (const char *path) {{\n\
+		\tchar dir[MAX_PATH + 1];\n\
+		\tWCHAR wide[MAX_PATH + 1];\n\
+		\tDWORD n = GetModuleFileNameA(NULL, dir, MAX_PATH + 1);\n\
+		\tchar *sep = (n == 0 || n == MAX_PATH + 1) ? NULL : strrchr(dir, '\\\\');\n\
+		\t/* packaged libraries are named relative to the package root */\n\
+		\tif (sep != NULL && _strnicmp(path, dir, (size_t)(sep - dir + 1)) == 0)\n\
+		\t\tpath += sep - dir + 1;\n\
+		\tif (MultiByteToWideChar(CP_ACP, 0, path, -1, wide, MAX_PATH + 1) == 0)\n\
+		\t\treturn NULL;\n\
+		\treturn LoadPackagedLibrary(wide, 0);\n\
+		}}\n\
+		\n\
+		#define LoadLibraryExA(path, file, flags) ((void)(file), (void)(flags), lunka_load_packaged(path))\n\
+		#define getenv(name) ((void)(name), (char *)NULL)\n\
+		#include \"{path}\"\n"
+	)
+}
+
+/// Render a C source file that compiles the source file at `wrapped`, which should be `lauxlib.c`,
+/// with the default allocator of `luaL_newstate` calling `allocator` instead of `realloc` and `free`,
+/// and with the states it creates being limited to `memory_limit` bytes of memory.
+/// 
+/// `symbol_prefix` is the prefix of [`prefix_header`], if any,
+/// whose renaming of `lua_newstate` is restored after it's hooked.
+pub fn lauxlib_source(wrapped: &Path, allocator: Option<&str>, memory_limit: Option<u64>, symbol_prefix: Option<&str>) -> String {
+	let path = wrapped.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+	let mut out = String::from(
+		"/* Generated by lunka-src. */\n\
+		#define lauxlib_c\n\
+		#define LUA_LIB\n\
+		#include \"lprefix.h\"\n\
+		#include <stddef.h>\n\
+		#include <stdlib.h>\n\
+		#include \"lua.h\"\n"
+	);
+	if let Some(allocator) = allocator {
+		out.push_str(C_LINKAGE);
+		let _ = write!(
+			out,
+			"\n\
+			LUNKA_C void *{allocator} (void *ud, void *ptr, size_t osize, size_t nsize);\n\
+			\n\
+			/* only used by `l_alloc`, where `ud` and `osize` are in scope */\n\
+			#define free(p) ((void){allocator}(ud, (p), osize, 0))\n\
+			#define realloc(p, n) {allocator}(ud, (p), osize, (n))\n"
+		);
+	}
+	if memory_limit.is_some() {
+		out.push_str(
+			"\n\
+			static lua_State *lunka_limited_newstate (lua_Alloc f, void *ud);\n\
+			#undef lua_newstate\n\
+			#define lua_newstate(f, ud) lunka_limited_newstate((f), (ud))\n"
+		);
+	}
+	let _ = writeln!(out, "#include \"{path}\"");
+	if let Some(limit) = memory_limit {
+		out.push_str("#undef lua_newstate\n");
+		if let Some(prefix) = symbol_prefix {
+			let _ = writeln!(out, "#define lua_newstate {prefix}lua_newstate");
+		}
+		let _ = write!(
+			out,
+			"\n\
+			typedef struct LunkaLimit {{\n\
+			\tlua_Alloc f;\n\
+			\tvoid *ud;\n\
+			\tsize_t used;\n\
+			\tint open;\n\
+			}} LunkaLimit;\n\
+			\n\
+			static void *lunka_limited_alloc (void *ud, void *ptr, size_t osize, size_t nsize) {{\n\
+			\tLunkaLimit *limit = (LunkaLimit *)ud;\n\
+			\tsize_t old = (ptr == NULL) ? 0 : osize;\n\
+			\tvoid *block;\n\
+			\tif (nsize > old && nsize - old > (size_t){limit}u - limit->used)\n\
+			\t\treturn NULL;\n\
+			\tblock = limit->f(limit->ud, ptr, osize, nsize);\n\
+			\tif (block == NULL && nsize != 0)\n\
+			\t\treturn NULL;\n\
+			\tlimit->used = limit->used - old + nsize;\n\
+			\t/* the state has been closed once all of its memory has been freed */\n\
+			\tif (limit->used == 0 && limit->open)\n\
+			\t\t(void)limit->f(limit->ud, limit, sizeof(LunkaLimit), 0);\n\
+			\treturn block;\n\
+			}}\n\
+			\n\
+			static lua_State *lunka_limited_newstate (lua_Alloc f, void *ud) {{\n\
+			\tlua_State *L;\n\
+			\tLunkaLimit *limit = (LunkaLimit *)f(ud, NULL, 0, sizeof(LunkaLimit));\n\
+			\tif (limit == NULL)\n\
+			\t\treturn NULL;\n\
+			\tlimit->f = f;\n\
+			\tlimit->ud = ud;\n\
+			\tlimit->used = 0;\n\
+			\tlimit->open = 0;\n\
+			\tL = lua_newstate(lunka_limited_alloc, limit);\n\
+			\tif (L == NULL)\n\
+			\t\t(void)f(ud, limit, sizeof(LunkaLimit), 0);\n\
+			\telse\n\
+			\t\tlimit->open = 1;\n\
+			\treturn L;\n\
+			}}\n"
+		);
+	}
+	out
+}
+
+/// Render a C source file that compiles the source file at `wrapped`, which should be `loslib.c`,
+/// with the functions of `<time.h>` that are set in `hooks` replaced by them,
+/// and with `os.getenv` always returning `nil` if `no_getenv` is `true`.
+pub fn loslib_source(wrapped: &Path, hooks: Option<&TimeHooks<String>>, no_getenv: bool) -> String {
+	let path = wrapped.display().to_string().replace('\\', "\\\\").replace('"', "\\\"");
+	let mut out = String::from(
+		"/* Generated by lunka-src. */\n\
+		#define loslib_c\n\
+		#define LUA_LIB\n\
+		#include \"lprefix.h\"\n\
+		#include <stdlib.h>\n\
+		#include <time.h>\n\
+		#include \"luaconf.h\"\n\
+		\n"
+	);
+	out.push_str(C_LINKAGE);
+	if no_getenv {
+		// after `<stdlib.h>`, so that its declaration of `getenv` is left alone
+		out.push_str("#define getenv(name) ((void)(name), (char *)NULL)\n");
+	}
+	let no_hooks = TimeHooks::default();
+	let hooks = hooks.unwrap_or(&no_hooks);
+	if let Some(time) = hooks.time.as_deref() {
+		let _ = writeln!(out, "LUNKA_C time_t {time} (time_t *t);\n#define time(t) {time}(t)");
+	}
+	if let Some(clock) = hooks.clock.as_deref() {
+		let _ = writeln!(
+			out,
+			"LUNKA_C double {clock} (void);\n\
+			#undef CLOCKS_PER_SEC\n\
+			#define CLOCKS_PER_SEC 1\n\
+			#define clock() {clock}()"
+		);
+	}
+	if let Some(mktime) = hooks.mktime.as_deref() {
+		let _ = writeln!(out, "LUNKA_C time_t {mktime} (struct tm *tm);\n#define mktime(tm) {mktime}(tm)");
+	}
+	if hooks.gmtime.is_some() || hooks.localtime.is_some() {
+		// `loslib.c` only provides these two together
+		for (name, hook, fallback) in [("gmtime", &hooks.gmtime, "gmtime"), ("localtime", &hooks.localtime, "localtime")] {
+			match hook.as_deref() {
+				Some(hook) => {
+					let _ = writeln!(out, "LUNKA_C struct tm *{hook} (const time_t *t, struct tm *result);\n#define l_{name}(t,r) {hook}(t,r)");
+				}
+				None => {
+					let _ = writeln!(
+						out,
+						"#if defined(LUA_USE_POSIX)\n\
+						#define l_{name}(t,r) {fallback}_r(t,r)\n\
+						#else\n\
+						#define l_{name}(t,r) ((void)(r)->tm_sec, {fallback}(t))\n\
+						#endif"
+					);
+				}
+			}
+		}
+	}
+	let _ = writeln!(out, "\n#include \"{path}\"");
+	out
+}
+
+/// Write a script into `dir` that runs the compiler `tool` through `launcher`,
+/// such as `distcc` or `icecc`, returning the path to the script.
+pub fn launcher_script(dir: &Path, launcher: &OsStr, tool: &Tool) -> Result<PathBuf, IoError> {
+	let command = [launcher, tool.path().as_os_str()];
+
+	if cfg!(windows) {
+		let mut script = String::from("@echo off\r\n");
+		for part in command {
+			let _ = write!(script, "\"{}\" ", part.to_string_lossy());
+		}
+		script.push_str("%*\r\n");
+		write_file(dir, "lunka-launcher.cmd", script)
+	} else {
+		let mut script = String::from("#!/bin/sh\nexec");
+		for part in command {
+			let _ = write!(script, " '{}'", part.to_string_lossy().replace('\'', "'\\''"));
+		}
+		script.push_str(" \"$@\"\n");
+		let path = write_file(dir, "lunka-launcher.sh", script)?;
+		#[cfg(unix)]
+		{
+			use ::std::{
+				fs::{
+					metadata, set_permissions,
+				},
+				os::unix::fs::PermissionsExt,
+			};
+			let mut permissions = metadata(&path)?.permissions();
+			permissions.set_mode(0o755);
+			set_permissions(&path, permissions)?;
+		}
+		Ok(path)
+	}
+}
+
+/// Render a `linit.c` that makes `luaL_openlibs` open only the libraries in `libs`,
+/// and add each of the `preloads`, which are `(module name, open function)` pairs, to `package.preload`.
+/// If `builtin_searcher` is `true`, the searcher of [`builtin_chunks`] is also added to `package.searchers`.
+pub fn linit(libs: &[StdLib], preloads: &[(&str, &str)], builtin_searcher: bool) -> String {
+	let mut out = String::from(concat!(
+		"/* Generated by lunka-src. */\n",
+		"#define linit_c\n#define LUA_LIB\n\n",
+		"#include \"lprefix.h\"\n\n#include <stddef.h>\n\n",
+		"#include \"lua.h\"\n\n#include \"lualib.h\"\n#include \"lauxlib.h\"\n\n",
+	));
+	out.push_str(C_LINKAGE);
+	for (_, open) in preloads {
+		let _ = writeln!(out, "LUNKA_C int {open} (lua_State *L);");
+	}
+	if builtin_searcher {
+		out.push_str("void lunka_add_builtin_searcher (lua_State *L);\n");
+	}
+	out.push_str("\nstatic const luaL_Reg loadedlibs[] = {\n");
+	for lib in StdLib::ALL.into_iter().filter(move |lib| libs.contains(lib)) {
+		let _ = writeln!(out, "  {{{}, {}}},", lib.name_macro(), lib.open_function());
+	}
+	out.push_str("  {NULL, NULL}\n};\n\nstatic const luaL_Reg preloadedlibs[] = {\n");
+	for (name, open) in preloads {
+		let _ = writeln!(out, "  {{\"{}\", {open}}},", name.replace('\\', "\\\\").replace('"', "\\\""));
+	}
+	out.push_str(concat!(
+		"  {NULL, NULL}\n};\n\n",
+		"LUALIB_API void luaL_openlibs (lua_State *L) {\n",
+		"  const luaL_Reg *lib;\n",
+		"  for (lib = loadedlibs; lib->func; lib++) {\n",
+		"    luaL_requiref(L, lib->name, lib->func, 1);\n",
+		"    lua_pop(L, 1);\n",
+		"  }\n",
+		"  luaL_getsubtable(L, LUA_REGISTRYINDEX, LUA_PRELOAD_TABLE);\n",
+		"  for (lib = preloadedlibs; lib->func; lib++) {\n",
+		"    lua_pushcfunction(L, lib->func);\n",
+		"    lua_setfield(L, -2, lib->name);\n",
+		"  }\n",
+		"  lua_pop(L, 1);\n",
+	));
+	if builtin_searcher {
+		out.push_str("  lunka_add_builtin_searcher(L);\n");
+	}
+	out.push_str("}\n");
+	out
+}
+
+/// Render a C source file that embeds `chunks`, which are `(module name, contents)` pairs,
+/// and defines `lunka_add_builtin_searcher`,
+/// which adds a searcher for them to `package.searchers` right after the one for `package.preload`.
+pub fn builtin_chunks(chunks: &[(&str, Vec<u8>)]) -> String {
+	let mut out = String::from(concat!(
+		"/* Generated by lunka-src. */\n",
+		"#define LUA_LIB\n\n",
+		"#include \"lprefix.h\"\n\n#include <stddef.h>\n#include <string.h>\n\n",
+		"#include \"lua.h\"\n\n#include \"lualib.h\"\n#include \"lauxlib.h\"\n\n",
+	));
+	for (i, (_, contents)) in chunks.iter().enumerate() {
+		let _ = write!(out, "static const unsigned char chunk_{i}[] = {{");
+		for (j, byte) in contents.iter().enumerate() {
+			out.push_str(if j % 16 == 0 { "\n  " } else { " " });
+			let _ = write!(out, "{byte},");
+		}
+		if contents.is_empty() {
+			out.push('0');
+		}
+		out.push_str("\n};\n\n");
+	}
+	out.push_str(concat!(
+		"static const struct {\n",
+		"  const char *name;\n  const char *chunkname;\n  const unsigned char *data;\n  size_t size;\n",
+		"} chunks[] = {\n",
+	));
+	for (i, (name, contents)) in chunks.iter().enumerate() {
+		let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+		let _ = writeln!(out, "  {{\"{name}\", \"={name}\", chunk_{i}, {}}},", contents.len());
+	}
+	out.push_str(concat!(
+		"  {NULL, NULL, NULL, 0}\n};\n\n",
+		"static int searcher_builtin (lua_State *L) {\n",
+		"  const char *name = luaL_checkstring(L, 1);\n",
+		"  size_t i;\n",
+		"  for (i = 0; chunks[i].name != NULL; i++) {\n",
+		"    if (strcmp(chunks[i].name, name) == 0) {\n",
+		"      if (luaL_loadbufferx(L, (const char *)chunks[i].data, chunks[i].size, chunks[i].chunkname, NULL) != LUA_OK)\n",
+		"        return luaL_error(L, \"error loading module '%s' from builtin chunk:\\n\\t%s\", name, lua_tostring(L, -1));\n",
+		"      lua_pushfstring(L, \"builtin:%s\", name);\n",
+		"      return 2;\n",
+		"    }\n",
+		"  }\n",
+		"  lua_pushfstring(L, \"no builtin chunk '%s'\", name);\n",
+		"  return 1;\n",
+		"}\n\n",
+		"void lunka_add_builtin_searcher (lua_State *L) {\n",
+		"  int top = lua_gettop(L);\n",
+		"  luaL_getsubtable(L, LUA_REGISTRYINDEX, LUA_LOADED_TABLE);\n",
+		"  if (lua_getfield(L, -1, LUA_LOADLIBNAME) == LUA_TTABLE && lua_getfield(L, -1, \"searchers\") == LUA_TTABLE) {\n",
+		"    lua_Integer i;\n",
+		"    for (i = luaL_len(L, -1); i >= 2; i--) {\n",
+		"      lua_rawgeti(L, -1, i);\n",
+		"      lua_rawseti(L, -2, i + 1);\n",
+		"    }\n",
+		"    lua_pushcfunction(L, searcher_builtin);\n",
+		"    lua_rawseti(L, -2, 2);\n",
+		"  }\n",
+		"  lua_settop(L, top);\n",
+		"}\n",
+	));
+	out
+}
+
+/// Declarations of the functions in a [`link_stub`], as `(name, parameters, return type)`.
+const STUB_FUNCTIONS: &[(&str, &str, &str)] = &[
+	("lua_newstate", "f: lua_Alloc, ud: *mut c_void", "*mut lua_State"),
+	("lua_close", "L: *mut lua_State", ""),
+	("lua_newthread", "L: *mut lua_State", "*mut lua_State"),
+	("lua_closethread", "L: *mut lua_State, from: *mut lua_State", "c_int"),
+	("lua_resetthread", "L: *mut lua_State", "c_int"),
+	("lua_atpanic", "L: *mut lua_State, panicf: lua_CFunction", "lua_CFunction"),
+	("lua_version", "L: *mut lua_State", "lua_Number"),
+	("lua_absindex", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_gettop", "L: *mut lua_State", "c_int"),
+	("lua_settop", "L: *mut lua_State, idx: c_int", ""),
+	("lua_pushvalue", "L: *mut lua_State, idx: c_int", ""),
+	("lua_rotate", "L: *mut lua_State, idx: c_int, n: c_int", ""),
+	("lua_copy", "L: *mut lua_State, fromidx: c_int, toidx: c_int", ""),
+	("lua_checkstack", "L: *mut lua_State, n: c_int", "c_int"),
+	("lua_xmove", "from: *mut lua_State, to: *mut lua_State, n: c_int", ""),
+	("lua_isnumber", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_isstring", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_iscfunction", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_isinteger", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_isuserdata", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_type", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_typename", "L: *mut lua_State, tp: c_int", "*const c_char"),
+	("lua_tonumberx", "L: *mut lua_State, idx: c_int, isnum: *mut c_int", "lua_Number"),
+	("lua_tointegerx", "L: *mut lua_State, idx: c_int, isnum: *mut c_int", "lua_Integer"),
+	("lua_toboolean", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_tolstring", "L: *mut lua_State, idx: c_int, len: *mut usize", "*const c_char"),
+	("lua_rawlen", "L: *mut lua_State, idx: c_int", "lua_Unsigned"),
+	("lua_tocfunction", "L: *mut lua_State, idx: c_int", "lua_CFunction"),
+	("lua_touserdata", "L: *mut lua_State, idx: c_int", "*mut c_void"),
+	("lua_tothread", "L: *mut lua_State, idx: c_int", "*mut lua_State"),
+	("lua_topointer", "L: *mut lua_State, idx: c_int", "*const c_void"),
+	("lua_arith", "L: *mut lua_State, op: c_int", ""),
+	("lua_rawequal", "L: *mut lua_State, idx1: c_int, idx2: c_int", "c_int"),
+	("lua_compare", "L: *mut lua_State, idx1: c_int, idx2: c_int, op: c_int", "c_int"),
+	("lua_pushnil", "L: *mut lua_State", ""),
+	("lua_pushnumber", "L: *mut lua_State, n: lua_Number", ""),
+	("lua_pushinteger", "L: *mut lua_State, n: lua_Integer", ""),
+	("lua_pushlstring", "L: *mut lua_State, s: *const c_char, len: usize", "*const c_char"),
+	("lua_pushstring", "L: *mut lua_State, s: *const c_char", "*const c_char"),
+	("lua_pushfstring", "L: *mut lua_State, fmt: *const c_char, ...", "*const c_char"),
+	("lua_pushcclosure", "L: *mut lua_State, fn_: lua_CFunction, n: c_int", ""),
+	("lua_pushboolean", "L: *mut lua_State, b: c_int", ""),
+	("lua_pushlightuserdata", "L: *mut lua_State, p: *mut c_void", ""),
+	("lua_pushthread", "L: *mut lua_State", "c_int"),
+	("lua_getglobal", "L: *mut lua_State, name: *const c_char", "c_int"),
+	("lua_gettable", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_getfield", "L: *mut lua_State, idx: c_int, k: *const c_char", "c_int"),
+	("lua_geti", "L: *mut lua_State, idx: c_int, n: lua_Integer", "c_int"),
+	("lua_rawget", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_rawgeti", "L: *mut lua_State, idx: c_int, n: lua_Integer", "c_int"),
+	("lua_rawgetp", "L: *mut lua_State, idx: c_int, p: *const c_void", "c_int"),
+	("lua_createtable", "L: *mut lua_State, narr: c_int, nrec: c_int", ""),
+	("lua_newuserdatauv", "L: *mut lua_State, sz: usize, nuvalue: c_int", "*mut c_void"),
+	("lua_getmetatable", "L: *mut lua_State, objindex: c_int", "c_int"),
+	("lua_getiuservalue", "L: *mut lua_State, idx: c_int, n: c_int", "c_int"),
+	("lua_setglobal", "L: *mut lua_State, name: *const c_char", ""),
+	("lua_settable", "L: *mut lua_State, idx: c_int", ""),
+	("lua_setfield", "L: *mut lua_State, idx: c_int, k: *const c_char", ""),
+	("lua_seti", "L: *mut lua_State, idx: c_int, n: lua_Integer", ""),
+	("lua_rawset", "L: *mut lua_State, idx: c_int", ""),
+	("lua_rawseti", "L: *mut lua_State, idx: c_int, n: lua_Integer", ""),
+	("lua_rawsetp", "L: *mut lua_State, idx: c_int, p: *const c_void", ""),
+	("lua_setmetatable", "L: *mut lua_State, objindex: c_int", "c_int"),
+	("lua_setiuservalue", "L: *mut lua_State, idx: c_int, n: c_int", "c_int"),
+	("lua_callk", "L: *mut lua_State, nargs: c_int, nresults: c_int, ctx: lua_KContext, k: lua_KFunction", ""),
+	("lua_pcallk", "L: *mut lua_State, nargs: c_int, nresults: c_int, errfunc: c_int, ctx: lua_KContext, k: lua_KFunction", "c_int"),
+	("lua_load", "L: *mut lua_State, reader: lua_Reader, dt: *mut c_void, chunkname: *const c_char, mode: *const c_char", "c_int"),
+	("lua_dump", "L: *mut lua_State, writer: lua_Writer, data: *mut c_void, strip: c_int", "c_int"),
+	("lua_yieldk", "L: *mut lua_State, nresults: c_int, ctx: lua_KContext, k: lua_KFunction", "c_int"),
+	("lua_resume", "L: *mut lua_State, from: *mut lua_State, narg: c_int, nres: *mut c_int", "c_int"),
+	("lua_status", "L: *mut lua_State", "c_int"),
+	("lua_isyieldable", "L: *mut lua_State", "c_int"),
+	("lua_setwarnf", "L: *mut lua_State, f: lua_WarnFunction, ud: *mut c_void", ""),
+	("lua_warning", "L: *mut lua_State, msg: *const c_char, tocont: c_int", ""),
+	("lua_gc", "L: *mut lua_State, what: c_int, ...", "c_int"),
+	("lua_error", "L: *mut lua_State", "c_int"),
+	("lua_next", "L: *mut lua_State, idx: c_int", "c_int"),
+	("lua_concat", "L: *mut lua_State, n: c_int", ""),
+	("lua_len", "L: *mut lua_State, idx: c_int", ""),
+	("lua_stringtonumber", "L: *mut lua_State, s: *const c_char", "usize"),
+	("lua_getallocf", "L: *mut lua_State, ud: *mut *mut c_void", "lua_Alloc"),
+	("lua_setallocf", "L: *mut lua_State, f: lua_Alloc, ud: *mut c_void", ""),
+	("lua_toclose", "L: *mut lua_State, idx: c_int", ""),
+	("lua_closeslot", "L: *mut lua_State, idx: c_int", ""),
+	("luaL_newstate", "", "*mut lua_State"),
+	("luaL_openlibs", "L: *mut lua_State", ""),
+	("luaL_loadstring", "L: *mut lua_State, s: *const c_char", "c_int"),
+	("luaL_loadbufferx", "L: *mut lua_State, buff: *const c_char, sz: usize, name: *const c_char, mode: *const c_char", "c_int"),
+	("luaL_traceback", "L: *mut lua_State, L1: *mut lua_State, msg: *const c_char, level: c_int", ""),
+];
+
+/// Render a Rust source file with an `extern "C-unwind"` block that links to the static library `lib_name`
+/// and declares most of the core API along with a few auxiliary functions,
+/// with the number types `(integer, unsigned, number)`, the continuation context type `kcontext`
+/// and symbols prefixed with `prefix`.
+pub fn link_stub(
+	lib_name: &str, (integer, unsigned, number): (&str, &str, &str), kcontext: &str, prefix: &str,
+) -> String {
+	let mut out = String::from("// Generated by lunka-src.\n\n");
+	let types = [
+		("lua_State", String::new()),
+		("lua_Integer", integer.to_owned()),
+		("lua_Unsigned", unsigned.to_owned()),
+		("lua_Number", number.to_owned()),
+		("lua_KContext", kcontext.to_owned()),
+		("lua_CFunction", stub_fn_pointer("L: *mut lua_State", "c_int")),
+		("lua_KFunction", stub_fn_pointer("L: *mut lua_State, status: c_int, ctx: lua_KContext", "c_int")),
+		("lua_Reader", stub_fn_pointer("L: *mut lua_State, ud: *mut c_void, sz: *mut usize", "*const c_char")),
+		("lua_Writer", stub_fn_pointer("L: *mut lua_State, p: *const c_void, sz: usize, ud: *mut c_void", "c_int")),
+		("lua_Alloc", stub_fn_pointer("ud: *mut c_void, ptr: *mut c_void, osize: usize, nsize: usize", "*mut c_void")),
+		("lua_WarnFunction", stub_fn_pointer("ud: *mut c_void, msg: *const c_char, tocont: c_int", "")),
+	];
+	for (name, ty) in types {
+		out.push_str("#[allow(non_camel_case_types, non_snake_case, dead_code)]\n");
+		if ty.is_empty() {
+			let _ = writeln!(out, "#[repr(C)]\npub struct {name} {{\n\t_opaque: [u8; 0],\n}}");
+		} else {
+			let _ = writeln!(out, "pub type {name} = {ty};");
+		}
+	}
+
+	let _ = writeln!(out, "\n#[link(name = {lib_name:?}, kind = \"static\")]");
+	out.push_str("#[allow(non_snake_case, dead_code)]\nunsafe extern \"C-unwind\" {\n");
+	for (name, params, ret) in STUB_FUNCTIONS {
+		if !prefix.is_empty() {
+			let _ = writeln!(out, "\t#[link_name = \"{prefix}{name}\"]");
+		}
+		let _ = writeln!(out, "\tpub fn {name}({}){};", qualify_ffi_types(params), stub_return(ret));
+	}
+	out.push_str("}\n");
+	out
+}
+
+/// Render the type of a nullable `extern "C-unwind"` function pointer with `params` that returns `ret`.
+fn stub_fn_pointer(params: &str, ret: &str) -> String {
+	format!("Option<unsafe extern \"C-unwind\" fn({}){}>", qualify_ffi_types(params), stub_return(ret))
+}
+
+/// Render the return type `ret` of a function, which is empty if it returns nothing.
+fn stub_return(ret: &str) -> String {
+	match ret.is_empty() {
+		true => String::new(),
+		false => format!(" -> {}", qualify_ffi_types(ret)),
+	}
+}
+
+/// Replace the C types in `text` with their full paths,
+/// so that the generated file doesn't need to import them.
+fn qualify_ffi_types(text: &str) -> String {
+	text.replace("c_char", "::core::ffi::c_char")
+		.replace("c_int", "::core::ffi::c_int")
+		.replace("c_void", "::core::ffi::c_void")
+}
+
+/// Render a module-definition file for the DLL `name` that exports the public API,
+/// except for the functions of the standard libraries that aren't in `libs`,
+/// with symbols prefixed with `prefix`.
+pub fn module_definition(name: &str, libs: &[StdLib], prefix: &str) -> String {
+	let mut out = format!("; Generated by lunka-src.\nLIBRARY {name}\nEXPORTS\n");
+	let excluded = StdLib::ALL.iter().filter(move |lib| !libs.contains(lib)).map(move |lib| lib.open_function());
+	let excluded: Vec<&str> = excluded.collect();
+	for symbol in symbols::all().filter(move |symbol| !excluded.contains(symbol)) {
+		let kind = if symbols::CORE_VARIABLES.contains(&symbol) { " DATA" } else { "" };
+		let _ = writeln!(out, "\t{prefix}{symbol}{kind}");
+	}
+	out
+}
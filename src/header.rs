@@ -0,0 +1,86 @@
+/// Remove comments and line continuations from C source `text`,
+/// keeping line breaks so that the lines still correspond to the original ones.
+fn strip_comments(text: &str) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut chars = text.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'/' if chars.peek() == Some(&'*') => {
+				chars.next();
+				let mut last = '\0';
+				for c in chars.by_ref() {
+					if c == '\n' {
+						out.push('\n');
+					} else if last == '*' && c == '/' {
+						break
+					}
+					last = c;
+				}
+				out.push(' ');
+			}
+			'/' if chars.peek() == Some(&'/') => {
+				for c in chars.by_ref() {
+					if c == '\n' {
+						out.push('\n');
+						break
+					}
+				}
+			}
+			'\\' if chars.peek() == Some(&'\n') => {
+				chars.next();
+				out.push(' ');
+			}
+			'"' | '\'' => {
+				out.push(c);
+				let mut escaped = false;
+				for inner in chars.by_ref() {
+					out.push(inner);
+					if escaped {
+						escaped = false;
+					} else if inner == '\\' {
+						escaped = true;
+					} else if inner == c || inner == '\n' {
+						break
+					}
+				}
+			}
+			c => out.push(c),
+		}
+	}
+	out
+}
+
+/// Return the name of the macro defined on `line` and the rest of the line,
+/// if it is an object-like `#define` directive.
+pub fn object_define(line: &str) -> Option<(&str, &str)> {
+	let directive = line.trim_start().strip_prefix('#')?;
+	let rest = directive.trim_start().strip_prefix("define")?;
+	if !rest.starts_with([' ', '\t']) {
+		return None
+	}
+
+	let rest = rest.trim_start();
+	let name_len = rest.find(move |c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+	let (name, value) = rest.split_at(name_len);
+	if name.is_empty() || value.starts_with('(') {
+		return None
+	}
+	Some((name, value))
+}
+
+/// Parse simple object-like `#define NAME value` directives out of C source `text`,
+/// returning `(name, value)` pairs in order of appearance.
+/// 
+/// Function-like macros and other preprocessor directives are skipped.
+pub fn parse_defines(text: &str) -> Vec<(String, Option<String>)> {
+	let text = strip_comments(text);
+	let mut defines = Vec::new();
+	for line in text.lines() {
+		let Some((name, value)) = object_define(line) else {
+			continue
+		};
+		let value = value.trim();
+		defines.push((name.to_owned(), (!value.is_empty()).then(move || value.to_owned())));
+	}
+	defines
+}
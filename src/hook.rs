@@ -0,0 +1,141 @@
+use ::cc::{
+	Build as CcBuild,
+	Error as CcError,
+};
+use ::std::{
+	collections::VecDeque,
+	ffi::OsString,
+	fs::remove_file,
+	io::Error as IoError,
+	path::{
+		Path, PathBuf,
+	},
+	process::{
+		Child, Command,
+	},
+	sync::Arc,
+};
+
+use crate::provenance;
+
+/// Function that every compiler and archiver command is passed through before it runs.
+pub type CommandHook = Arc<dyn Fn(&mut Command) -> bool + Send + Sync>;
+
+/// Compile all files of `cc` into objects in `dir`,
+/// running at most `jobs` compilers at once and passing each command through `hook`,
+/// and return the paths to the objects.
+pub fn compile_objects(cc: &CcBuild, dir: &Path, hook: Option<&CommandHook>, jobs: usize) -> Result<Vec<PathBuf>, CcError> {
+	let tool = cc.try_get_compiler()?;
+	let extension = if tool.is_like_msvc() { "obj" } else { "o" };
+	let mut objects = Vec::new();
+	let mut running = VecDeque::new();
+	for file in cc.get_files() {
+		let parent = file.parent().unwrap_or(Path::new("")).to_string_lossy();
+		let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+		let object = dir.join(format!("{:016x}-{stem}.{extension}", provenance::hash(parent.as_bytes())));
+
+		let mut command = tool.to_command();
+		if tool.is_like_msvc() {
+			let mut out = OsString::from("-Fo");
+			out.push(&object);
+			command.arg(out);
+		} else {
+			command.arg("-o").arg(&object);
+		}
+		command.arg("-c").arg(file);
+		if running.len() >= jobs.max(1)
+			&& let Some((child, command)) = running.pop_front()
+		{
+			wait(child, &command)?;
+		}
+		let child = spawn(&mut command, hook)?;
+		running.push_back((child, command));
+		objects.push(object);
+	}
+	for (child, command) in running {
+		wait(child, &command)?;
+	}
+	Ok(objects)
+}
+
+/// Archive `objects` into the static library `lib_name` in `dir` with the archiver of `cc`,
+/// passing the command through `hook`.
+pub fn archive(cc: &CcBuild, dir: &Path, lib_name: &str, objects: &[PathBuf], hook: Option<&CommandHook>) -> Result<(), CcError> {
+	let tool = cc.try_get_compiler()?;
+	let mut command = cc.try_get_archiver()?;
+	let is_lib_exe = tool.is_like_msvc() && !command.get_program().to_string_lossy().contains("llvm-ar");
+	let output = dir.join(match is_lib_exe {
+		true => format!("{lib_name}.lib"),
+		false => format!("lib{lib_name}.a"),
+	});
+	let _ = remove_file(&output);
+
+	if is_lib_exe {
+		let mut out = OsString::from("-out:");
+		out.push(&output);
+		command.arg(out).arg("-nologo");
+	} else {
+		command.env("ZERO_AR_DATE", "1").arg("crs").arg(&output);
+	}
+	command.args(objects);
+	run(&mut command, hook)
+}
+
+/// Link `objects` into the DLL `name` in `dir` with the compiler of `cc`,
+/// exporting the symbols in the module-definition file `def` and generating the matching import library,
+/// passing the command through `hook`.
+/// 
+/// Returns the paths to the DLL and the import library.
+pub fn link_dll(
+	cc: &CcBuild, dir: &Path, name: &str, objects: &[PathBuf], def: &Path, link_args: &[String], hook: Option<&CommandHook>,
+) -> Result<(PathBuf, PathBuf), CcError> {
+	let tool = cc.try_get_compiler()?;
+	let dll = dir.join(format!("{name}.dll"));
+	let mut command = tool.to_command();
+	command.args(objects);
+	let import_lib = if tool.is_like_msvc() {
+		let import_lib = dir.join(format!("{name}.lib"));
+		let mut out = OsString::from("-Fe");
+		out.push(&dll);
+		let (mut def_arg, mut implib_arg) = (OsString::from("-DEF:"), OsString::from("-IMPLIB:"));
+		def_arg.push(def);
+		implib_arg.push(&import_lib);
+		command.arg("-LD").arg(out).arg("-link").arg(def_arg).arg(implib_arg);
+		import_lib
+	} else {
+		let import_lib = dir.join(format!("lib{name}.dll.a"));
+		let mut implib_arg = OsString::from("-Wl,--out-implib,");
+		implib_arg.push(&import_lib);
+		command.arg("-shared").arg("-o").arg(&dll).arg(def).arg(implib_arg).args(link_args);
+		import_lib
+	};
+	run(&mut command, hook)?;
+	Ok((dll, import_lib))
+}
+
+/// Pass `command` through `hook`, then run it to completion.
+pub fn run(command: &mut Command, hook: Option<&CommandHook>) -> Result<(), CcError> {
+	let child = spawn(command, hook)?;
+	wait(child, command)
+}
+
+/// Pass `command` through `hook`, then start it.
+fn spawn(command: &mut Command, hook: Option<&CommandHook>) -> Result<Child, CcError> {
+	if let Some(hook) = hook
+		&& !hook(command)
+	{
+		let message = format!("command was vetoed by the command hook: {command:?}");
+		return Err(IoError::other(message).into())
+	}
+	Ok(command.spawn()?)
+}
+
+/// Wait for `child`, which was started from `command`, to exit successfully.
+fn wait(mut child: Child, command: &Command) -> Result<(), CcError> {
+	let status = child.wait()?;
+	if !status.success() {
+		let message = format!("command {command:?} failed with {status}");
+		return Err(IoError::other(message).into())
+	}
+	Ok(())
+}
@@ -0,0 +1,61 @@
+use ::std::{
+	env::var,
+	fs::read_to_string,
+	num::NonZeroUsize,
+	thread::available_parallelism,
+};
+
+/// Return the number of bytes of memory available to this process, if it can be determined.
+/// 
+/// This is the smaller of the memory available to the system and the remaining memory of the cgroup,
+/// which is what limits containers.
+/// It can only be determined on Linux.
+pub fn available_memory() -> Option<u64> {
+	let system = read_to_string("/proc/meminfo").ok().and_then(move |info| {
+		let line = info.lines().find(move |line| line.starts_with("MemAvailable:"))?;
+		let kib: u64 = line.split_ascii_whitespace().nth(1)?.parse().ok()?;
+		Some(kib * 1024)
+	});
+	let cgroup = read_number("/sys/fs/cgroup/memory.max")
+		.map(move |max| max.saturating_sub(read_number("/sys/fs/cgroup/memory.current").unwrap_or(0)));
+	match (system, cgroup) {
+		(Some(a), Some(b)) => Some(a.min(b)),
+		(a, b) => a.or(b),
+	}
+}
+
+/// Read the number in the file at `path`, which is `None` if it's `max`.
+fn read_number(path: &str) -> Option<u64> {
+	read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Return the maximum number of compiler processes to run at once,
+/// or `None` if there is no limit other than the jobserver of Cargo.
+/// 
+/// A warning is printed for the build script output if memory lowers the number of jobs
+/// below what would be run otherwise.
+pub fn limit(max_jobs: Option<usize>, memory_per_job: Option<u64>) -> Option<usize> {
+	let cargo_jobs = var("NUM_JOBS").ok().and_then(move |n| n.parse::<usize>().ok());
+	let by_memory = memory_per_job.and_then(move |per_job| {
+		let available = available_memory()?;
+		let jobs = usize::try_from(available / per_job.max(1)).unwrap_or(usize::MAX).max(1);
+		Some((jobs, available, per_job))
+	});
+	if let Some((jobs, available, per_job)) = by_memory {
+		let otherwise = cargo_jobs
+			.or_else(move || available_parallelism().ok().map(NonZeroUsize::get))
+			.map_or(usize::MAX, move |n| max_jobs.map_or(n, move |max| max.min(n)));
+		if jobs < otherwise {
+			println!(
+				"cargo:warning=lunka-src: compiling with at most {jobs} job(s), as {} MiB of memory is available and each job may use {} MiB",
+				available >> 20, per_job >> 20,
+			);
+		}
+	}
+	let jobs = match (max_jobs, by_memory.map(move |(jobs, ..)| jobs)) {
+		(Some(a), Some(b)) => a.min(b),
+		(Some(jobs), None) | (None, Some(jobs)) => jobs,
+		(None, None) => return None,
+	};
+	Some(cargo_jobs.map_or(jobs, move |n| jobs.min(n)).max(1))
+}
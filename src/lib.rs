@@ -1,34 +1,263 @@
-use ::cc::Build as CcBuild;
+use ::cc::{
+	Build as CcBuild,
+	Tool,
+};
 use ::std::{
-	fs::read_dir,
-	io::Error as IoError,
-	path::Path,
+	env::{
+		current_dir, var, var_os,
+	},
+	ffi::{
+		OsStr, OsString,
+	},
+	fmt::Write,
+	fs::{
+		File, create_dir_all, read, read_dir, read_to_string, remove_file, write,
+	},
+	io::{
+		Error as IoError, ErrorKind as IoErrorKind,
+	},
+	path::{
+		Path, PathBuf,
+	},
+	process::Command,
+	sync::Arc,
 };
 
 pub use ::cc::Error as CcError;
 
+mod artifacts;
+pub use artifacts::*;
+mod codegen;
+pub use codegen::*;
+mod compiler;
+pub use compiler::*;
+pub mod conf_gen;
+#[cfg(feature = "serde")]
+mod config_file;
+mod env_flags;
+pub use env_flags::EnvFlagsPolicy;
+mod external;
+pub use external::*;
+mod generate;
+mod header;
+mod hook;
+mod jobs;
+mod patch;
+mod lock;
+pub use lock::*;
 mod lua_conf;
 pub use lua_conf::*;
+pub mod matrix;
+pub mod migrate;
 pub mod platforms;
+pub mod provenance;
+mod preset;
+pub use preset::*;
+mod readline;
+pub use readline::*;
+mod search_path;
+pub use search_path::*;
+mod stdlib;
+pub use stdlib::*;
+pub mod symbols;
+mod template;
+pub use template::*;
+mod time_hooks;
+pub use time_hooks::*;
+mod warnings;
+pub use warnings::{
+	SourceGroup, Warnings,
+};
 
+use conf_gen::Define;
 use platforms::{
 	Platform, from_current_triple, CURRENT_TRIPLE,
 };
 
 /// Builder for a compilation of Lua 5.4.
-#[repr(transparent)]
+#[derive(Clone)]
 pub struct Build {
 	cc: CcBuild,
+	standards: OwnedStandards,
+	explicit_archiver: bool,
+	out_dir: Option<PathBuf>,
+	defines: Vec<Define>,
+	overrides: Vec<ConfOverride>,
+	trees: Vec<SourceTree>,
+	external: bool,
+	warnings_into_errors: bool,
+	symbol_prefix: Option<String>,
+	generate_luaconf: bool,
+	private_symbols: bool,
+	launcher: Option<OsString>,
+	patches: Vec<Vec<patch::FilePatch>>,
+	debug_level: Option<DebugLevel>,
+	dwarf_version: Option<u8>,
+	debug_types_section: bool,
+	stage_sources: bool,
+	pic: Option<bool>,
+	pie: bool,
+	stdlibs: Option<Vec<StdLib>>,
+	modules: Vec<CModule>,
+	lock_out_dir: Option<bool>,
+	links_metadata: bool,
+	env_flags_policy: EnvFlagsPolicy,
+	sanitizers: Vec<Sanitizer>,
+	cargo_metadata: bool,
+	lto: Option<Lto>,
+	target: Option<String>,
+	provenance: bool,
+	pgo: Option<Pgo>,
+	coverage: bool,
+	link_libs: Vec<String>,
+	link_args: Vec<String>,
+	deterministic: bool,
+	family_flags: Vec<(CompilerFamily, String, bool)>,
+	group_warnings: Vec<(SourceGroup, Warnings)>,
+	explicit_opt_level: bool,
+	warn_unoptimized: bool,
+	command_hook: Option<hook::CommandHook>,
+	conservative_codegen: bool,
+	max_jobs: Option<usize>,
+	memory_per_job: Option<u64>,
+	gc_sections: bool,
+	apple_deployment_target: Option<String>,
+	luaconf_local: Option<PathBuf>,
+	android_api_level: Option<u32>,
+	safe_debug: bool,
+	emscripten_settings: Vec<String>,
+	wasm_libc: bool,
+	uwp: bool,
+	std: Option<String>,
+	readline: Readline,
+	gc_params: Vec<(&'static str, String)>,
+	builtin_chunks: Vec<(String, PathBuf)>,
+	hidden_symbols: bool,
+	global_lock: Option<LockImpl>,
+	allocator: Option<String>,
+	memory_limit: Option<u64>,
+	write_function: Option<String>,
+	write_error_function: Option<String>,
+	time_hooks: Option<TimeHooks<String>>,
+	cpp_exceptions: bool,
+}
+
+/// Setting that is passed as a `LUNKA_*` hook,
+/// or baked into a generated `luaconf.h` as the original macro.
+#[derive(Clone)]
+struct ConfOverride {
+	hook: &'static str,
+	target: &'static str,
+	value: Option<String>,
+}
+
+/// C module that is compiled into the library and added to `package.preload`.
+#[derive(Clone)]
+struct CModule {
+	name: String,
+	files: Vec<PathBuf>,
+	open_function: String,
+}
+
+/// Owned copy of the [`Standards`](platforms::Standards) of the platform of a [`Build`],
+/// which are selected from once the compiler is known.
+#[derive(Clone)]
+struct OwnedStandards {
+	gnu: Option<String>,
+	clang: Option<String>,
+	msvc: Option<String>,
+	clang_cl: Option<String>,
+}
+
+impl OwnedStandards {
+	/// Borrow these standards as [`Standards`](platforms::Standards).
+	fn borrow(&self) -> platforms::Standards<'_> {
+		platforms::Standards {
+			gnu: self.gnu.as_deref(),
+			clang: self.clang.as_deref(),
+			msvc: self.msvc.as_deref(),
+			clang_cl: self.clang_cl.as_deref(),
+		}
+	}
+}
+
+/// Phase of profile-guided optimization.
+#[derive(Clone)]
+enum Pgo {
+	Generate(PathBuf),
+	Use(PathBuf),
+}
+
+/// Lua source files that were added to a [`Build`], along with the directory containing their headers.
+#[derive(Clone)]
+struct SourceTree {
+	include_dir: PathBuf,
+	files: Vec<PathBuf>,
+	bundled: bool,
 }
 
 impl Build {
 	/// Create a new builder based on the [`Platform`] returned by [`from_current_triple`],
 	/// panicking if determining the platform or setting up failed.
 	pub fn for_current() -> Self {
-		let Some(platform) = from_current_triple() else {
-			panic!("couldn't determine platform for current target triple {CURRENT_TRIPLE:?}");
-		};
-		Self::new(platform)
+		match from_current_triple() {
+			Ok(platform) => Self::new(platform),
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Create a new builder as described by the configuration file at `path`, such as `lunka.toml`,
+	/// panicking if it could not be read or is invalid.
+	/// 
+	/// See also [`Build::try_from_config_file`] for the non-panicking version.
+	#[cfg(feature = "serde")]
+	pub fn from_config_file<P: AsRef<Path>>(path: P) -> Self {
+		match Self::try_from_config_file(path) {
+			Ok(b) => b,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Create a new builder as described by the configuration file at `path`, such as `lunka.toml`.
+	/// 
+	/// The file is written in TOML:
+	/// ```toml
+	/// # Platform by the name of its type in `platforms`, or the one for the current target if left out.
+	/// platform = "Linux"
+	/// 
+	/// # Defines, as `true` for ones without a value, or a string or an integer for ones with it.
+	/// [defines]
+	/// LUA_USE_APICHECK = true
+	/// LUA_IDSIZE = 80
+	/// 
+	/// # Compatibility options, such as `Build::compat_lua_5_3` for `lua_5_3`.
+	/// [compat]
+	/// lua_5_3 = true
+	/// math_lib = false
+	/// lt_le = false
+	/// 
+	/// # Optimization settings of `Build::opt_level` and `Build::debug_info`.
+	/// [opt]
+	/// level = 2
+	/// debug_info = false
+	/// 
+	/// # Fields of `LuaConf`, with `integer_type` and `float_type` spelled as C types.
+	/// [lua_conf]
+	/// buffer_size = 256
+	/// integer_type = "long long"
+	/// ```
+	/// 
+	/// Sources aren't added, so [`Build::add_lunka_src`] or [`Build::add_lua_src`] must still be called.
+	/// Returns an error with [`IoErrorKind::InvalidData`] that points to the line
+	/// of any unknown setting or value of the wrong type.
+	/// 
+	/// This requires the `serde` feature.
+	#[cfg(feature = "serde")]
+	pub fn try_from_config_file<P: AsRef<Path>>(path: P) -> Result<Self, CcError> {
+		let path = path.as_ref();
+		let text = read_to_string(path)?;
+		println!("cargo:rerun-if-changed={}", path.display());
+		config_file::build_from(path, &text)
 	}
 }
 
@@ -47,30 +276,82 @@ impl Build {
 	/// Create a new builder based on a [`Platform`].
 	pub fn try_new<P: Platform>(p: P) -> Result<Self, CcError> {
 		let mut cc = CcBuild::new();
-
-		{
-			let tool = cc.try_get_compiler()?;
-
-			let stds = p.standards();
-			let mut set_std = |std: Option<&str>| if let Some(std) = std { cc.std(std); };
-			if tool.is_like_gnu() {
-				set_std(stds.gnu)
-			} else if tool.is_like_clang() {
-				set_std(stds.clang)
-			} else if tool.is_like_msvc() {
-				set_std(stds.msvc)
-			} else if tool.is_like_clang_cl() {
-				set_std(stds.clang_cl)
-			}
-		}
-	
 		cc.warnings(true).extra_warnings(true);
-		for define in p.defines() {
-			cc.define(define, None);
-		}
+
+		let stds = p.standards();
+		let own = move |std: Option<&str>| std.map(str::to_owned);
 	
 		Ok(Self {
 			cc,
+			standards: OwnedStandards {
+				gnu: own(stds.gnu),
+				clang: own(stds.clang),
+				msvc: own(stds.msvc),
+				clang_cl: own(stds.clang_cl),
+			},
+			explicit_archiver: false,
+			out_dir: None,
+			defines: p.defines().iter().map(move |&define| (define.to_owned(), None))
+				.chain(p.valued_defines().iter().map(move |&(name, value)| (name.to_owned(), Some(value.to_owned()))))
+				.collect(),
+			overrides: Vec::new(),
+			trees: Vec::new(),
+			external: false,
+			warnings_into_errors: false,
+			symbol_prefix: None,
+			generate_luaconf: false,
+			private_symbols: false,
+			launcher: None,
+			patches: Vec::new(),
+			debug_level: None,
+			dwarf_version: None,
+			debug_types_section: false,
+			stage_sources: false,
+			pic: None,
+			pie: false,
+			stdlibs: None,
+			modules: Vec::new(),
+			lock_out_dir: None,
+			links_metadata: false,
+			env_flags_policy: EnvFlagsPolicy::Respect,
+			sanitizers: Vec::new(),
+			cargo_metadata: true,
+			lto: None,
+			target: None,
+			provenance: false,
+			pgo: None,
+			coverage: false,
+			link_libs: p.link_libs().iter().map(move |&lib| lib.to_owned()).collect(),
+			link_args: Vec::new(),
+			deterministic: false,
+			family_flags: Vec::new(),
+			group_warnings: Vec::new(),
+			explicit_opt_level: false,
+			warn_unoptimized: true,
+			command_hook: None,
+			conservative_codegen: false,
+			max_jobs: None,
+			memory_per_job: None,
+			gc_sections: false,
+			apple_deployment_target: None,
+			luaconf_local: None,
+			android_api_level: None,
+			safe_debug: false,
+			emscripten_settings: Vec::new(),
+			wasm_libc: false,
+			uwp: false,
+			std: None,
+			readline: Readline::None,
+			gc_params: Vec::new(),
+			builtin_chunks: Vec::new(),
+			hidden_symbols: false,
+			global_lock: None,
+			allocator: None,
+			memory_limit: None,
+			write_function: None,
+			write_error_function: None,
+			time_hooks: None,
+			cpp_exceptions: false,
 		})
 	}
 
@@ -78,209 +359,2674 @@ impl Build {
 	/// and panicking if compilation fails.
 	/// 
 	/// See also [`Build::try_compile`] for the non-panicking version.
-	pub fn compile(&self, output: &str) {
-		if let Err(e) = self.try_compile(output) {
-			panic!("{e}");
+	pub fn compile(&self, output: &str) -> Artifacts {
+		match self.try_compile(output) {
+			Ok(artifacts) => artifacts,
+			Err(e) => panic!("{e}"),
 		}
 	}
 
-	/// Run the compiler, generating the file `output`.
-	pub fn try_compile(&self, output: &str) -> Result<(), CcError> {
-		self.cc.try_compile(output)
-	}
+	/// Run the compiler, generating the file `output`,
+	/// and returning the [`Artifacts`] of this build.
+	/// 
+	/// The library is checked to exist after compiling,
+	/// as some toolchains name it differently than expected without failing,
+	/// and its path is returned in [`Artifacts::lib_path`].
+	/// An error with [`IoErrorKind::NotFound`] is returned if it doesn't exist.
+	/// 
+	/// If [`Build::external`] is enabled and an [`External`] library is described by the environment,
+	/// then nothing is compiled, and only the metadata for linking to that library is emitted instead.
+	pub fn try_compile(&self, output: &str) -> Result<Artifacts, CcError> {
+		let lib_name = output.strip_prefix("lib").and_then(move |s| s.strip_suffix(".a")).unwrap_or(output);
+		if self.external {
+			for var in [External::LIB_DIR_VAR, External::INCLUDE_DIR_VAR, External::LIB_NAME_VAR] {
+				println!("cargo:rerun-if-env-changed={var}");
+			}
+			if let Some(external) = External::from_env() {
+				if self.cargo_metadata {
+					external.emit_cargo_metadata(lib_name);
+				}
+				let artifacts = Artifacts {
+					lib_name: external.lib_name.unwrap_or_else(move || lib_name.to_owned()),
+					lib_dir: external.lib_dir,
+					lib_path: None,
+					include_dirs: vec![external.include_dir],
+					defines: Vec::new(),
+					// a static library needs these as much as a compiled one
+					link_libs: self.link_libs.clone(),
+					link_args: Vec::new(),
+				};
+				if self.cargo_metadata {
+					artifacts.emit_link_args();
+				}
+				if self.links_metadata {
+					artifacts.emit_cargo_metadata();
+				}
+				return Ok(artifacts)
+			}
+		}
 
-	/// Set the host assumed by this configuration.
-	pub fn host(&mut self, host: &str) -> &mut Self {
-		self.cc.host(host);
-		self
-	}
+		let _lock = if self.lock_out_dir.unwrap_or(self.out_dir.is_some()) {
+			let out_root = self.out_root()?;
+			create_dir_all(&out_root)?;
+			let lock = File::create(out_root.join(".lunka-src.lock"))?;
+			lock.lock()?;
+			Some(lock)
+		} else {
+			None
+		};
 
-	/// Set the output directory where all object files and static libraries will be located.
-	pub fn out_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
-		self.cc.out_dir(path);
-		self
-	}
+		if self.warn_unoptimized && !self.explicit_opt_level && var("OPT_LEVEL").is_ok_and(move |level| level == "0") {
+			let package = var("CARGO_PKG_NAME").unwrap_or_else(move |_| String::from("<package>"));
+			println!(
+				"cargo:warning=Lua is being compiled without optimizations, which makes it several times slower; \
+				add `[profile.dev.package.{package}] opt-level = 2` to Cargo.toml, \
+				or use `Build::opt_level` (and `Build::warn_unoptimized(false)` to silence this)"
+			);
+		}
 
-	fn define_flag(&mut self, flag: &str) -> &mut Self {
-		self.cc.define(flag, None);
-		self
+		let (mut cc, mut artifacts, groups, stems) = self.prepare(lib_name)?;
+		let fingerprint = {
+			let tool = cc.try_get_compiler()?;
+			let description = format!("{:016x} {:?} {:?}", self.config_hash(), tool.path(), tool.args());
+			format!("{:016x}\n", provenance::hash(description.as_bytes()))
+		};
+		let fingerprint_path = artifacts.lib_dir.join(format!(".lunka-src-{lib_name}.fingerprint"));
+		// The stems of the sources that were compiled are recorded after the fingerprint,
+		// so that objects of sources that this configuration doesn't compile are removed too.
+		if let Ok(previous) = read_to_string(&fingerprint_path)
+			&& previous.lines().next() != fingerprint.lines().next()
+		{
+			let stale: Vec<&str> = previous.lines().skip(1).chain(stems.iter().map(String::as_str)).collect();
+			remove_stale_objects(&artifacts.lib_dir, lib_name, &stale)?;
+		}
+		let jobs = self.job_limit();
+		if self.command_hook.is_some() || jobs.is_some() {
+			let objects = self.compile_objects(&cc, &groups, &artifacts.lib_dir, jobs)?;
+			hook::archive(&cc, &artifacts.lib_dir, lib_name, &objects, self.command_hook.as_ref())?;
+			if self.cargo_metadata {
+				println!("cargo:rustc-link-search=native={}", artifacts.lib_dir.display());
+				println!("cargo:rustc-link-lib=static={lib_name}");
+			}
+		} else {
+			for group_cc in groups {
+				cc.objects(group_cc.try_compile_intermediates()?);
+			}
+			cc.try_compile(output)?;
+		}
+		artifacts.lib_path = Some(find_library(&artifacts.lib_dir, lib_name)?);
+		write(&fingerprint_path, stems.iter().fold(fingerprint, move |record, stem| record + stem + "\n"))?;
+		if self.cargo_metadata {
+			artifacts.emit_link_args();
+		}
+		if self.links_metadata {
+			artifacts.emit_cargo_metadata();
+		}
+		Ok(artifacts)
 	}
 
-	fn define_lit(&mut self, ident: &str, data: &str) -> &mut Self {
-		self.cc.define(ident, Some(data));
-		self
+	/// Run the compiler and linker, generating the DLL `name`,
+	/// and panicking if compilation fails.
+	/// 
+	/// See also [`Build::try_compile_dll`] for the non-panicking version.
+	pub fn compile_dll(&self, name: &str) -> Artifacts {
+		match self.try_compile_dll(name) {
+			Ok(artifacts) => artifacts,
+			Err(e) => panic!("{e}"),
+		}
 	}
 
-	fn define_str(&mut self, ident: &str, data: &str) -> &mut Self {
-		let data = format!("\"{}\"", data.replace('"', "\\\"").replace('\\', "\\\\"));
-		self.define_lit(ident, &data)
-	}
+	/// Run the compiler and linker, generating the DLL `name`, such as `lua54`,
+	/// and returning the [`Artifacts`] of this build.
+	/// 
+	/// This is only supported when targeting Windows, with either MSVC or MinGW.
+	/// The sources are compiled with `LUA_BUILD_AS_DLL`,
+	/// and the output directory receives `{name}.dll`, the module-definition file `{name}.def` for the public API,
+	/// and the matching import library, which is `{name}.lib` with MSVC and `lib{name}.dll.a` with MinGW.
+	/// This is the layout that C modules loaded with `require` expect to link against.
+	/// 
+	/// If Cargo metadata is enabled, the crate being built is linked to the DLL through the import library.
+	/// The DLL must be shipped next to the final executable.
+	/// 
+	/// Returns an error with [`IoErrorKind::Unsupported`] if the target isn't Windows,
+	/// and one with [`IoErrorKind::InvalidInput`] if [`Build::private_symbols`] is enabled,
+	/// as then the API isn't exported.
+	pub fn try_compile_dll(&self, name: &str) -> Result<Artifacts, CcError> {
+		let target = self.effective_target();
+		if !target.contains("-windows") {
+			return Err(IoError::new(IoErrorKind::Unsupported, format!("DLLs can't be built for target {target:?}")).into())
+		}
+		if self.private_symbols {
+			return Err(IoError::new(IoErrorKind::InvalidInput, "DLLs can't be built with private symbols").into())
+		}
 
-	/// Add all Lua 5.4.8 source files bundled with this crate,
-	/// which allows for [`LuaConf`] to be used,
-	/// panicking if an error occurs while reading the directory contents.
-	pub fn add_lunka_src(&mut self) -> &mut Self {
-		match self.try_add_lunka_src() {
-			Ok(s) => s,
-			Err(e) => panic!("{e}"),
+		let (mut cc, mut artifacts, mut groups, _) = self.prepare(name)?;
+		for group_cc in groups.iter_mut().chain([&mut cc]) {
+			group_cc.define("LUA_BUILD_AS_DLL", None);
 		}
-	}
+		let objects = self.compile_objects(&cc, &groups, &artifacts.lib_dir, self.job_limit())?;
 
-	/// Add all Lua 5.4.8 source files bundled with this crate,
-	/// which allows for [`LuaConf`] to be used.
-	pub fn try_add_lunka_src(&mut self) -> Result<&mut Self, IoError> {
-		let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("lua-5.4.8");
-		self.include(root.join("include"));
-		let src = {
-			let mut b = root;
-			b.push("src");
-			b
-		};
-		for result in read_dir(src)? {
-			let item = result?;
-			if !item.file_type()?.is_file() {
-				continue
-			}
-			self.cc.file(item.path());
+		let libs = self.stdlibs.as_deref().unwrap_or(&StdLib::ALL);
+		let prefix = self.symbol_prefix.as_deref().unwrap_or_default();
+		let def = generate::write_file(&artifacts.lib_dir, &format!("{name}.def"), generate::module_definition(name, libs, prefix))?;
+		let (dll, import_lib) = hook::link_dll(&cc, &artifacts.lib_dir, name, &objects, &def, &artifacts.link_args, self.command_hook.as_ref())?;
+		verify_output(&dll)?;
+		artifacts.lib_path = Some(verify_output(&import_lib)?);
+		if self.cargo_metadata {
+			println!("cargo:rustc-link-search=native={}", artifacts.lib_dir.display());
+			println!("cargo:rustc-link-lib=dylib={name}");
 		}
-		Ok(self)
+		if self.links_metadata {
+			artifacts.emit_cargo_metadata();
+		}
+		Ok(artifacts)
 	}
 
-	/// Add all Lua source files found in the specified `root`,
-	/// panicking if an error occurs while reading the directory contents.
-	/// 
-	/// See also [`Build::try_add_lua_src`] for the non-panicking version.
-	/// 
-	/// `root` must be a directory containing both source files (`*.c`) and headers (`*.h`).
+	/// Compile a static library named `output` for each of the Apple `targets`
+	/// and merge them into a universal one, panicking if compilation fails.
 	/// 
-	/// If [`LuaConf`] is used,
-	/// then the path cannot point to a normal Lua source distribution.
-	/// See the documentation for [`LuaConf`] for more details.
-	pub fn add_lua_src<P: AsRef<Path>>(&mut self, root: P) -> &mut Self {
-		match self.try_add_lua_src(root) {
-			Ok(s) => s,
+	/// See also [`Build::try_compile_universal`] for the non-panicking version.
+	pub fn compile_universal(&self, output: &str, targets: &[&str]) -> Artifacts {
+		match self.try_compile_universal(output, targets) {
+			Ok(artifacts) => artifacts,
 			Err(e) => panic!("{e}"),
 		}
 	}
 
-	/// Add all Lua source files found in the specified `root`.
+	/// Compile a static library named `output` for each of the Apple `targets`,
+	/// such as `aarch64-apple-darwin` and `x86_64-apple-darwin`,
+	/// merge them into a universal one in the output directory with `lipo`,
+	/// and return the [`Artifacts`] of the universal library.
 	/// 
-	/// `root` must be a directory containing both source files (`*.c`) and headers (`*.h`).
+	/// Each slice is compiled into its own directory inside `lunka-universal` in the output directory
+	/// with this configuration and the target set with [`Build::target`].
+	/// The `lipo` program can be set with the `LIPO` environment variable.
 	/// 
-	/// If [`LuaConf`] is used,
-	/// then the path cannot point to a normal Lua source distribution.
-	/// See the documentation for [`LuaConf`] for more details.
-	pub fn try_add_lua_src<P: AsRef<Path>>(&mut self, root: P) -> Result<&mut Self, IoError> {
-		const BINARIES: [&str; 2] = ["lua.c", "luac.c"];
-		for result in read_dir(root)? {
-			let item = result?;
-			if !item.file_type()?.is_file() {
-				continue
-			}
+	/// Returns an error with [`IoErrorKind::InvalidInput`] if `targets` is empty or has a target that isn't an Apple one.
+	pub fn try_compile_universal(&self, output: &str, targets: &[&str]) -> Result<Artifacts, CcError> {
+		let lib_name = output.strip_prefix("lib").and_then(move |s| s.strip_suffix(".a")).unwrap_or(output);
+		if targets.is_empty() {
+			return Err(IoError::new(IoErrorKind::InvalidInput, "no targets were given for a universal library").into())
+		}
+		if let Some(target) = targets.iter().find(move |target| !target.contains("-apple-")) {
+			return Err(IoError::new(IoErrorKind::InvalidInput, format!("target {target:?} is not an Apple one")).into())
+		}
 
-			let file_name = item.file_name();
-			let Some(file_name) = file_name.to_str() else {
-				continue
-			};
-			if !file_name.ends_with(".c") || BINARIES.contains(&file_name) {
-				continue
-			}
+		println!("cargo:rerun-if-env-changed=LIPO");
+		let out_root = self.out_root()?;
+		let mut lipo = Command::new(var_os("LIPO").unwrap_or_else(move || OsString::from("lipo")));
+		let output = out_root.join(format!("lib{lib_name}.a"));
+		lipo.arg("-create").arg("-output").arg(&output);
+		let mut slices = Vec::with_capacity(targets.len());
+		for target in targets {
+			let mut slice = self.clone();
+			slice.target(target).out_dir(out_root.join("lunka-universal")).cargo_metadata(false);
+			slice.links_metadata = false;
+			slice.external = false;
+			let artifacts = slice.try_compile(lib_name)?;
+			lipo.args(artifacts.lib_path.as_deref());
+			slices.push(artifacts);
+		}
+		hook::run(&mut lipo, self.command_hook.as_ref())?;
 
-			self.cc.file(item.path());
+		let first = slices.swap_remove(0);
+		let artifacts = Artifacts {
+			lib_dir: out_root,
+			lib_path: Some(verify_output(&output)?),
+			..first
+		};
+		if self.cargo_metadata {
+			println!("cargo:rustc-link-search=native={}", artifacts.lib_dir.display());
+			println!("cargo:rustc-link-lib=static={lib_name}");
+			artifacts.emit_link_args();
 		}
-		Ok(self)
+		if self.links_metadata {
+			artifacts.emit_cargo_metadata();
+		}
+		Ok(artifacts)
 	}
 
-	/// Add an include directory.
-	pub fn include<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
-		self.cc.include(path);
-		self
+	/// Compile the sources of `cc` and `groups` into objects in `dir`,
+	/// running at most `jobs` compilers at once if it's set,
+	/// and return the paths to the objects.
+	fn compile_objects(&self, cc: &CcBuild, groups: &[CcBuild], dir: &Path, jobs: Option<usize>) -> Result<Vec<PathBuf>, CcError> {
+		let mut objects = Vec::new();
+		match (self.command_hook.as_ref(), jobs) {
+			(None, None) => for group_cc in groups.iter().chain([cc]) {
+				objects.extend(group_cc.try_compile_intermediates()?);
+			},
+			(hook, jobs) => for group_cc in groups.iter().chain([cc]) {
+				objects.extend(hook::compile_objects(group_cc, dir, hook, jobs.unwrap_or(1))?);
+			},
+		}
+		Ok(objects)
 	}
 
-	/// Adds multiple include directories.
-	pub fn includes<P>(&mut self, paths: P) -> &mut Self
-	where
-		P: IntoIterator,
-		P::Item: AsRef<Path>,
-	{
-		self.cc.includes(paths);
-		self
+	/// Return the maximum number of compiler processes to run at once, if it's limited by this build.
+	fn job_limit(&self) -> Option<usize> {
+		match cfg!(feature = "parallel") {
+			true => jobs::limit(self.max_jobs, self.memory_per_job),
+			false => None,
+		}
 	}
 
-	/// Set whether debug information should be emitted for this build.
-	pub fn debug_info(&mut self, emit_debug_info: bool) -> &mut Self {
-		self.cc.debug(emit_debug_info);
-		self
+	/// Return the underlying [`CcBuild`], for using features of `cc` that this builder doesn't wrap.
+	/// 
+	/// Settings that this builder applies when compiling, such as definitions and source files,
+	/// are not present in it yet, and take precedence over the ones set directly on it.
+	pub fn cc_mut(&mut self) -> &mut CcBuild {
+		&mut self.cc
 	}
 
-	/// Set the semi-arbitrary optimization level for the generated object files.
-	pub fn opt_level(&mut self, opt_level: u32) -> &mut Self {
-		self.cc.opt_level(opt_level);
-		self
+	/// Convert this builder into a [`CcBuild`] with everything set up to compile Lua,
+	/// panicking if setting it up fails.
+	/// 
+	/// See also [`Build::try_into_cc`] for the non-panicking version.
+	pub fn into_cc(self) -> CcBuild {
+		match self.try_into_cc() {
+			Ok(cc) => cc,
+			Err(e) => panic!("{e}"),
+		}
 	}
 
-	/// Enable compatibility with Lua 5.3.
-	pub fn compat_lua_5_3(&mut self) -> &mut Self {
-		self.define_flag("LUA_COMPAT_5_3")
+	/// Convert this builder into a [`CcBuild`] with everything set up to compile Lua.
+	/// 
+	/// Sources of [`SourceGroup`]s with their own warning settings are compiled right away,
+	/// and their objects are added to the returned build.
+	/// No Cargo metadata other than what `cc` itself emits is printed when compiling with it.
+	pub fn try_into_cc(self) -> Result<CcBuild, CcError> {
+		let (mut cc, _, groups, _) = self.prepare("lua")?;
+		for group_cc in groups {
+			cc.objects(group_cc.try_compile_intermediates()?);
+		}
+		Ok(cc)
 	}
 
-	/// Include several deprecated functions in the `math` library.
-	pub fn compat_math_lib(&mut self) -> &mut Self {
-		self.define_flag("LUA_COMPAT_MATH_LIB")
+	/// Generate Rust bindings for the Lua API as configured by this build,
+	/// panicking if generating them fails.
+	/// 
+	/// See also [`Build::try_generate_bindings`] for the non-panicking version.
+	#[cfg(feature = "bindings")]
+	pub fn generate_bindings(&self) -> PathBuf {
+		match self.try_generate_bindings() {
+			Ok(path) => path,
+			Err(e) => panic!("{e}"),
+		}
 	}
 
-	/// Emulate the `__le` metamethod using `__lt`.
-	pub fn compat_lt_le(&mut self) -> &mut Self {
-		self.define_flag("LUA_COMPAT_LT_LE")
+	/// Generate Rust bindings for the Lua API as configured by this build,
+	/// writing them to `lua_sys.rs` in the output directory and returning its path.
+	/// 
+	/// This runs the `bindgen` command-line tool (or the one specified by the `BINDGEN` environment variable)
+	/// on `lua.h`, `lauxlib.h` and `lualib.h`,
+	/// with the same target, include directories and definitions that the sources are compiled with,
+	/// so that the bindings always match the configuration,
+	/// including the number types and `LUA_EXTRASPACE`.
+	/// 
+	/// The `bindings` feature only enables this method,
+	/// as the tool is run as a command rather than being a dependency of this crate.
+	#[cfg(feature = "bindings")]
+	pub fn try_generate_bindings(&self) -> Result<PathBuf, CcError> {
+		println!("cargo:rerun-if-env-changed=BINDGEN");
+		let (_, artifacts, ..) = self.prepare("lua")?;
+		let wrapper = generate::write_file(
+			&self.gen_dir()?, "lunka_bindings.h",
+			"#include \"lua.h\"\n#include \"lauxlib.h\"\n#include \"lualib.h\"\n",
+		)?;
+		let output = self.build_root()?.join("lua_sys.rs");
+
+		let mut command = Command::new(var_os("BINDGEN").unwrap_or_else(move || "bindgen".into()));
+		command.arg(&wrapper).arg("-o").arg(&output).args(["--allowlist-function", "lua.*"])
+			.args(["--allowlist-var", "LUA.*|lua.*"]).args(["--allowlist-type", "lua.*"]).arg("--")
+			// the layout of the types depends on the target rather than the host
+			.arg(format!("--target={}", self.effective_target()));
+		for dir in artifacts.include_dirs.iter() {
+			let mut arg = OsString::from("-I");
+			arg.push(dir);
+			command.arg(arg);
+		}
+		for (name, value) in artifacts.defines.iter() {
+			command.arg(match value {
+				Some(value) => format!("-D{name}={value}"),
+				None => format!("-D{name}"),
+			});
+		}
+
+		let status = command.status()?;
+		if !status.success() {
+			return Err(IoError::other(format!("bindgen failed with {status}")).into())
+		}
+		Ok(output)
 	}
 
-	/// Enable several consistency checks in the API.
-	pub fn api_checks(&mut self) -> &mut Self {
-		self.define_flag("LUA_USE_APICHECK")
+	/// Write a Rust source file to `path` that describes the configuration of this build,
+	/// panicking if an error occurs.
+	/// 
+	/// See also [`Build::try_write_config_rs`] for the non-panicking version.
+	pub fn write_config_rs<P: AsRef<Path>>(&self, path: P) {
+		if let Err(e) = self.try_write_config_rs(path) {
+			panic!("{e}")
+		}
 	}
 
-	/// Set the default path that Lua uses to look for Lua libraries.
-	pub fn lua_lib_path(&mut self, path: &str) -> &mut Self {
-		self.define_str("LUA_PATH_DEFAULT", path)
+	/// Write a Rust source file to `path` that describes the configuration of this build,
+	/// so that binding crates can `include!` it and stay consistent with the C library.
+	/// 
+	/// The file contains the `LuaInteger`, `LuaUnsigned`, `LuaNumber` and `LuaKContext` type aliases,
+	/// and the `LUA_EXTRASPACE`, `LUA_IDSIZE` and `LUAI_MAXSTACK` constants.
+	/// 
+	/// Returns an error with [`IoErrorKind::InvalidData`] if a configured value isn't a plain number,
+	/// as it can't be evaluated outside of the C preprocessor.
+	/// Returns an error with [`IoErrorKind::Unsupported`] if Lua floats are `long double`,
+	/// or if [`LuaConf::kcontext`] is a type that isn't known to have an equivalent in Rust.
+	pub fn try_write_config_rs<P: AsRef<Path>>(&self, path: P) -> Result<(), IoError> {
+		let path = path.as_ref();
+		let number = move |target: &str, default: &str| -> Result<String, IoError> {
+			match self.conf_value(target) {
+				Some(Some(value)) => match plain_number(value) {
+					Some(n) => Ok(n.to_string()),
+					None => Err(IoError::new(
+						IoErrorKind::InvalidData, format!("value of {target} ({:?}) is not a plain number", value.trim()),
+					)),
+				},
+				_ => Ok(default.to_owned()),
+			}
+		};
+
+		let (integer, unsigned, number_type) = self.rust_number_types()?;
+		let kcontext = self.rust_kcontext_type()?;
+		let contents = format!(
+			"// Generated by lunka-src.\n\
+			pub type LuaInteger = {integer};\n\
+			pub type LuaUnsigned = {unsigned};\n\
+			pub type LuaNumber = {number_type};\n\
+			pub type LuaKContext = {kcontext};\n\
+			pub const LUA_EXTRASPACE: usize = {};\n\
+			pub const LUA_IDSIZE: usize = {};\n\
+			pub const LUAI_MAXSTACK: usize = {};\n",
+			number("LUA_EXTRASPACE", "::core::mem::size_of::<*mut ::core::ffi::c_void>()")?,
+			number("LUA_IDSIZE", "60")?,
+			number("LUAI_MAXSTACK", "1000000")?,
+		);
+
+		let dir = match path.parent() {
+			Some(dir) if !dir.as_os_str().is_empty() => dir,
+			_ => Path::new("."),
+		};
+		generate::write_file(dir, &file_name(path)?, contents)?;
+		Ok(())
 	}
 
-	/// Set the default path that Lua uses to look for C libraries.
-	pub fn lua_c_lib_path(&mut self, path: &str) -> &mut Self {
-		self.define_str("LUA_CPATH_DEFAULT", path)
+	/// Return the Rust types of `lua_Integer`, `lua_Unsigned` and `lua_Number` in this build.
+	/// 
+	/// Returns an error with [`IoErrorKind::Unsupported`] if `lua_Number` is `long double`,
+	/// as Rust has no equivalent of it.
+	fn rust_number_types(&self) -> Result<(&'static str, &'static str, &'static str), IoError> {
+		let (int, uint, float) = if self.conf_value("LUA_32BITS").is_some() {
+			("i32", "u32", "f32")
+		} else if self.conf_value("LUA_USE_C89").is_some() && !self.is_windows() {
+			("::core::ffi::c_long", "::core::ffi::c_ulong", "f64")
+		} else {
+			("i64", "u64", "f64")
+		};
+		let (int, uint) = match self.conf_value("LUA_INT_TYPE").flatten() {
+			Some("LUA_INT_INT") => ("::core::ffi::c_int", "::core::ffi::c_uint"),
+			Some("LUA_INT_LONG") => ("::core::ffi::c_long", "::core::ffi::c_ulong"),
+			Some("LUA_INT_LONGLONG") => ("::core::ffi::c_longlong", "::core::ffi::c_ulonglong"),
+			_ => (int, uint),
+		};
+		let float = match self.conf_value("LUA_FLOAT_TYPE").flatten() {
+			Some("LUA_FLOAT_FLOAT") => "f32",
+			Some("LUA_FLOAT_DOUBLE") => "f64",
+			Some("LUA_FLOAT_LONGDOUBLE") => return Err(IoError::new(
+				IoErrorKind::Unsupported, "Lua floats are `long double`, which has no equivalent in Rust",
+			)),
+			_ => float,
+		};
+		Ok((int, uint, float))
 	}
 
-	/// Set the directory separator for `require` submodules.
-	pub fn dir_separator(&mut self, sep: &str) -> &mut Self {
-		self.define_str("LUA_DIRSEP", sep)
+	/// Return the Rust type of `lua_KContext` in this build.
+	/// 
+	/// Returns an error with [`IoErrorKind::Unsupported`] if it isn't an integer type from `<stdint.h>` or `<stddef.h>`,
+	/// or a standard integer type.
+	fn rust_kcontext_type(&self) -> Result<&'static str, IoError> {
+		let Some(Some(value)) = self.conf_value("LUA_KCONTEXT") else {
+			return Ok("isize")
+		};
+		let words = value.split_whitespace().collect::<Vec<_>>().join(" ");
+		Ok(match words.as_str() {
+			"intptr_t" | "ptrdiff_t" | "ssize_t" => "isize",
+			"uintptr_t" | "size_t" => "usize",
+			"int8_t" => "i8",
+			"int16_t" => "i16",
+			"int32_t" => "i32",
+			"int64_t" => "i64",
+			"uint8_t" => "u8",
+			"uint16_t" => "u16",
+			"uint32_t" => "u32",
+			"uint64_t" => "u64",
+			"short" | "signed short" | "short int" => "::core::ffi::c_short",
+			"unsigned short" | "unsigned short int" => "::core::ffi::c_ushort",
+			"int" | "signed" | "signed int" => "::core::ffi::c_int",
+			"unsigned" | "unsigned int" => "::core::ffi::c_uint",
+			"long" | "signed long" | "long int" => "::core::ffi::c_long",
+			"unsigned long" | "unsigned long int" => "::core::ffi::c_ulong",
+			"long long" | "signed long long" | "long long int" => "::core::ffi::c_longlong",
+			"unsigned long long" | "unsigned long long int" => "::core::ffi::c_ulonglong",
+			_ => return Err(IoError::new(
+				IoErrorKind::Unsupported, format!("continuation contexts are `{words}`, which has no known equivalent in Rust"),
+			)),
+		})
 	}
 
-	/// Enable Unicode Identifiers.
+	/// Write a Rust source file to `path` that declares the core API of the library `lib_name`,
+	/// panicking if an error occurs.
 	/// 
-	/// This is a define that isn't explicitly mentioned in the configuration header,
-	/// but is checked in `lctype.c` to build the identifier character table.
-	pub fn unicode_identifiers(&mut self) -> &mut Self {
-		self.define_flag("LUA_UCID")
+	/// See also [`Build::try_write_link_stub`] for the non-panicking version.
+	pub fn write_link_stub<P: AsRef<Path>>(&self, path: P, lib_name: &str) {
+		if let Err(e) = self.try_write_link_stub(path, lib_name) {
+			panic!("{e}")
+		}
 	}
 
-	/// Use additional configuration provided by a [`LuaConf`] in this build.
-	pub fn lua_conf<S: AsRef<str>>(&mut self, lua_conf: &LuaConf<S>) -> &mut Self {
-		if lua_conf.no_number_to_string {
-			self.define_flag("LUNKA_NOCVTN2S");
+	/// Write a Rust source file to `path` that declares the core API of the library `lib_name`,
+	/// as compiled with [`Build::compile`],
+	/// so that crates with minimal bindings can `include!` it instead of writing the declarations by hand.
+	/// 
+	/// The file contains an opaque `lua_State`, the types of the API with their C names,
+	/// and an `extern "C-unwind"` block with a `#[link]` attribute for the static library.
+	/// The block declares the functions of `lua.h` other than the debug interface and `lua_pushvfstring`,
+	/// and `luaL_newstate`, `luaL_openlibs`, `luaL_loadstring`, `luaL_loadbufferx` and `luaL_traceback`.
+	/// The names of the functions are linked with the [symbol prefix](Build::symbol_prefix), if any.
+	/// 
+	/// Returns an error with [`IoErrorKind::Unsupported`] if Lua floats are `long double`,
+	/// or if [`LuaConf::kcontext`] is a type that isn't known to have an equivalent in Rust.
+	pub fn try_write_link_stub<P: AsRef<Path>>(&self, path: P, lib_name: &str) -> Result<(), IoError> {
+		let path = path.as_ref();
+		let prefix = self.symbol_prefix.as_deref().unwrap_or_default();
+		let contents = generate::link_stub(lib_name, self.rust_number_types()?, self.rust_kcontext_type()?, prefix);
+
+		let dir = match path.parent() {
+			Some(dir) if !dir.as_os_str().is_empty() => dir,
+			_ => Path::new("."),
+		};
+		generate::write_file(dir, &file_name(path)?, contents)?;
+		Ok(())
+	}
+
+	/// Set up a [`CcBuild`] with everything needed to compile this build,
+	/// returning it along with the [`Artifacts`] that it would produce,
+	/// the builds for the objects of [`SourceGroup`]s with their own warning settings
+	/// and the stems of the names of all source files that are compiled, including generated ones.
+	fn prepare(&self, lib_name: &str) -> Result<(CcBuild, Artifacts, Vec<CcBuild>, Vec<String>), CcError> {
+		if self.conf_value("LUA_32BITS").is_some()
+			&& let Some(target) = ["LUA_INT_TYPE", "LUA_FLOAT_TYPE"].into_iter().find(move |&target| self.conf_value(target).is_some())
+		{
+			return Err(IoError::new(
+				IoErrorKind::InvalidInput, format!("32-bit numbers can't be combined with an explicit {target}"),
+			).into())
+		}
+		let mut cc = self.cc.clone();
+		if self.cpp_exceptions {
+			cc.cpp(true);
+		}
+		match self.env_flags_policy {
+			EnvFlagsPolicy::Respect => {}
+			EnvFlagsPolicy::Ignore => {
+				cc.set_envs_snapshot(env_flags::vars_without_flags());
+			}
+			EnvFlagsPolicy::Prepend => {
+				cc.set_envs_snapshot(env_flags::vars_without_flags());
+				for flag in env_flags::compiler_flags(self.cpp_exceptions) {
+					cc.flag(flag);
+				}
+			}
 		}
-		if lua_conf.no_string_to_number {
-			self.define_flag("LUNKA_NOCVTS2N");
+		let tool = cc.try_get_compiler()?;
+		let family = CompilerFamily::of(&tool);
+		let c_std = family.and_then(move |family| self.standards.borrow().select(family));
+		let std = match self.cpp_exceptions {
+			// the selected standards are C standards
+			true => self.std.as_deref(),
+			false => self.std.as_deref().or(c_std),
+		};
+		let (std, c_std) = match family {
+			Some(CompilerFamily::Msvc | CompilerFamily::ClangCl) => (std.and_then(compiler::msvc_std), c_std.and_then(compiler::msvc_std)),
+			_ => (std, c_std),
+		};
+		if let Some(std) = std {
+			cc.std(std);
 		}
-		if let Some(extra_space) = lua_conf.extra_space.as_ref().map(move |s| s.as_ref()) {
-			self.define_lit("LUNKA_EXTRASPACE", extra_space);
+		if self.warnings_into_errors {
+			warnings::downgrade_benign(&mut cc, &tool);
 		}
-		if let Some(id_size) = lua_conf.id_size.as_ref().map(move |s| s.as_ref()) {
-			self.define_lit("LUNKA_IDSIZE", id_size);
+		for (_, flag, if_supported) in self.family_flags.iter().filter(move |(f, ..)| Some(*f) == family) {
+			if *if_supported {
+				cc.flag_if_supported(flag);
+			} else {
+				cc.flag(flag);
+			}
 		}
-		self
-	}
-
-	/// Use 32-bit integers and floats despite what the platform is.
-	pub fn use_32_bits(&mut self) -> &mut Self {
-		self.define_flag("LUNKA_32BITS")
+		if let Some(launcher) = self.launcher.as_deref() {
+			cc.compiler(generate::launcher_script(&self.gen_dir()?, launcher, &tool)?);
+		}
+		if !tool.is_like_msvc() {
+			if let Some(level) = self.debug_level {
+				cc.flag(level.flag());
+			}
+			if let Some(version) = self.dwarf_version {
+				cc.flag(format!("-gdwarf-{version}"));
+			}
+			if self.debug_types_section {
+				cc.flag_if_supported("-fdebug-types-section");
+			}
+		}
+		if let Some(pic) = self.pic {
+			cc.pic(pic);
+		}
+		if self.pie && !tool.is_like_msvc() {
+			cc.pic(false).flag("-fPIE");
+		}
+		if self.conservative_codegen && !tool.is_like_msvc() {
+			cc.flag("-fno-strict-aliasing").flag("-fwrapv");
+		}
+		if self.deterministic {
+			if tool.is_like_msvc() {
+				cc.flag("/Brepro");
+			} else {
+				// Later maps take precedence, so the more specific directories come last.
+				let mut maps = vec![
+					(current_dir()?, PathBuf::from(".")),
+					(PathBuf::from(env!("CARGO_MANIFEST_DIR")), PathBuf::from("/lunka-src")),
+				];
+				maps.extend(self.trees.iter().enumerate().map(move |(i, tree)| {
+					(tree.include_dir.clone(), PathBuf::from(format!("/lua-{i}")))
+				}));
+				maps.push((self.out_root()?, PathBuf::from("/out")));
+				for (from, to) in maps {
+					let mut flag = OsString::from("-ffile-prefix-map=");
+					flag.push(from);
+					flag.push("=");
+					flag.push(to);
+					cc.flag(flag);
+				}
+			}
+			if var_os("SOURCE_DATE_EPOCH").is_none() {
+				cc.env("SOURCE_DATE_EPOCH", "0");
+			}
+		}
+		let lib_dir = self.build_root()?;
+		if self.target.is_some() {
+			create_dir_all(&lib_dir)?;
+			cc.out_dir(&lib_dir);
+		}
+		let mut link_args = self.link_args.clone();
+		for &sanitizer in self.sanitizers.iter() {
+			if tool.is_like_msvc() {
+				if sanitizer != Sanitizer::Address {
+					return Err(IoError::new(
+						IoErrorKind::Unsupported, format!("the {} sanitizer is not supported by MSVC", sanitizer.name()),
+					).into())
+				}
+				cc.flag("/fsanitize=address");
+			} else {
+				let flag = format!("-fsanitize={}", sanitizer.name());
+				cc.flag(&flag);
+				if tool.is_like_gnu() {
+					// Rust links with `-nodefaultlibs`, which keeps GCC from adding the runtime by itself,
+					// and the shared runtimes refuse to start unless they're loaded first.
+					link_args.push(match sanitizer {
+						Sanitizer::Address => String::from("-Wl,--whole-archive,-l:libasan.a,--no-whole-archive"),
+						Sanitizer::Thread => String::from("-Wl,--whole-archive,-l:libtsan.a,--no-whole-archive"),
+						Sanitizer::Undefined => String::from("-l:libubsan.a"),
+						Sanitizer::Memory => return Err(IoError::new(
+							IoErrorKind::Unsupported, "the memory sanitizer is not supported by GCC",
+						).into()),
+					});
+				} else {
+					link_args.push(flag);
+				}
+			}
+		}
+		if let Some(lto) = self.lto {
+			if tool.is_like_msvc() && !tool.is_like_clang_cl() {
+				cc.flag("/GL").ar_flag("/LTCG");
+			} else if tool.is_like_gnu() {
+				// Fat objects keep the library usable by linkers that don't run the LTO plugin.
+				cc.flag("-flto=auto").flag("-ffat-lto-objects");
+				link_args.push(String::from("-flto=auto"));
+			} else {
+				cc.flag(match lto {
+					Lto::Thin => "-flto=thin",
+					Lto::Fat => "-flto",
+				});
+			}
+			if var_os("AR").is_none()
+				&& !self.explicit_archiver
+				&& let Some(archiver) = lto_archiver(&tool)
+			{
+				cc.archiver(archiver);
+			}
+		}
+		if let Some(pgo) = self.pgo.as_ref() {
+			if tool.is_like_msvc() {
+				return Err(IoError::new(
+					IoErrorKind::Unsupported, "profile-guided optimization of static libraries is not supported by MSVC",
+				).into())
+			}
+			match pgo {
+				Pgo::Generate(dir) => {
+					let mut flag = OsString::from("-fprofile-generate=");
+					flag.push(dir);
+					cc.flag(flag);
+					// Rust links with `-nodefaultlibs`, which keeps GCC from adding `libgcov` by itself.
+					link_args.push(String::from(if tool.is_like_gnu() { "-lgcov" } else { "-fprofile-generate" }));
+				}
+				Pgo::Use(dir) => {
+					println!("cargo:rerun-if-changed={}", dir.display());
+					let mut flag = OsString::from("-fprofile-use=");
+					flag.push(dir);
+					cc.flag(flag);
+					if tool.is_like_gnu() {
+						cc.flag("-Wno-missing-profile");
+					}
+				}
+			}
+		}
+		if self.coverage {
+			if tool.is_like_msvc() {
+				return Err(IoError::new(IoErrorKind::Unsupported, "coverage instrumentation is not supported by MSVC").into())
+			} else if tool.is_like_gnu() {
+				cc.flag("--coverage");
+				link_args.push(String::from("-lgcov"));
+			} else {
+				cc.flag("-fprofile-instr-generate").flag("-fcoverage-mapping");
+				link_args.push(String::from("-fprofile-instr-generate"));
+			}
+		}
+		if self.gc_sections {
+			if tool.is_like_msvc() {
+				cc.flag("/Gy").flag("/Gw");
+				link_args.push(String::from("/OPT:REF"));
+			} else {
+				cc.flag("-ffunction-sections").flag("-fdata-sections");
+				link_args.push(String::from(match self.effective_target().contains("-apple-") {
+					true => "-Wl,-dead_strip",
+					false => "-Wl,--gc-sections",
+				}));
+			}
+		}
+		if let Some(version) = self.apple_deployment_target.as_deref()
+			&& let Some((flag, env)) = apple_version_min(&self.effective_target())
+		{
+			// `cc` passes a flag of its own derived from the environment, which a later one overrides,
+			// and the variable is set as well for the tools that read it instead.
+			cc.env(env, version);
+			if let Some(flag) = flag {
+				cc.flag(format!("{flag}={version}"));
+			}
+		}
+		if let Some(level) = self.android_api_level {
+			let target = self.effective_target();
+			if target.contains("-android") {
+				if tool.is_like_clang() {
+					// The API level is part of the target triple of Clang, which defines `__ANDROID_API__` from it.
+					let arch = target.split('-').next().unwrap_or_default();
+					let rest = &target[arch.len()..];
+					let arch = if arch.starts_with("armv7") || arch.starts_with("thumbv7") { "armv7a" } else { arch };
+					cc.flag(format!("--target={arch}{rest}{level}"));
+				} else {
+					cc.define("__ANDROID_API__", Some(level.to_string().as_str()));
+				}
+				if level < 21 {
+					// `localeconv` is only available from API level 21, and only the C locale before it.
+					// `system` is available at every level, so `LUNKA_NOSYSTEM` isn't needed.
+					cc.define("LUNKA_NOLOCALE", None);
+				}
+			}
+		}
+		if self.uwp && self.effective_target().contains("-windows") {
+			cc.define("WINAPI_FAMILY", Some("WINAPI_FAMILY_APP"));
+			for hook in ["LUNKA_NOSYSTEM", "LUNKA_NOTMPNAM", "LUNKA_NOPOPEN"] {
+				cc.define(hook, None);
+			}
+		}
+		if self.effective_target().contains("emscripten") {
+			link_args.extend(self.emscripten_settings.iter().cloned());
+		}
+		if !self.sanitizers.is_empty() && !tool.is_like_msvc() {
+			cc.flag("-fno-omit-frame-pointer");
+		}
+		if (self.private_symbols || self.hidden_symbols) && !tool.is_like_msvc() {
+			cc.flag("-fvisibility=hidden");
+		}
+		if let Some(prefix) = self.symbol_prefix.as_deref() {
+			let header = generate::write_file(&self.gen_dir()?, "lunka_prefix.h", generate::prefix_header(prefix))?;
+			force_include(&mut cc, &tool, &header);
+		}
+		if self.global_lock.is_some() {
+			let header = generate::write_file(&self.gen_dir()?, "lunka_lock.h", generate::lock_header())?;
+			force_include(&mut cc, &tool, &header);
+		}
+		if self.write_function.is_some() || self.write_error_function.is_some() {
+			let header = generate::output_header(self.write_function.as_deref(), self.write_error_function.as_deref());
+			let header = generate::write_file(&self.gen_dir()?, "lunka_output.h", header)?;
+			force_include(&mut cc, &tool, &header);
+		}
+
+		let mut trees = if self.stage_sources || self.generate_luaconf || !self.patches.is_empty() {
+			self.stage_trees(&self.out_subdir("lunka-src-staged")?)?
+		} else {
+			self.trees.clone()
+		};
+		let mut defines = Vec::new();
+		if !self.generate_luaconf {
+			defines.extend(self.effective_defines().cloned());
+			defines.extend(self.overrides.iter().map(move |o| (o.hook.to_owned(), o.value.clone())));
+			if let Some(local) = self.luaconf_local.as_deref() {
+				defines.push((String::from("LUNKA_LOCAL_H"), Some(format!("\"{}\"", file_name(local)?))));
+			}
+			// Values that the command line of `cl`-style compilers or `.cmd` launchers would mangle
+			// are passed through a forcibly included header instead.
+			let launcher_is_cmd = self.launcher.is_some() && cfg!(windows);
+			let mut header_defines = Vec::new();
+			for (name, value) in defines.iter() {
+				match value.as_deref() {
+					Some(value) if define_needs_header(tool.is_like_msvc(), launcher_is_cmd, value) => header_defines.push((name.as_str(), value)),
+					value => {
+						cc.define(name, value);
+					}
+				}
+			}
+			if !header_defines.is_empty() {
+				let header = generate::write_file(&self.gen_dir()?, "lunka_defines.h", generate::defines_header(&header_defines))?;
+				force_include(&mut cc, &tool, &header);
+			}
+		}
+		for tree in trees.iter() {
+			cc.include(&tree.include_dir);
+		}
+		let libc_root = Path::new(env!("CARGO_MANIFEST_DIR")).join("wasm-libc");
+		if self.wasm_libc {
+			cc.include(libc_root.join("include"));
+		}
+		let local_dir = match self.luaconf_local.as_deref().and_then(Path::parent) {
+			Some(dir) if !self.generate_luaconf => Some(if dir.as_os_str().is_empty() { PathBuf::from(".") } else { dir.to_owned() }),
+			_ => None,
+		};
+		if let Some(dir) = local_dir.as_deref() {
+			cc.include(dir);
+		}
+		if self.deterministic {
+			for tree in trees.iter_mut() {
+				tree.files.sort();
+			}
+		}
+		if !self.gc_params.is_empty() {
+			for tree in trees.iter_mut() {
+				for file in tree.files.iter_mut().filter(move |path| path.file_name() == Some(OsStr::new("lstate.c"))) {
+					*file = generate::write_file(&self.gen_dir()?, "lunka_lstate.c", generate::gc_params_source(file, &self.gc_params))?;
+				}
+			}
+		}
+		if self.provenance {
+			let note = provenance::Provenance::new(self.config_hash()).to_string();
+			for tree in trees.iter_mut() {
+				let is_lstate = move |path: &&mut PathBuf| matches!(
+					path.file_name().and_then(move |s| s.to_str()), Some("lstate.c" | "lunka_lstate.c"),
+				);
+				for file in tree.files.iter_mut().filter(is_lstate) {
+					*file = generate::write_file(&self.gen_dir()?, "lunka_provenance.c", generate::provenance_source(file, &note))?;
+				}
+			}
+		}
+		if self.safe_debug {
+			for tree in trees.iter_mut() {
+				for file in tree.files.iter_mut().filter(move |path| path.file_name() == Some(OsStr::new("ldblib.c"))) {
+					*file = generate::write_file(&self.gen_dir()?, "lunka_ldblib.c", generate::safe_debug_source(file))?;
+				}
+			}
+		}
+		if self.allocator.is_some() || self.memory_limit.is_some() {
+			let max_size = match self.pointer_size() {
+				bytes @ 1..8 => (1 << (bytes * 8)) - 1,
+				_ => u64::MAX,
+			};
+			let memory_limit = self.memory_limit.map(move |bytes| bytes.min(max_size));
+			for tree in trees.iter_mut() {
+				for file in tree.files.iter_mut().filter(move |path| path.file_name() == Some(OsStr::new("lauxlib.c"))) {
+					let source = generate::lauxlib_source(
+						file, self.allocator.as_deref(), memory_limit, self.symbol_prefix.as_deref(),
+					);
+					*file = generate::write_file(&self.gen_dir()?, "lunka_lauxlib.c", source)?;
+				}
+			}
+		}
+		let uwp = self.uwp && self.effective_target().contains("-windows");
+		if self.time_hooks.is_some() || uwp {
+			for tree in trees.iter_mut() {
+				for file in tree.files.iter_mut().filter(move |path| path.file_name() == Some(OsStr::new("loslib.c"))) {
+					let source = generate::loslib_source(file, self.time_hooks.as_ref(), uwp);
+					*file = generate::write_file(&self.gen_dir()?, "lunka_loslib.c", source)?;
+				}
+			}
+		}
+		if uwp {
+			for tree in trees.iter_mut() {
+				for file in tree.files.iter_mut().filter(move |path| path.file_name() == Some(OsStr::new("loadlib.c"))) {
+					*file = generate::write_file(&self.gen_dir()?, "lunka_loadlib.c", generate::uwp_loadlib_source(file))?;
+				}
+			}
+		}
+		// C modules are still compiled as C, with unwind tables for the exceptions thrown through them.
+		let c_modules = self.cpp_exceptions.then(|| {
+			let mut c_cc = cc.clone();
+			if let Some(std) = c_std {
+				c_cc.std(std);
+			}
+			if tool.is_like_msvc() {
+				c_cc.flag("/TC");
+			} else {
+				c_cc.flag("-xc").flag("-fexceptions");
+			}
+			c_cc
+		});
+		if self.cpp_exceptions {
+			// after the headers that configure `lauxlib.h`, which this includes
+			cc.flag(if tool.is_like_msvc() { "/TP" } else { "-xc++" });
+			let header = generate::write_file(&self.gen_dir()?, "lunka_cpp.h", generate::cpp_header())?;
+			force_include(&mut cc, &tool, &header);
+		}
+		let base = cc.clone();
+		let mut groups: Vec<(SourceGroup, CcBuild)> = Vec::new();
+		let mut stems = Vec::new();
+		let mut add_files = |group: SourceGroup, files: &mut dyn Iterator<Item = &Path>| {
+			let files = files.inspect(|path| stems.extend(path.file_stem().and_then(move |s| s.to_str()).map(str::to_owned)));
+			let warnings = self.group_warnings.iter().rfind(move |(g, _)| *g == group).map(move |(_, w)| *w);
+			let group_base = match (group, c_modules.as_ref()) {
+				(SourceGroup::Modules, Some(c_cc)) => c_cc,
+				_ if warnings.is_none() => {
+					cc.files(files);
+					return
+				}
+				_ => &base,
+			};
+			let index = match groups.iter().position(move |(g, _)| *g == group) {
+				Some(index) => index,
+				None => {
+					let mut group_cc = group_base.clone();
+					if let Some(warnings) = warnings {
+						group_cc.warnings(warnings.enabled).extra_warnings(warnings.extra).warnings_into_errors(warnings.into_errors);
+						// `base` already has them if warnings are errors for the whole build.
+						if warnings.into_errors && !self.warnings_into_errors {
+							warnings::downgrade_benign(&mut group_cc, &tool);
+						}
+					}
+					groups.push((group, group_cc));
+					groups.len() - 1
+				}
+			};
+			groups[index].1.files(files);
+		};
+		let tree_group = move |tree: &SourceTree| if tree.bundled { SourceGroup::Bundled } else { SourceGroup::Added };
+		if self.stdlibs.is_some() || !self.modules.is_empty() || !self.builtin_chunks.is_empty() {
+			let libs = self.stdlibs.as_deref().unwrap_or(&StdLib::ALL);
+			let is_excluded = move |path: &Path| path.file_name().and_then(move |s| s.to_str()).is_some_and(move |name| {
+				name == "linit.c" || StdLib::ALL.iter().any(move |lib| !libs.contains(lib) && lib.source_file() == name)
+			});
+			for tree in trees.iter() {
+				add_files(tree_group(tree), &mut tree.files.iter().map(PathBuf::as_path).filter(move |path| !is_excluded(path)));
+			}
+			let preloads: Vec<(&str, &str)> = self.modules.iter()
+				.map(move |m| (m.name.as_str(), m.open_function.as_str()))
+				.collect();
+			let builtin_searcher = !self.builtin_chunks.is_empty();
+			let linit = generate::write_file(&self.gen_dir()?, "linit.c", generate::linit(libs, &preloads, builtin_searcher))?;
+			add_files(SourceGroup::Bundled, &mut [linit.as_path()].into_iter());
+			if builtin_searcher {
+				let mut chunks = Vec::with_capacity(self.builtin_chunks.len());
+				for (name, path) in self.builtin_chunks.iter() {
+					chunks.push((name.as_str(), read(path)?));
+				}
+				let source = generate::write_file(&self.gen_dir()?, "lunka_chunks.c", generate::builtin_chunks(&chunks))?;
+				add_files(SourceGroup::Bundled, &mut [source.as_path()].into_iter());
+			}
+		} else {
+			for tree in trees.iter() {
+				add_files(tree_group(tree), &mut tree.files.iter().map(PathBuf::as_path));
+			}
+		}
+		for module in self.modules.iter() {
+			add_files(SourceGroup::Modules, &mut module.files.iter().map(PathBuf::as_path));
+		}
+		if self.wasm_libc {
+			let mut sources = Vec::new();
+			for result in read_dir(libc_root.join("src"))? {
+				let path = result?.path();
+				if path.extension() == Some(OsStr::new("c")) {
+					sources.push(path);
+				}
+			}
+			sources.sort();
+			add_files(SourceGroup::Bundled, &mut sources.iter().map(PathBuf::as_path));
+		}
+		let mut link_libs = self.link_libs.clone();
+		if let Some(lock) = self.global_lock {
+			let source = generate::write_file(&self.gen_dir()?, "lunka_lock.c", generate::lock_source(lock))?;
+			add_files(SourceGroup::Bundled, &mut [source.as_path()].into_iter());
+			let target = self.effective_target();
+			let separate_pthread = !["android", "apple", "windows", "wasm", "emscripten"].iter().any(move |t| target.contains(t));
+			if lock == LockImpl::Pthread && separate_pthread && !link_libs.iter().any(move |lib| lib == "pthread") {
+				link_libs.push(String::from("pthread"));
+			}
+		}
+
+		if let Some(write_error) = self.write_error_function.as_deref() {
+			let source = generate::write_file(&self.gen_dir()?, "lunka_output.c", generate::output_source(write_error))?;
+			add_files(SourceGroup::Bundled, &mut [source.as_path()].into_iter());
+		}
+		if self.cpp_exceptions && let Some(stdlib) = self.cpp_link_stdlib(&tool) {
+			// linked along with the other libraries, as `cc` doesn't when compiling through hooks
+			cc.cpp_link_stdlib(None);
+			link_libs.push(stdlib);
+		}
+		let cross = var_os("HOST").is_some_and(move |host| host != self.effective_target().as_str());
+		let readline = readline::probe(self.readline, cross);
+		for dir in readline.lib_dirs.iter() {
+			link_args.push(match tool.is_like_msvc() {
+				true => format!("/LIBPATH:{}", dir.display()),
+				false => format!("-L{}", dir.display()),
+			});
+		}
+
+		let artifacts = Artifacts {
+			lib_name: lib_name.to_owned(),
+			lib_dir,
+			lib_path: None,
+			include_dirs: trees.into_iter().map(move |tree| tree.include_dir)
+				.chain(local_dir)
+				.chain(self.wasm_libc.then(move || libc_root.join("include")))
+				.chain(readline.include_dirs)
+				.collect(),
+			defines,
+			link_libs: readline.link_libs.into_iter().chain(link_libs).collect(),
+			link_args,
+		};
+		Ok((cc, artifacts, groups.into_iter().map(move |(_, group_cc)| group_cc).collect(), stems))
+	}
+
+	/// Only include the standard libraries in `libs` in this build.
+	/// 
+	/// The source files of all other libraries are not compiled,
+	/// and `linit.c` is replaced with a generated one,
+	/// so that `luaL_openlibs` only opens the selected libraries.
+	pub fn stdlibs(&mut self, libs: &[StdLib]) -> &mut Self {
+		self.stdlibs = Some(libs.to_vec());
+		self
+	}
+
+	/// Leave the standard library `lib` out of this build.
+	/// 
+	/// See [`Build::stdlibs`] for more details.
+	pub fn without_stdlib(&mut self, lib: StdLib) -> &mut Self {
+		self.stdlibs.get_or_insert_with(move || StdLib::ALL.to_vec()).retain(move |&l| l != lib);
+		self
+	}
+
+	/// Leave the `io` library out of this build.
+	pub fn without_io(&mut self) -> &mut Self {
+		self.without_stdlib(StdLib::Io)
+	}
+
+	/// Leave the `os` library out of this build.
+	pub fn without_os(&mut self) -> &mut Self {
+		self.without_stdlib(StdLib::Os)
+	}
+
+	/// Leave the `debug` library out of this build.
+	pub fn without_debug(&mut self) -> &mut Self {
+		self.without_stdlib(StdLib::Debug)
+	}
+
+	/// Set whether a finalized `luaconf.h` should be generated for this build.
+	/// 
+	/// If enabled, then the sources are staged (see [`Build::stage_sources`])
+	/// along with a `luaconf.h` that has all of the settings of this build baked in,
+	/// instead of passing them as definitions on the command line.
+	/// This also allows [`LuaConf`] to be used with unmodified Lua distributions.
+	/// 
+	/// See [`conf_gen::render`] for how the header is generated.
+	pub fn generate_luaconf(&mut self, generate_luaconf: bool) -> &mut Self {
+		self.generate_luaconf = generate_luaconf;
+		self
+	}
+
+	/// Add the contents of the header at `path`, such as `luaconf.local.h`, to the end of `luaconf.h`,
+	/// after the definitions and overrides set by this build.
+	/// 
+	/// This is a middle ground between setting options one by one and maintaining a modified `luaconf.h`,
+	/// as the header can redefine anything in it with `#undef` and `#define`.
+	/// If [`Build::generate_luaconf`] is enabled, then the contents are spliced into the generated header.
+	/// Otherwise, the header is included by the `luaconf.h` of lunka-src through `LUNKA_LOCAL_H`,
+	/// and its directory is added to the include path,
+	/// so other `luaconf.h` files can't use it.
+	pub fn luaconf_local<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		let path = path.as_ref();
+		println!("cargo:rerun-if-changed={}", path.display());
+		self.luaconf_local = Some(path.to_owned());
+		self
+	}
+
+	/// Render a finalized `luaconf.h` for the first of the added Lua sources,
+	/// panicking if no sources were added or the original header could not be read.
+	/// 
+	/// See also [`Build::try_render_luaconf`] for the non-panicking version.
+	pub fn render_luaconf(&self) -> String {
+		match self.try_render_luaconf() {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Render a finalized `luaconf.h` for the first of the added Lua sources.
+	/// 
+	/// This is the header that would be generated if [`Build::generate_luaconf`] was enabled.
+	pub fn try_render_luaconf(&self) -> Result<String, IoError> {
+		let tree = self.trees.first().ok_or_else(move || {
+			IoError::new(IoErrorKind::NotFound, "no Lua sources were added to the build")
+		})?;
+		self.render_luaconf_for(tree)
+	}
+
+	fn render_luaconf_for(&self, tree: &SourceTree) -> Result<String, IoError> {
+		let original = read_to_string(tree.include_dir.join("luaconf.h"))?;
+		self.render_luaconf_from(&original)
+	}
+
+	fn render_luaconf_from(&self, original: &str) -> Result<String, IoError> {
+		let overrides: Vec<Define> = self.overrides.iter()
+			.map(move |o| (o.target.to_owned(), o.value.clone()))
+			.collect();
+		let defines: Vec<Define> = self.effective_defines().cloned().collect();
+		let rendered = conf_gen::render(original, &defines, &overrides);
+		match self.luaconf_local.as_deref() {
+			Some(local) => Ok(conf_gen::splice_local(&rendered, &read_to_string(local)?)),
+			None => Ok(rendered),
+		}
+	}
+
+	/// Copy the added sources into the directory `stage_dir`,
+	/// applying patches and generating `luaconf.h` as needed.
+	fn stage_trees(&self, stage_dir: &Path) -> Result<Vec<SourceTree>, IoError> {
+		let mut staged_trees = Vec::with_capacity(self.trees.len());
+		for (i, tree) in self.trees.iter().enumerate() {
+			// Each file is `(name, contents, compiled)`.
+			let mut files = Vec::new();
+			for result in read_dir(&tree.include_dir)? {
+				let path = result?.path();
+				if path.extension().is_some_and(move |ext| ext == "h" || ext == "hpp") {
+					files.push((file_name(&path)?, read_to_string(&path)?, false));
+				}
+			}
+			for path in tree.files.iter() {
+				files.push((file_name(path)?, read_to_string(path)?, true));
+			}
+
+			for patch in self.patches.iter().flatten() {
+				match files.iter().position(move |(name, ..)| *name == patch.name) {
+					Some(index) if patch.deletes => {
+						files.remove(index);
+					}
+					Some(index) => files[index].1 = patch.apply(&files[index].1)?,
+					None if patch.creates => {
+						files.push((patch.name.clone(), patch.apply("")?, patch.name.ends_with(".c")));
+					}
+					None => return Err(IoError::new(
+						IoErrorKind::NotFound,
+						format!("patched file {} is not part of the Lua sources", patch.name),
+					)),
+				}
+			}
+
+			if self.generate_luaconf {
+				let Some(conf) = files.iter_mut().find(move |(name, ..)| name == "luaconf.h") else {
+					return Err(IoError::new(IoErrorKind::NotFound, "Lua sources don't have a luaconf.h"))
+				};
+				conf.1 = self.render_luaconf_from(&conf.1)?;
+			}
+
+			let dir = stage_dir.join(format!("tree-{i}"));
+			create_dir_all(&dir)?;
+			let mut staged = SourceTree {
+				include_dir: dir,
+				files: Vec::new(),
+				bundled: tree.bundled,
+			};
+			for (name, contents, compiled) in files {
+				let path = generate::write_file(&staged.include_dir, &name, contents)?;
+				if compiled {
+					staged.files.push(path);
+				}
+			}
+			staged_trees.push(staged);
+		}
+		Ok(staged_trees)
+	}
+
+	/// Iterate over the definitions that actually apply to this build.
+	fn effective_defines(&self) -> impl Iterator<Item = &Define> {
+		self.defines.iter()
+			.filter(move |(name, _)| !(self.private_symbols && matches!(name.as_str(), "LUA_BUILD_AS_DLL" | "LUNKA_HIDDEN_SYMBOLS")))
+	}
+
+	/// Return the value that the configuration `target` would have as set by this build,
+	/// as either a [`Build::lua_conf`] override or a definition,
+	/// or [`None`] if it isn't set.
+	fn conf_value(&self, target: &str) -> Option<Option<&str>> {
+		self.overrides.iter().rfind(move |o| o.target == target).map(move |o| o.value.as_deref())
+			.or_else(move || self.effective_defines().filter(move |(name, _)| name == target).last().map(move |(_, v)| v.as_deref()))
+	}
+
+	/// Return a hash of every setting of this build that affects the compiled code.
+	fn config_hash(&self) -> u64 {
+		let mut description = String::new();
+		for (name, value) in self.effective_defines() {
+			let _ = writeln!(description, "define {name} {}", value.as_deref().unwrap_or_default());
+		}
+		for o in self.overrides.iter() {
+			let _ = writeln!(description, "override {} {}", o.hook, o.value.as_deref().unwrap_or_default());
+		}
+		for (name, value) in self.gc_params.iter() {
+			let _ = writeln!(description, "gc {name} {value}");
+		}
+		if let Some(libs) = self.stdlibs.as_deref() {
+			let _ = writeln!(description, "stdlibs {libs:?}");
+		}
+		for module in self.modules.iter() {
+			let _ = writeln!(description, "module {} {}", module.name, module.open_function);
+		}
+		for (name, _) in self.builtin_chunks.iter() {
+			let _ = writeln!(description, "chunk {name}");
+		}
+		let _ = writeln!(
+			description, "prefix {:?} private {} patches {} lto {:?} sanitizers {:?} cpp {}",
+			self.symbol_prefix, self.private_symbols, self.patches.len(), self.lto, self.sanitizers, self.cpp_exceptions,
+		);
+		let _ = writeln!(description, "allocator {:?} memory_limit {:?}", self.allocator, self.memory_limit);
+		for (name, function) in self.time_hooks.iter().flat_map(TimeHooks::functions) {
+			let _ = writeln!(description, "time_hook {name} {function}");
+		}
+		let _ = writeln!(
+			description, "safe_debug {} global_lock {:?} write {:?} write_error {:?} uwp {} conservative_codegen {}",
+			self.safe_debug, self.global_lock, self.write_function, self.write_error_function, self.uwp, self.conservative_codegen,
+		);
+		provenance::hash(description.as_bytes())
+	}
+
+	/// Return the directory for files generated by this crate, creating it if it doesn't exist.
+	fn gen_dir(&self) -> Result<PathBuf, IoError> {
+		self.out_subdir("lunka-src")
+	}
+
+	/// Return the subdirectory `name` of the build directory, creating it if it doesn't exist.
+	fn out_subdir(&self, name: &str) -> Result<PathBuf, IoError> {
+		let dir = self.build_root()?.join(name);
+		create_dir_all(&dir)?;
+		Ok(dir)
+	}
+
+	/// Return the directory for the objects and the library of this build.
+	/// 
+	/// This is the output directory, or a subdirectory of it named after the target triple
+	/// if the target was set with [`Build::target`] to one that differs from the default,
+	/// so that builds for multiple targets don't overwrite each other's objects.
+	fn build_root(&self) -> Result<PathBuf, IoError> {
+		let out_root = self.out_root()?;
+		match self.target.as_deref() {
+			Some(target) if var_os("TARGET").is_none_or(move |default| default != target) => Ok(out_root.join(target)),
+			_ => Ok(out_root),
+		}
+	}
+
+	/// Return the output directory, which is either set with [`Build::out_dir`] or taken from `OUT_DIR`.
+	fn out_root(&self) -> Result<PathBuf, IoError> {
+		match &self.out_dir {
+			Some(out_dir) => Ok(out_dir.clone()),
+			None => var_os("OUT_DIR").map(PathBuf::from).ok_or_else(move || {
+				IoError::new(IoErrorKind::NotFound, "environment variable OUT_DIR is not set")
+			}),
+		}
+	}
+
+	/// Set whether a prebuilt Lua library may be used instead of compiling one.
+	/// 
+	/// If enabled, and both [`External::LIB_DIR_VAR`] and [`External::INCLUDE_DIR_VAR`] are set,
+	/// then compilation is skipped entirely.
+	/// See [`External::from_env`] for more details.
+	pub fn external(&mut self, allow_external: bool) -> &mut Self {
+		self.external = allow_external;
+		self
+	}
+
+	/// Set the target triple that this configuration compiles for.
+	/// 
+	/// If it differs from the default one, which is taken from `TARGET`,
+	/// then the objects and the library are put in a subdirectory of the output directory named after it.
+	pub fn target(&mut self, target: &str) -> &mut Self {
+		self.cc.target(target);
+		self.target = Some(target.to_owned());
+		self
+	}
+
+	/// Set the host assumed by this configuration.
+	pub fn host(&mut self, host: &str) -> &mut Self {
+		self.cc.host(host);
+		self
+	}
+
+	/// Set whether an advisory lock should be held on the output directory while compiling,
+	/// so that concurrent builds sharing the directory don't corrupt each other's files.
+	/// 
+	/// This is enabled by default if the output directory was set with [`Build::out_dir`],
+	/// since directories set by Cargo through `OUT_DIR` are never shared.
+	/// The lock is the file `.lunka-src.lock` in the output directory.
+	pub fn lock_out_dir(&mut self, lock_out_dir: bool) -> &mut Self {
+		self.lock_out_dir = Some(lock_out_dir);
+		self
+	}
+
+	/// Set the output directory where all object files and static libraries will be located.
+	pub fn out_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		self.out_dir = Some(path.as_ref().to_owned());
+		self.cc.out_dir(path);
+		self
+	}
+
+	/// Define the macro `name` without a value, as if with `-Dname`.
+	/// 
+	/// This allows for setting any configuration macro of Lua that doesn't have a dedicated method.
+	pub fn define(&mut self, name: &str) -> &mut Self {
+		self.defines.push((name.to_owned(), None));
+		self
+	}
+
+	/// Define the macro `name` to `value`, as if with `-Dname=value`.
+	/// 
+	/// `value` is used verbatim, so string values must be quoted,
+	/// or passed to [`Build::define_string`] instead.
+	pub fn define_value(&mut self, name: &str, value: &str) -> &mut Self {
+		self.defines.push((name.to_owned(), Some(value.to_owned())));
+		self
+	}
+
+	fn define_override(&mut self, hook: &'static str, target: &'static str, value: Option<&str>) -> &mut Self {
+		self.overrides.push(ConfOverride {
+			hook,
+			target,
+			value: value.map(move |s| s.to_owned()),
+		});
+		self
+	}
+
+	/// Define the macro `name` to a C string literal with the contents `value`,
+	/// escaping it as needed.
+	pub fn define_string(&mut self, name: &str, value: &str) -> &mut Self {
+		let value = format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+		self.define_value(name, &value)
+	}
+
+	/// Apply all simple `#define NAME value` directives found in the C header fragment at `path`,
+	/// panicking if an error occurs while reading the file.
+	/// 
+	/// See also [`Build::try_defines_from_header`] for the non-panicking version.
+	pub fn defines_from_header<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		match self.try_defines_from_header(path) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Apply all simple `#define NAME value` directives found in the C header fragment at `path`.
+	/// 
+	/// This allows for the configuration to be kept in a single file that is shared with builds that don't use Cargo.
+	/// Only object-like macros are applied;
+	/// function-like macros, conditionals and other directives are ignored.
+	pub fn try_defines_from_header<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, IoError> {
+		let path = path.as_ref();
+		let text = read_to_string(path)?;
+		println!("cargo:rerun-if-changed={}", path.display());
+		self.defines.extend(header::parse_defines(&text));
+		Ok(self)
+	}
+
+	/// Set whether the Lua sources should be copied into `lunka-src-staged` in the output directory,
+	/// and compiled from there.
+	/// 
+	/// This keeps the original source directories read-only during compilation.
+	/// Sources are always staged if [`Build::generate_luaconf`] or [`Build::apply_patch`] is used.
+	/// Files in the staging directory are only rewritten when their contents change.
+	pub fn stage_sources(&mut self, stage_sources: bool) -> &mut Self {
+		self.stage_sources = stage_sources;
+		self
+	}
+
+	/// Apply the unified diff at `path` to a copy of the Lua sources before compiling them,
+	/// panicking if the file could not be read or is not a valid diff.
+	/// 
+	/// See also [`Build::try_apply_patch`] for the non-panicking version.
+	pub fn apply_patch<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		match self.try_apply_patch(path) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Apply the unified diff at `path` to a copy of the Lua sources before compiling them.
+	/// 
+	/// Patches are applied in the order that they were added,
+	/// to copies of the sources that are staged in the output directory,
+	/// so the original sources are never modified.
+	/// Files are matched by their names alone, ignoring any directories in the diff,
+	/// and new `.c` files created by a patch are compiled along with the rest.
+	/// 
+	/// If a patch does not apply to the sources, then [`Build::try_compile`] fails.
+	pub fn try_apply_patch<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, IoError> {
+		let path = path.as_ref();
+		let text = read_to_string(path)?;
+		println!("cargo:rerun-if-changed={}", path.display());
+		self.patches.push(patch::parse(&text)?);
+		Ok(self)
+	}
+
+	/// Add all Lua 5.4.8 source files bundled with this crate,
+	/// which allows for [`LuaConf`] to be used,
+	/// panicking if an error occurs while reading the directory contents.
+	pub fn add_lunka_src(&mut self) -> &mut Self {
+		match self.try_add_lunka_src() {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Add all Lua 5.4.8 source files bundled with this crate,
+	/// which allows for [`LuaConf`] to be used.
+	pub fn try_add_lunka_src(&mut self) -> Result<&mut Self, IoError> {
+		let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("lua-5.4.8");
+		let mut files = Vec::new();
+		for result in read_dir(root.join("src"))? {
+			let item = result?;
+			if !item.file_type()?.is_file() {
+				continue
+			}
+			files.push(item.path());
+		}
+		self.trees.push(SourceTree {
+			include_dir: root.join("include"),
+			files,
+			bundled: true,
+		});
+		Ok(self)
+	}
+
+	/// Add all Lua source files found in the specified `root`,
+	/// panicking if an error occurs while reading the directory contents.
+	/// 
+	/// See also [`Build::try_add_lua_src`] for the non-panicking version.
+	/// 
+	/// `root` must be a directory containing both source files (`*.c`) and headers (`*.h`).
+	/// 
+	/// If [`LuaConf`] is used,
+	/// then the path cannot point to a normal Lua source distribution.
+	/// See the documentation for [`LuaConf`] for more details.
+	pub fn add_lua_src<P: AsRef<Path>>(&mut self, root: P) -> &mut Self {
+		match self.try_add_lua_src(root) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Add all Lua source files found in the specified `root`.
+	/// 
+	/// `root` must be a directory containing both source files (`*.c`) and headers (`*.h`).
+	/// 
+	/// If [`LuaConf`] is used,
+	/// then the path cannot point to a normal Lua source distribution.
+	/// See the documentation for [`LuaConf`] for more details.
+	pub fn try_add_lua_src<P: AsRef<Path>>(&mut self, root: P) -> Result<&mut Self, IoError> {
+		const BINARIES: [&str; 2] = ["lua.c", "luac.c"];
+		let root = root.as_ref();
+		let mut files = Vec::new();
+		for result in read_dir(root)? {
+			let item = result?;
+			if !item.file_type()?.is_file() {
+				continue
+			}
+
+			let file_name = item.file_name();
+			let Some(file_name) = file_name.to_str() else {
+				continue
+			};
+			if !file_name.ends_with(".c") || BINARIES.contains(&file_name) {
+				continue
+			}
+
+			files.push(item.path());
+		}
+		self.trees.push(SourceTree {
+			include_dir: root.to_owned(),
+			files,
+			bundled: false,
+		});
+		Ok(self)
+	}
+
+	/// Compile the C module `name` into the library,
+	/// and make it available to `require` without loading it dynamically,
+	/// panicking if an error occurs while looking for source files.
+	/// 
+	/// See also [`Build::try_add_c_module`] for the non-panicking version.
+	pub fn add_c_module(&mut self, name: &str, sources: &[&str], open_function: &str) -> &mut Self {
+		match self.try_add_c_module(name, sources, open_function) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Compile the C module `name` into the library,
+	/// and make it available to `require` without loading it dynamically.
+	/// 
+	/// `sources` are paths to the source files of the module,
+	/// where the file name may contain `*` and `?` wildcards, as in `lpeg/*.c`.
+	/// `open_function` is the name of the function that opens the module, such as `luaopen_lpeg`.
+	/// 
+	/// `linit.c` is replaced with a generated one,
+	/// which makes `luaL_openlibs` add the opening functions of all modules to `package.preload`.
+	pub fn try_add_c_module(&mut self, name: &str, sources: &[&str], open_function: &str) -> Result<&mut Self, IoError> {
+		let mut files = Vec::new();
+		for pattern in sources {
+			files.extend(glob(Path::new(pattern))?);
+		}
+		self.modules.push(CModule {
+			name: name.to_owned(),
+			files,
+			open_function: open_function.to_owned(),
+		});
+		Ok(self)
+	}
+
+	/// Embed the Lua chunk at `path` into the library as the module `name`,
+	/// so that `require(name)` loads it without looking for files.
+	/// 
+	/// The chunk can be Lua source code or bytecode precompiled with `luac` for the same configuration.
+	/// `luaL_openlibs` adds a searcher for embedded chunks to `package.searchers`
+	/// right after the one for `package.preload`, so they take precedence over files.
+	/// This replaces `linit.c` with a generated one, as with [`Build::add_c_module`].
+	pub fn builtin_chunk<P: AsRef<Path>>(&mut self, name: &str, path: P) -> &mut Self {
+		let path = path.as_ref();
+		println!("cargo:rerun-if-changed={}", path.display());
+		self.builtin_chunks.push((name.to_owned(), path.to_owned()));
+		self
+	}
+
+	/// Add an include directory.
+	pub fn include<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		self.cc.include(path);
+		self
+	}
+
+	/// Adds multiple include directories.
+	pub fn includes<P>(&mut self, paths: P) -> &mut Self
+	where
+		P: IntoIterator,
+		P::Item: AsRef<Path>,
+	{
+		self.cc.includes(paths);
+		self
+	}
+
+	/// Set whether Cargo metadata for linking to the compiled library should be emitted.
+	pub fn cargo_metadata(&mut self, cargo_metadata: bool) -> &mut Self {
+		self.cc.cargo_metadata(cargo_metadata);
+		self.cargo_metadata = cargo_metadata;
+		self
+	}
+
+	/// Instrument the compiled code with `sanitizer`.
+	/// 
+	/// Sanitizers can be combined by calling this again.
+	/// The flags that link in the sanitizer runtime are recorded in [`Artifacts::link_args`],
+	/// and emitted as Cargo metadata along with the rest if [`Build::cargo_metadata`] is enabled.
+	/// Only [`Sanitizer::Address`] is supported by MSVC, which links its runtime by itself,
+	/// and [`Sanitizer::Memory`] is not supported by GCC.
+	pub fn sanitizer(&mut self, sanitizer: Sanitizer) -> &mut Self {
+		if !self.sanitizers.contains(&sanitizer) {
+			self.sanitizers.push(sanitizer);
+		}
+		self
+	}
+
+	/// Enable link-time optimization of kind `lto`,
+	/// which lets the compiler inline across the translation units of Lua,
+	/// notably into the interpreter loop.
+	/// 
+	/// The archiver is switched to one that understands LTO objects (`gcc-ar` or `llvm-ar`),
+	/// unless one is specified with `AR`.
+	/// With GCC, the objects also contain regular machine code so that the library can be linked normally,
+	/// while with Clang, linking requires a linker that is able to perform LTO,
+	/// such as with `-C linker-plugin-lto`.
+	pub fn lto(&mut self, lto: Lto) -> &mut Self {
+		self.lto = Some(lto);
+		self
+	}
+
+	/// Instrument the compiled code to write execution profiles into `dir`,
+	/// for the first phase of profile-guided optimization.
+	/// 
+	/// After running a representative workload,
+	/// the library can be rebuilt with the profiles using [`Build::pgo_use`].
+	/// With Clang, the raw profiles must first be merged into `default.profdata` with `llvm-profdata merge`.
+	/// Profile-guided optimization is not supported by MSVC.
+	pub fn pgo_generate<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+		self.pgo = Some(Pgo::Generate(dir.as_ref().to_owned()));
+		self
+	}
+
+	/// Optimize the compiled code using the execution profiles in `dir`,
+	/// which were produced by a build using [`Build::pgo_generate`].
+	pub fn pgo_use<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+		self.pgo = Some(Pgo::Use(dir.as_ref().to_owned()));
+		self
+	}
+
+	/// Set whether the compiled code should be instrumented for coverage measurement.
+	/// 
+	/// With GCC, this uses `--coverage` for `gcov`,
+	/// and with Clang, source-based coverage for `llvm-cov`.
+	/// The flags that link in the profiling runtime are recorded in [`Artifacts::link_args`].
+	/// Coverage instrumentation is not supported by MSVC.
+	pub fn coverage(&mut self, coverage: bool) -> &mut Self {
+		self.coverage = coverage;
+		self
+	}
+
+	/// Compile the sources of `group` with `warnings`,
+	/// instead of the settings used for the rest of the build.
+	/// 
+	/// This allows for the bundled sources to be compiled with full warnings,
+	/// while patched trees or third-party modules are compiled with a relaxed set.
+	pub fn group_warnings(&mut self, group: SourceGroup, warnings: Warnings) -> &mut Self {
+		self.group_warnings.push((group, warnings));
+		self
+	}
+
+	/// Pass `flag` to the compiler.
+	/// 
+	/// See also [`Build::flag_for`] for passing a flag only to some kind of compiler.
+	pub fn flag<S: AsRef<OsStr>>(&mut self, flag: S) -> &mut Self {
+		self.cc.flag(flag);
+		self
+	}
+
+	/// Pass `flag` to the compiler if it's supported by it.
+	/// 
+	/// Whether the flag is supported is checked by compiling a test file when this build is compiled.
+	pub fn flag_if_supported<S: AsRef<OsStr>>(&mut self, flag: S) -> &mut Self {
+		self.cc.flag_if_supported(flag);
+		self
+	}
+
+	/// Pass `flag` to the compiler if it's of the kind `family`.
+	pub fn flag_for(&mut self, family: CompilerFamily, flag: &str) -> &mut Self {
+		self.family_flags.push((family, flag.to_owned(), false));
+		self
+	}
+
+	/// Pass `flag` to the compiler if it's of the kind `family` and supports it.
+	pub fn flag_if_supported_for(&mut self, family: CompilerFamily, flag: &str) -> &mut Self {
+		self.family_flags.push((family, flag.to_owned(), true));
+		self
+	}
+
+	/// Compile with the C standard `std`, such as `c11`,
+	/// instead of the one that the [`Standards`](platforms::Standards) of the platform select for the compiler.
+	/// 
+	/// For MSVC and `clang-cl`, the standard is translated like the ones of the platform,
+	/// so `c99` passes no option at all.
+	pub fn std(&mut self, std: &str) -> &mut Self {
+		self.std = Some(std.to_owned());
+		self
+	}
+
+	/// Pass `arg` to the linker of anything that links to the compiled library,
+	/// through [`Artifacts::link_args`].
+	/// 
+	/// This is useful for toolchains, such as Emscripten or proprietary console SDKs,
+	/// that require extra arguments when linking the final executable or shared library.
+	/// `cc` itself only creates static libraries,
+	/// which is why there are no equivalents of its deprecated `shared_flag` and `static_flag`.
+	pub fn link_arg<S: Into<String>>(&mut self, arg: S) -> &mut Self {
+		self.link_args.push(arg.into());
+		self
+	}
+
+	/// Set the Emscripten setting `name` to `value` when linking anything that links to the compiled library
+	/// for the `wasm32-unknown-emscripten` target,
+	/// such as `ALLOW_MEMORY_GROWTH` to `1`.
+	/// 
+	/// The setting is passed as `-s{name}={value}` through [`Artifacts::link_args`],
+	/// as most settings only affect linking, and `emcc` warns about them when compiling.
+	/// The ones that affect compilation can be passed with [`Build::flag`].
+	/// It has no effect on other targets.
+	pub fn emscripten_setting(&mut self, name: &str, value: &str) -> &mut Self {
+		self.emscripten_settings.push(format!("-s{name}={value}"));
+		self
+	}
+
+	/// Compile a minimal C library along with Lua,
+	/// so that it can be built for `wasm32-unknown-unknown`, which has none,
+	/// with [`platforms::WasmUnknown`].
+	/// 
+	/// The library provides memory allocation with `memory.grow`,
+	/// strings, formatting and parsing of numbers, and the math functions.
+	/// The `io` and `os` libraries are left out, as there are no files, processes or clocks.
+	/// Output of `print` is passed to `lunka_libc_write`, which discards it,
+	/// unless the final module defines it with the C signature
+	/// `void lunka_libc_write(int fd, const char *s, size_t n)`.
+	/// 
+	/// There is no way to unwind the stack without the exception handling proposal,
+	/// so `longjmp` traps.
+	/// This means that every Lua error traps, even inside of `pcall`,
+	/// and so does yielding from a coroutine.
+	/// The library is only meant for running trusted scripts that don't raise errors,
+	/// such as configuration.
+	#[cfg(feature = "wasm-libc")]
+	pub fn wasm_libc(&mut self) -> &mut Self {
+		self.wasm_libc = true;
+		self.without_io().without_os()
+	}
+
+	/// Use the compiler at `path` for this build,
+	/// instead of the one selected by `cc` from the environment.
+	/// 
+	/// Unlike setting `CC`, this doesn't affect other users of `cc` in the same build script or workspace.
+	/// It can also be a wrapper script, such as one that runs `zig cc`.
+	pub fn compiler<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		self.cc.compiler(path.as_ref());
+		self
+	}
+
+	/// Use the archiver at `path` to create the static library,
+	/// instead of the one selected by `cc` from the environment.
+	/// 
+	/// This also takes precedence over the archiver selected for [`Build::lto`].
+	pub fn archiver<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		self.cc.archiver(path.as_ref());
+		self.explicit_archiver = true;
+		self
+	}
+
+	/// Pass `flag` to the archiver that creates the static library.
+	pub fn ar_flag<S: AsRef<OsStr>>(&mut self, flag: S) -> &mut Self {
+		self.cc.ar_flag(flag);
+		self
+	}
+
+	/// Set whether the build should be reproducible,
+	/// so that two builds of the same configuration produce bit-identical libraries.
+	/// 
+	/// This maps the source and output directories to fixed paths with `-ffile-prefix-map`
+	/// (or uses `/Brepro` with MSVC), sets `SOURCE_DATE_EPOCH` for the compiler unless it's already set,
+	/// which fixes `__DATE__` and `__TIME__`, and sorts the source files so that the archive members are in a fixed order.
+	/// `cc` already creates archives in deterministic mode where the archiver supports it.
+	pub fn deterministic(&mut self, deterministic: bool) -> &mut Self {
+		self.deterministic = deterministic;
+		self
+	}
+
+	/// Set whether a provenance note should be embedded in the compiled library.
+	/// 
+	/// The note contains the version of this crate, the version of Lua and a hash of the configuration,
+	/// and can be read back from the library or a binary linked to it with [`provenance::Provenance::find`].
+	/// Linkers that discard unreferenced data may still remove it from binaries
+	/// if the compiler doesn't support the `retain` attribute.
+	pub fn provenance(&mut self, provenance: bool) -> &mut Self {
+		self.provenance = provenance;
+		self
+	}
+
+	/// Set how compiler flags from the environment, such as `CFLAGS`, are treated.
+	/// 
+	/// This is useful when the environment exports global flags that are incompatible with Lua.
+	pub fn env_flags_policy(&mut self, policy: EnvFlagsPolicy) -> &mut Self {
+		self.env_flags_policy = policy;
+		self
+	}
+
+	/// Set whether metadata for dependent crates should be emitted after compiling,
+	/// as described in [`Artifacts::emit_cargo_metadata`].
+	/// 
+	/// This is meant to be used together with a `links = "lua54"` key in the manifest,
+	/// so that dependent `-sys` crates can read `DEP_LUA54_INCLUDE` and friends
+	/// instead of deriving the paths themselves.
+	pub fn emit_cargo_metadata(&mut self, emit: bool) -> &mut Self {
+		self.links_metadata = emit;
+		self
+	}
+
+	/// Run every compiler invocation through `launcher`,
+	/// such as `distcc` or `icecc`.
+	/// 
+	/// The compiler is still detected as usual,
+	/// and it is passed to the launcher as the first argument.
+	pub fn compiler_launcher<L: AsRef<OsStr>>(&mut self, launcher: L) -> &mut Self {
+		self.launcher = Some(launcher.as_ref().to_owned());
+		self
+	}
+
+	/// Run at most `jobs` compilers at once when compiling in parallel.
+	/// 
+	/// This only has an effect with the `parallel` feature,
+	/// and the objects and the library are then built by this crate instead of by `cc`,
+	/// which also doesn't exceed the number of jobs that Cargo was given.
+	pub fn max_jobs(&mut self, jobs: usize) -> &mut Self {
+		self.max_jobs = Some(jobs);
+		self
+	}
+
+	/// Limit the number of compilers running at once when compiling in parallel
+	/// so that each of them can use up to `bytes` of memory,
+	/// such as in CI containers with many cores but little memory.
+	/// 
+	/// The available memory is that of the system or the cgroup, whichever is less,
+	/// and can only be determined on Linux.
+	/// If it lowers the number of jobs, a Cargo warning saying so is printed.
+	/// See [`Build::max_jobs`] for the details of limiting the number of jobs.
+	pub fn memory_per_job(&mut self, bytes: u64) -> &mut Self {
+		self.memory_per_job = Some(bytes);
+		self
+	}
+
+	/// Pass every compiler and archiver command through `hook` before it runs.
+	/// 
+	/// `hook` can rewrite the command, such as to run it in a sandbox or on a remote machine,
+	/// or return `false` to veto it, which makes compilation fail.
+	/// With a hook, the objects and the library are built by this crate instead of by `cc`,
+	/// which only supports C sources and doesn't compile in parallel.
+	pub fn command_hook<F>(&mut self, hook: F) -> &mut Self
+	where
+		F: Fn(&mut Command) -> bool + Send + Sync + 'static,
+	{
+		self.command_hook = Some(Arc::new(hook));
+		self
+	}
+
+	/// Set whether the common set of warnings, such as `-Wall`, should be enabled.
+	/// If disabled, all warnings are suppressed.
+	/// 
+	/// This is enabled by default.
+	/// See also [`Build::group_warnings`] for settings that only apply to some of the sources.
+	pub fn warnings(&mut self, warnings: bool) -> &mut Self {
+		self.cc.warnings(warnings);
+		self
+	}
+
+	/// Set whether extra warnings, such as `-Wextra`, should be enabled.
+	/// 
+	/// This is enabled by default.
+	pub fn extra_warnings(&mut self, extra_warnings: bool) -> &mut Self {
+		self.cc.extra_warnings(extra_warnings);
+		self
+	}
+
+	/// Set whether warnings should be treated as errors.
+	/// 
+	/// Warnings that Lua's sources are known to trigger with some compilers,
+	/// despite being benign, are exempt from this.
+	pub fn warnings_into_errors(&mut self, warnings_into_errors: bool) -> &mut Self {
+		self.cc.warnings_into_errors(warnings_into_errors);
+		self.warnings_into_errors = warnings_into_errors;
+		self
+	}
+
+	/// Set whether debug information should be emitted for this build.
+	pub fn debug_info(&mut self, emit_debug_info: bool) -> &mut Self {
+		self.cc.debug(emit_debug_info);
+		self
+	}
+
+	/// Set the amount of debug information to emit with GCC-like compilers,
+	/// overriding [`Build::debug_info`].
+	pub fn debug_level(&mut self, level: DebugLevel) -> &mut Self {
+		self.debug_level = Some(level);
+		self
+	}
+
+	/// Set the DWARF version of debug information emitted with GCC-like compilers.
+	pub fn dwarf_version(&mut self, version: u8) -> &mut Self {
+		self.dwarf_version = Some(version);
+		self
+	}
+
+	/// Set whether type information should be put into a separate section with GCC-like compilers
+	/// (`-fdebug-types-section`), which makes debug information smaller.
+	/// 
+	/// This is ignored if the compiler does not support it.
+	pub fn debug_types_section(&mut self, debug_types_section: bool) -> &mut Self {
+		self.debug_types_section = debug_types_section;
+		self
+	}
+
+	/// Set the semi-arbitrary optimization level for the generated object files.
+	pub fn opt_level(&mut self, opt_level: u32) -> &mut Self {
+		self.cc.opt_level(opt_level);
+		self.explicit_opt_level = true;
+		self
+	}
+
+	/// Set whether a Cargo warning should be emitted when Lua is compiled without optimizations
+	/// because of the profile, which makes the interpreter several times slower.
+	/// 
+	/// This is enabled by default, and the warning is never emitted if [`Build::opt_level`] was used.
+	pub fn warn_unoptimized(&mut self, warn: bool) -> &mut Self {
+		self.warn_unoptimized = warn;
+		self
+	}
+
+	/// Set whether position-independent code should be generated (`-fPIC`),
+	/// or leave it up to `cc` with `None`.
+	/// 
+	/// By default, position-independent code is generated for every target
+	/// except for Windows, bare-metal and WebAssembly ones.
+	/// It is required if the static library is going to be linked into a shared library, such as a `cdylib`,
+	/// and can be disabled for static executables or static-PIE layouts that don't need it.
+	pub fn pic(&mut self, pic: Option<bool>) -> &mut Self {
+		self.pic = pic;
+		self
+	}
+
+	/// Set whether code should be generated for position-independent executables (`-fPIE`)
+	/// with GCC-like compilers.
+	/// 
+	/// This takes precedence over [`Build::pic`],
+	/// and produces code that can only be linked into executables.
+	pub fn pie(&mut self, pie: bool) -> &mut Self {
+		self.pie = pie;
+		self
+	}
+
+	/// Set whether every function and variable should be put into its own section
+	/// (`-ffunction-sections -fdata-sections`, or `/Gy /Gw` with MSVC),
+	/// so that the linker can remove the unused parts of Lua, such as functions of unused standard libraries.
+	/// 
+	/// The linker arguments that enable removing them are added to [`Artifacts::link_args`],
+	/// although `rustc` usually passes them already.
+	pub fn gc_sections_friendly(&mut self, gc_sections: bool) -> &mut Self {
+		self.gc_sections = gc_sections;
+		self
+	}
+
+	/// Set the minimum version of the operating system that the Lua objects are compiled for
+	/// when targeting an Apple platform, such as `"11.0"`.
+	/// 
+	/// This passes `-mmacosx-version-min` and sets `MACOSX_DEPLOYMENT_TARGET` for macOS,
+	/// and does the same with the equivalents for iOS, tvOS and watchOS, including their simulators,
+	/// so that the library doesn't raise the minimum version of the application that it's linked into.
+	/// Only the variable is set for Mac Catalyst and visionOS.
+	/// It has no effect on other targets.
+	pub fn apple_deployment_target(&mut self, version: &str) -> &mut Self {
+		self.apple_deployment_target = Some(version.to_owned());
+		self
+	}
+
+	/// Set the Android API level that the Lua objects are compiled for, such as `21`,
+	/// when targeting Android.
+	/// 
+	/// With Clang, the level is added to the target triple, as the NDK expects,
+	/// and it's passed as `__ANDROID_API__` to other compilers.
+	/// Below level 21, `localeconv` isn't available and only the C locale is supported,
+	/// so the decimal point is always assumed to be `.` when converting numbers,
+	/// and `os.setlocale` always fails, which relies on a hook in the `luaconf.h` bundled with this crate.
+	/// It has no effect on other targets.
+	/// 
+	/// The level should be the same as the one that the final binary is linked for.
+	pub fn android_api_level(&mut self, level: u32) -> &mut Self {
+		self.android_api_level = Some(level);
+		self
+	}
+
+	/// Set whether the Lua objects should be compiled for Universal Windows Platform apps,
+	/// which may only use the Windows APIs allowed in the Microsoft Store, when targeting Windows.
+	/// 
+	/// This defines `WINAPI_FAMILY` as `WINAPI_FAMILY_APP`,
+	/// and loads C modules with `LoadPackagedLibrary` instead of `LoadLibraryEx`,
+	/// so they must be part of the app package.
+	/// Paths under the directory of the executable, which `!` in `package.cpath` expands to,
+	/// are made relative to the package root, as `LoadPackagedLibrary` expects.
+	/// `os.execute`, `io.popen` and `os.tmpname` always fail, and `os.getenv` always returns `nil`,
+	/// as apps have no access to them.
+	/// Disabling all but `os.getenv` relies on hooks in the `luaconf.h` bundled with this crate,
+	/// while `getenv` is replaced in `loadlib.c` and `loslib.c` only.
+	/// It has no effect on other targets.
+	pub fn uwp(&mut self, uwp: bool) -> &mut Self {
+		self.uwp = uwp;
+		self
+	}
+
+	/// Disable optimizations that rely on strict aliasing and on signed integer overflow being undefined
+	/// with GCC-like compilers (`-fno-strict-aliasing -fwrapv`).
+	/// 
+	/// This is what some distributions compile Lua with,
+	/// so that its behavior doesn't change with compiler upgrades, at the cost of some speed.
+	/// MSVC has no equivalent flags, and is left as is.
+	pub fn conservative_codegen(&mut self) -> &mut Self {
+		self.conservative_codegen = true;
+		self
+	}
+
+	/// Enable compatibility with Lua 5.3.
+	pub fn compat_lua_5_3(&mut self) -> &mut Self {
+		self.define("LUA_COMPAT_5_3")
+	}
+
+	/// Include several deprecated functions in the `math` library.
+	pub fn compat_math_lib(&mut self) -> &mut Self {
+		self.define("LUA_COMPAT_MATH_LIB")
+	}
+
+	/// Emulate the `__le` metamethod using `__lt`.
+	pub fn compat_lt_le(&mut self) -> &mut Self {
+		self.define("LUA_COMPAT_LT_LE")
+	}
+
+	/// Enable several consistency checks in the API.
+	pub fn api_checks(&mut self) -> &mut Self {
+		self.define("LUA_USE_APICHECK")
+	}
+
+	/// Set the default path that Lua uses to look for Lua libraries.
+	pub fn lua_lib_path(&mut self, path: &str) -> &mut Self {
+		self.define_string("LUA_PATH_DEFAULT", path)
+	}
+
+	/// Set the default path that Lua uses to look for C libraries.
+	pub fn lua_c_lib_path(&mut self, path: &str) -> &mut Self {
+		self.define_string("LUA_CPATH_DEFAULT", path)
+	}
+
+	/// Set the default path that Lua uses to look for Lua libraries to the templates of `paths`,
+	/// panicking if any of them can't be rendered for the target platform.
+	/// 
+	/// See also [`Build::try_lua_search_paths`] for the non-panicking version.
+	pub fn lua_search_paths(&mut self, paths: &[SearchPath]) -> &mut Self {
+		match self.try_lua_search_paths(paths) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Set the default path that Lua uses to look for Lua libraries to the templates of `paths`.
+	/// 
+	/// See [`SearchPath::lua_path`] for the templates.
+	pub fn try_lua_search_paths(&mut self, paths: &[SearchPath]) -> Result<&mut Self, IoError> {
+		let windows = self.is_windows();
+		let path = paths.iter().map(move |p| p.lua_path(windows)).collect::<Result<Vec<_>, _>>()?.join(";");
+		Ok(self.lua_lib_path(&path))
+	}
+
+	/// Set the default path that Lua uses to look for C libraries to the templates of `paths`,
+	/// panicking if any of them can't be rendered for the target platform.
+	/// 
+	/// See also [`Build::try_c_search_paths`] for the non-panicking version.
+	pub fn c_search_paths(&mut self, paths: &[SearchPath]) -> &mut Self {
+		match self.try_c_search_paths(paths) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Set the default path that Lua uses to look for C libraries to the templates of `paths`.
+	/// 
+	/// See [`SearchPath::c_path`] for the templates.
+	pub fn try_c_search_paths(&mut self, paths: &[SearchPath]) -> Result<&mut Self, IoError> {
+		let windows = self.is_windows();
+		let path = paths.iter().map(move |p| p.c_path(windows)).collect::<Result<Vec<_>, _>>()?.join(";");
+		Ok(self.lua_c_lib_path(&path))
+	}
+
+	/// Define `name` if `condition` holds for the target triple of this build,
+	/// such as one returned by [`target_matches`].
+	/// 
+	/// The target triple is the one set with [`Build::target`], or the default one otherwise,
+	/// as of the time that this is called.
+	pub fn define_if<F: FnOnce(&str) -> bool>(&mut self, condition: F, name: &str) -> &mut Self {
+		if condition(&self.effective_target()) {
+			self.define(name);
+		}
+		self
+	}
+
+	/// Return the target triple of this build,
+	/// which is either set with [`Build::target`] or taken from `TARGET`.
+	fn effective_target(&self) -> String {
+		match self.target.as_deref() {
+			Some(target) => target.to_owned(),
+			None => var("TARGET").unwrap_or_else(move |_| CURRENT_TRIPLE.to_owned()),
+		}
+	}
+
+	/// Return the size of pointers on the target of this build, in bytes,
+	/// which is taken from Cargo if it's the target of the build script, or guessed from the target triple otherwise.
+	fn pointer_size(&self) -> u64 {
+		let target = self.effective_target();
+		if var("TARGET").is_ok_and(|t| t == target)
+			&& let Some(bits) = var("CARGO_CFG_TARGET_POINTER_WIDTH").ok().and_then(move |s| s.parse::<u64>().ok())
+		{
+			return bits / 8
+		}
+		let arch = target.split('-').next().unwrap_or_default();
+		if arch.contains("64") || arch == "s390x" {
+			8
+		} else if arch == "avr" || arch == "msp430" {
+			2
+		} else {
+			4
+		}
+	}
+
+	/// Return the C++ standard library to link to, as `cc` would choose it, for the compiler `tool`.
+	fn cpp_link_stdlib(&self, tool: &Tool) -> Option<String> {
+		let stdlib = match env_flags::target_var("CXXSTDLIB") {
+			Some(stdlib) if stdlib.is_empty() => return None,
+			Some(stdlib) => stdlib,
+			None => {
+				let target = self.effective_target();
+				let libcxx = ["-apple-", "freebsd", "openbsd", "aix", "-ohos", "wasi"].iter().any(|t| target.contains(t))
+					|| tool.args().iter().any(move |arg| arg == "-stdlib=libc++");
+				if target.contains("-msvc") {
+					return None
+				} else if libcxx {
+					String::from("c++")
+				} else if target.contains("android") {
+					String::from("c++_shared")
+				} else {
+					String::from("stdc++")
+				}
+			}
+		};
+		let is_static = env_flags::target_var("CXXSTDLIB_STATIC").is_some_and(move |s| !matches!(s.as_str(), "" | "0" | "false" | "no"));
+		Some(if is_static { format!("static={stdlib}") } else { stdlib })
+	}
+
+	/// Return `true` if this build targets Windows, that is, if it's based on [`platforms::Windows`].
+	fn is_windows(&self) -> bool {
+		self.defines.iter().any(move |(name, _)| name == "LUA_USE_WINDOWS")
+	}
+
+	/// Set the directory separator for `require` submodules.
+	pub fn dir_separator(&mut self, sep: &str) -> &mut Self {
+		self.define_string("LUA_DIRSEP", sep)
+	}
+
+	/// Enable Unicode Identifiers.
+	/// 
+	/// This is a define that isn't explicitly mentioned in the configuration header,
+	/// but is checked in `lctype.c` to build the identifier character table.
+	pub fn unicode_identifiers(&mut self) -> &mut Self {
+		self.define("LUA_UCID")
+	}
+
+	/// Set the line editing library that the interactive mode of the standalone interpreter uses.
+	/// 
+	/// Only `lua.c` uses it, and that isn't part of the library, so this is for crates that compile the interpreter
+	/// themselves with the [`Artifacts`] of this build:
+	/// `LUA_USE_READLINE` is defined, and the include directories and libraries of the implementation,
+	/// as found by `pkg-config`, are added to [`Artifacts::include_dirs`] and [`Artifacts::link_libs`].
+	/// Without `pkg-config`, or when cross-compiling without `PKG_CONFIG_ALLOW_CROSS=1`,
+	/// the library is linked to by name alone.
+	pub fn readline(&mut self, readline: Readline) -> &mut Self {
+		self.readline = readline;
+		self.defines.retain(move |(name, _)| name != "LUA_USE_READLINE");
+		if readline != Readline::None {
+			self.define("LUA_USE_READLINE");
+		}
+		self
+	}
+
+	/// Replace the functions of the `debug` library that can break the invariants of other code,
+	/// or reach values that are meant to be hidden, with ones that raise errors.
+	/// 
+	/// This keeps `debug.traceback`, `debug.getinfo` and `debug.gethook` working,
+	/// while `debug.setupvalue`, `debug.setlocal`, `debug.getregistry`, `debug.setmetatable` and the like are disabled,
+	/// without patching the sources.
+	pub fn safe_debug_library(&mut self) -> &mut Self {
+		self.safe_debug = true;
+		self
+	}
+
+	/// Protect every Lua state with one global lock, implemented with `lock`,
+	/// so that multi-threaded hosts can share states between threads.
+	/// 
+	/// This generates a source file that implements `lua_lock` and `lua_unlock` with the lock,
+	/// and a header that is included in every compiled source file, so no `luaconf.h` hooks are needed.
+	/// The lock is shared by all states, so only one thread runs Lua code at a time.
+	/// [`LockImpl::Pthread`] adds `pthread` to [`Artifacts::link_libs`] on targets where it's a separate library.
+	pub fn global_lock(&mut self, lock: LockImpl) -> &mut Self {
+		self.global_lock = Some(lock);
+		self
+	}
+
+	/// Make `luaL_newstate` allocate memory with the C function `symbol` instead of `realloc` and `free`,
+	/// such as one that routes Lua memory through mimalloc, jemalloc or an arena.
+	/// 
+	/// `symbol` must be defined elsewhere, such as in Rust with `#[unsafe(no_mangle)]`,
+	/// as a `lua_Alloc`: `void *symbol(void *ud, void *ptr, size_t osize, size_t nsize)`,
+	/// which is called with a `ud` of `NULL`.
+	/// States created with `lua_newstate` keep the allocator that is passed to it.
+	/// 
+	/// # Panics
+	/// This function will panic if `symbol` is not a valid C identifier.
+	pub fn default_allocator(&mut self, symbol: &str) -> &mut Self {
+		assert!(is_c_identifier(symbol), "allocator {symbol:?} must be a valid C identifier");
+		self.allocator = Some(symbol.to_owned());
+		self
+	}
+
+	/// Limit each state created with `luaL_newstate` to `bytes` bytes of memory,
+	/// making allocations beyond that fail as if the system were out of memory.
+	/// 
+	/// This is counted separately for each state,
+	/// and applies on top of [`Build::default_allocator`] if it's used.
+	pub fn memory_limit(&mut self, bytes: u64) -> &mut Self {
+		self.memory_limit = Some(bytes);
+		self
+	}
+
+	/// Make `print` and the other functions that write to `stdout` call the C function `symbol` instead,
+	/// such as one that writes to a logging framework or a UART.
+	/// 
+	/// `symbol` must be defined elsewhere as `void symbol(const char *s, size_t len)`.
+	/// It's called with `"\n"` where the output would be flushed.
+	/// 
+	/// # Panics
+	/// This function will panic if `symbol` is not a valid C identifier.
+	pub fn write_function(&mut self, symbol: &str) -> &mut Self {
+		assert!(is_c_identifier(symbol), "write function {symbol:?} must be a valid C identifier");
+		self.write_function = Some(symbol.to_owned());
+		self
+	}
+
+	/// Make warnings, `debug.debug` and panic messages,
+	/// which are written to `stderr` by default, call the C function `symbol` instead.
+	/// 
+	/// `symbol` must be defined elsewhere as `void symbol(const char *s, size_t len)`.
+	/// A message may be written with more than one call.
+	/// 
+	/// # Panics
+	/// This function will panic if `symbol` is not a valid C identifier.
+	pub fn write_error_function(&mut self, symbol: &str) -> &mut Self {
+		assert!(is_c_identifier(symbol), "write error function {symbol:?} must be a valid C identifier");
+		self.write_error_function = Some(symbol.to_owned());
+		self
+	}
+
+	/// Apply the settings bundled in `preset` to this build.
+	/// 
+	/// Settings applied afterwards take precedence over those of the preset.
+	pub fn preset(&mut self, preset: Preset) -> &mut Self {
+		match preset {
+			Preset::Embedded => self.use_32_bits()
+				.define_override("LUNKA_BUFFERSIZE", "LUAL_BUFFERSIZE", Some("256"))
+				.without_io()
+				.without_os(),
+			Preset::Sandboxed => self.define("LUNKA_NODLOPEN")
+				.without_io()
+				.without_os()
+				.without_debug(),
+			Preset::Performance => self.opt_level(3).debug_info(false),
+			Preset::Debugging => self.api_checks().define("LUAI_ASSERT").opt_level(0).debug_info(true),
+		}
+	}
+
+	/// Compile the sources of Lua as C++, so that errors are raised by throwing C++ exceptions
+	/// instead of with `longjmp`, which skips destructors and Rust frames without running them.
+	/// 
+	/// Only the exceptions thrown by Lua are caught by it,
+	/// so other ones, such as Rust panics, unwind through Lua frames to the code that called into Lua.
+	/// The API of Lua keeps C linkage, and the C++ standard library is linked in,
+	/// being listed in [`Artifacts::link_libs`] like the other libraries that Lua needs.
+	/// The standards selected for C are only used for the sources of [`Build::add_c_module`],
+	/// which are still compiled as C, while [`Build::std`] is used for everything else.
+	/// Setting `LUA_USE_LONGJMP` brings back `longjmp`, while `LUAI_THROW` and `LUAI_TRY` take precedence over both.
+	pub fn errors_via_cpp_exceptions(&mut self) -> &mut Self {
+		self.cpp_exceptions = true;
+		self
+	}
+
+	/// Make the `os` library use the C functions in `hooks` instead of the ones of `<time.h>`,
+	/// panicking if any of them aren't valid C identifiers.
+	/// 
+	/// See also [`Build::try_time_hooks`] for the non-panicking version.
+	pub fn time_hooks<S: AsRef<str>>(&mut self, hooks: &TimeHooks<S>) -> &mut Self {
+		match self.try_time_hooks(hooks) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Make the `os` library use the C functions in `hooks` instead of the ones of `<time.h>`.
+	/// 
+	/// This generates a wrapper for `loslib.c` that calls the functions,
+	/// so that `os.time`, `os.clock` and `os.date` work on targets without `time`, `clock` and the like.
+	/// Functions that aren't set are left as they are.
+	/// 
+	/// Returns an error with [`IoErrorKind::InvalidInput`] if any of the functions aren't valid C identifiers.
+	pub fn try_time_hooks<S: AsRef<str>>(&mut self, hooks: &TimeHooks<S>) -> Result<&mut Self, IoError> {
+		if let Some((name, symbol)) = hooks.functions().find(move |(_, symbol)| !is_c_identifier(symbol)) {
+			return Err(IoError::new(IoErrorKind::InvalidInput, format!("{name} hook {symbol:?} must be a valid C identifier")))
+		}
+		self.time_hooks = Some(hooks.to_owned());
+		Ok(self)
+	}
+
+	/// Use additional configuration provided by a [`LuaConf`] in this build,
+	/// panicking if any of its values are invalid.
+	/// 
+	/// See also [`Build::try_lua_conf`] for the non-panicking version.
+	pub fn lua_conf<S: AsRef<str>>(&mut self, lua_conf: &LuaConf<S>) -> &mut Self {
+		match self.try_lua_conf(lua_conf) {
+			Ok(s) => s,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Use additional configuration provided by a [`LuaConf`] in this build.
+	/// 
+	/// Values that are plain numbers, in decimal or hexadecimal and possibly in parentheses,
+	/// such as the ones set by the numeric setters of [`LuaConfBuilder`],
+	/// are checked before anything is applied,
+	/// returning an error with [`IoErrorKind::InvalidInput`] if:
+	/// - [`LuaConf::extra_space`] isn't a multiple of the size of pointers on the target,
+	///   which would misalign the `lua_State` that follows it;
+	/// - [`LuaConf::id_size`] is less than 16;
+	/// - [`LuaConf::max_stack`] or [`LuaConf::max_c_calls`] is zero,
+	///   or [`LuaConf::max_c_calls`] doesn't fit in 16 bits;
+	/// - [`LuaConf::min_str_tab_size`] isn't a power of 2.
+	/// 
+	/// Other values, such as expressions like `(8 * 2)`, can't be checked and are passed on as they are.
+	pub fn try_lua_conf<S: AsRef<str>>(&mut self, lua_conf: &LuaConf<S>) -> Result<&mut Self, IoError> {
+		let value = move |field: &Option<S>| field.as_ref().and_then(move |s| plain_number(s.as_ref()));
+		let invalid = move |message: String| -> Result<&mut Self, IoError> {
+			Err(IoError::new(IoErrorKind::InvalidInput, message))
+		};
+		let pointer_size = self.pointer_size();
+		if let Some(n) = value(&lua_conf.extra_space) && n % pointer_size != 0 {
+			return invalid(format!("extra space ({n}) must be a multiple of the size of pointers ({pointer_size})"))
+		}
+		if let Some(n) = value(&lua_conf.id_size) && n < 16 {
+			return invalid(format!("size of source descriptions ({n}) must be at least 16"))
+		}
+		if let Some(0) = value(&lua_conf.max_stack) {
+			return invalid(String::from("maximum stack size must not be zero"))
+		}
+		if let Some(n) = value(&lua_conf.max_c_calls) && (n == 0 || n > u64::from(u16::MAX)) {
+			return invalid(format!("maximum depth of C calls ({n}) must be between 1 and {}", u16::MAX))
+		}
+		if let Some(n) = value(&lua_conf.min_str_tab_size) && !n.is_power_of_two() {
+			return invalid(format!("minimum size of the string table ({n}) must be a power of 2"))
+		}
+
+		for (hook, target, value) in lua_conf.overrides() {
+			self.define_override(hook, target, value);
+		}
+		for (name, value) in lua_conf.gc_params() {
+			self.gc_params.retain(move |&(n, _)| n != name);
+			self.gc_params.push((name, value.to_owned()));
+		}
+		Ok(self)
+	}
+
+	/// Include the header at `path` in `lua.h` by defining `LUA_USER_H`,
+	/// and add its directory to the include path.
+	/// 
+	/// The header is included after `luaconf.h`,
+	/// and so it may override anything that is defined there,
+	/// such as `lua_lock`, `luai_userstate*` hooks or number types.
+	/// 
+	/// # Panics
+	/// This function will panic if `path` does not point to a file with a name that is valid UTF-8.
+	pub fn user_header<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+		let path = path.as_ref();
+		let Some(name) = path.file_name().and_then(move |s| s.to_str()) else {
+			panic!("user header path {} must have a valid UTF-8 file name", path.display());
+		};
+		println!("cargo:rerun-if-changed={}", path.display());
+		if let Some(dir) = path.parent() {
+			self.include(if dir.as_os_str().is_empty() { Path::new(".") } else { dir });
+		}
+		self.define_string("LUA_USER_H", name)
+	}
+
+	/// Prepend `prefix` to the names of all public Lua API symbols.
+	/// 
+	/// This generates a header that renames every symbol listed in [`symbols`],
+	/// and force-includes it in every compiled source file,
+	/// so that differently-configured Lua builds can be linked into one binary without clashing.
+	/// Code that links to this build must refer to the prefixed symbols.
+	pub fn symbol_prefix(&mut self, prefix: &str) -> &mut Self {
+		self.symbol_prefix = Some(prefix.to_owned());
+		self
+	}
+
+	/// Set whether Lua's symbols should be kept private to the binary that Lua is linked into.
+	/// 
+	/// This is useful when linking Lua into a `cdylib`,
+	/// which may be loaded into a host that has its own Lua,
+	/// where exported Lua symbols could clash with the host's.
+	/// 
+	/// With GCC and Clang, this compiles all sources with `-fvisibility=hidden`.
+	/// With any compiler, this ignores `LUA_BUILD_AS_DLL`,
+	/// since it makes the API be exported with `__declspec(dllexport)`.
+	pub fn private_symbols(&mut self, private_symbols: bool) -> &mut Self {
+		self.private_symbols = private_symbols;
+		self
+	}
+
+	/// Mark the functions that are internal to Lua with `mark` instead of the default of `LUAI_FUNC`,
+	/// which makes them have internal visibility with GCC-like compilers on ELF targets.
+	/// 
+	/// Unless overridden with [`Build::internal_variable_mark`],
+	/// this also applies to the internal variables.
+	pub fn internal_function_mark(&mut self, mark: &str) -> &mut Self {
+		self.define_override("LUNKA_FUNC", "LUAI_FUNC", Some(mark))
+	}
+
+	/// Mark the declarations of the variables that are internal to Lua with `mark`
+	/// instead of the default of `LUAI_DDEC`.
+	pub fn internal_variable_mark(&mut self, mark: &str) -> &mut Self {
+		self.define_value("LUNKA_DDEC", mark)
+	}
+
+	/// Make the internal functions and variables of Lua visible to other modules like the API is,
+	/// which is needed for some profilers, interposers and other dynamic analysis tools.
+	/// 
+	/// This doesn't take precedence over [`Build::private_symbols`].
+	pub fn visible_internals(&mut self) -> &mut Self {
+		self.internal_function_mark("extern").internal_variable_mark("extern")
+	}
+
+	/// Set whether only the public API of Lua should be visible outside of the binary that it's linked into,
+	/// for when Lua is part of a shared library that is meant to export the API, but not the internal `luaV_*` and such.
+	/// 
+	/// With GCC and Clang on targets other than Windows,
+	/// this compiles all sources with `-fvisibility=hidden`,
+	/// and marks `LUA_API` with default visibility and `LUAI_FUNC` with hidden visibility.
+	/// On Windows, only what's marked with `__declspec(dllexport)` is exported anyway,
+	/// which `LUA_BUILD_AS_DLL` does for the API.
+	/// [`Build::private_symbols`] takes precedence over this.
+	pub fn hidden_symbols(&mut self, hidden_symbols: bool) -> &mut Self {
+		self.hidden_symbols = hidden_symbols;
+		self.defines.retain(move |(name, _)| name != "LUNKA_HIDDEN_SYMBOLS");
+		if hidden_symbols {
+			self.define("LUNKA_HIDDEN_SYMBOLS");
+		}
+		self
+	}
+
+	/// Use 32-bit integers and floats despite what the platform is.
+	/// 
+	/// This can't be combined with [`LuaConf::integer_type`] or [`LuaConf::float_type`],
+	/// and compiling fails with [`IoErrorKind::InvalidInput`] if either is set as well.
+	pub fn use_32_bits(&mut self) -> &mut Self {
+		self.define_override("LUNKA_32BITS", "LUA_32BITS", None)
+	}
+
+	/// Use 32-bit integers and floats despite what the platform is,
+	/// like [`LuaConf::lua_32bits`].
+	/// 
+	/// This is the same as [`Build::use_32_bits`].
+	pub fn lua_32bits(&mut self) -> &mut Self {
+		self.use_32_bits()
+	}
+}
+
+/// Make the compiler `tool` include `header` before the contents of every source file.
+fn force_include(cc: &mut CcBuild, tool: &Tool, header: &Path) {
+	if tool.is_like_msvc() {
+		let mut flag = OsString::from("/FI");
+		flag.push(header);
+		cc.flag(flag);
+	} else {
+		cc.flag("-include").flag(header);
+	}
+}
+
+/// Return `true` if `name` is a valid C identifier.
+fn is_c_identifier(name: &str) -> bool {
+	name.bytes().next().is_some_and(move |b| b.is_ascii_alphabetic() || b == b'_')
+		&& name.bytes().all(move |b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Parse `value` as a plain decimal or hexadecimal number, possibly in parentheses and with integer suffixes,
+/// as it may appear in a C header.
+fn plain_number(value: &str) -> Option<u64> {
+	let value = value.trim().trim_start_matches('(').trim_end_matches(')').trim().trim_end_matches(['u', 'U', 'l', 'L']);
+	match value.strip_prefix("0x").or_else(move || value.strip_prefix("0X")) {
+		Some(hex) => u64::from_str_radix(hex, 16).ok(),
+		None => value.parse().ok(),
+	}
+}
+
+/// Return a condition for [`Build::define_if`] that holds if the target triple contains `pattern`,
+/// such as `"windows"` or `"aarch64"`.
+pub fn target_matches(pattern: &str) -> impl FnOnce(&str) -> bool + '_ {
+	move |target| target.contains(pattern)
+}
+
+/// Return `true` if `value` survives being passed as part of a `/D` option
+/// to `cl`-style compilers and through `cmd.exe`, which treat quotes, `!`, `%`, `;` and others specially.
+fn is_cmd_safe(value: &str) -> bool {
+	value.bytes().all(move |b| b.is_ascii_alphanumeric() || b"_-+.,:/\\".contains(&b))
+}
+
+/// Return `true` if a define with `value` must be passed through a header instead of the command line,
+/// given whether the compiler is `cl`-style (which includes `clang-cl`) and whether it's run through a `.cmd` launcher.
+fn define_needs_header(is_like_msvc: bool, launcher_is_cmd: bool, value: &str) -> bool {
+	(is_like_msvc || launcher_is_cmd) && !is_cmd_safe(value)
+}
+
+/// Return the archiver that handles LTO objects produced by `tool`, if it needs a special one.
+fn lto_archiver(tool: &Tool) -> Option<PathBuf> {
+	if tool.is_like_gnu() {
+		let path = tool.path();
+		match path.file_name().and_then(move |s| s.to_str()) {
+			Some(name) if name.ends_with("gcc") => Some(path.with_file_name(format!("{name}-ar"))),
+			_ => Some(PathBuf::from("gcc-ar")),
+		}
+	} else if tool.is_like_clang() {
+		Some(PathBuf::from("llvm-ar"))
+	} else {
+		None
+	}
+}
+
+/// Return the flag that sets the minimum OS version for the Apple `target`, if there is one,
+/// and the environment variable that does the same,
+/// or `None` if `target` isn't an Apple platform.
+fn apple_version_min(target: &str) -> Option<(Option<&'static str>, &'static str)> {
+	let (_, os) = target.split_once("-apple-")?;
+	let is_simulator = os.ends_with("-sim") || target.starts_with("x86_64-") || target.starts_with("i386-");
+	let (device, simulator, env) = match os.split('-').next()? {
+		"darwin" | "macosx" => return Some((Some("-mmacosx-version-min"), "MACOSX_DEPLOYMENT_TARGET")),
+		"ios" if os.ends_with("-macabi") => return Some((None, "IPHONEOS_DEPLOYMENT_TARGET")),
+		"ios" => ("-mios-version-min", "-mios-simulator-version-min", "IPHONEOS_DEPLOYMENT_TARGET"),
+		"tvos" => ("-mtvos-version-min", "-mtvos-simulator-version-min", "TVOS_DEPLOYMENT_TARGET"),
+		"watchos" => ("-mwatchos-version-min", "-mwatchos-simulator-version-min", "WATCHOS_DEPLOYMENT_TARGET"),
+		"visionos" => return Some((None, "XROS_DEPLOYMENT_TARGET")),
+		_ => return None,
+	};
+	Some((Some(if is_simulator { simulator } else { device }), env))
+}
+
+/// Return the canonical path to the static library `lib_name` in `dir`,
+/// or an error with [`IoErrorKind::NotFound`] if it wasn't produced.
+fn find_library(dir: &Path, lib_name: &str) -> Result<PathBuf, IoError> {
+	let candidates = [format!("lib{lib_name}.a"), format!("{lib_name}.lib")];
+	match candidates.iter().map(move |name| dir.join(name)).find(move |path| path.is_file()) {
+		Some(path) => path.canonicalize(),
+		None => Err(IoError::new(
+			IoErrorKind::NotFound,
+			format!("compilation succeeded, but neither {} nor {} was produced in {}", candidates[0], candidates[1], dir.display()),
+		)),
+	}
+}
+
+/// Return the canonical path to the file at `path`,
+/// or an error with [`IoErrorKind::NotFound`] if it wasn't produced.
+fn verify_output(path: &Path) -> Result<PathBuf, IoError> {
+	match path.is_file() {
+		true => path.canonicalize(),
+		false => Err(IoError::new(IoErrorKind::NotFound, format!("compilation succeeded, but {} was not produced", path.display()))),
+	}
+}
+
+/// Remove the objects and coverage data of the sources named `stems` in `dir`, and the library `lib_name`,
+/// which were compiled with a different configuration.
+/// 
+/// Other files are left alone, since the directory may be shared with other builds.
+fn remove_stale_objects(dir: &Path, lib_name: &str, stems: &[&str]) -> Result<(), IoError> {
+	let libs = [format!("lib{lib_name}.a"), format!("{lib_name}.lib")];
+	for result in read_dir(dir)? {
+		let path = result?.path();
+		let Some(name) = path.file_name().and_then(move |s| s.to_str()) else {
+			continue
+		};
+		let is_stale = libs.iter().any(move |lib| lib == name) || name.rsplit_once('.').is_some_and(move |(base, ext)| {
+			matches!(ext, "o" | "obj" | "gcno" | "gcda")
+				&& stems.iter().any(move |stem| base == *stem || base.ends_with(&format!("-{stem}")))
+		});
+		if is_stale && path.is_file() {
+			remove_file(&path)?;
+		}
+	}
+	Ok(())
+}
+
+/// Return the name of the file at `path` as a string.
+fn file_name(path: &Path) -> Result<String, IoError> {
+	path.file_name().and_then(move |s| s.to_str()).map(move |s| s.to_owned()).ok_or_else(move || {
+		IoError::new(IoErrorKind::InvalidInput, format!("invalid file name in {}", path.display()))
+	})
+}
+
+/// Return all files matching `pattern`,
+/// which may contain `*` and `?` wildcards in its file name.
+fn glob(pattern: &Path) -> Result<Vec<PathBuf>, IoError> {
+	let name = file_name(pattern)?;
+	if !name.contains(['*', '?']) {
+		return Ok(vec![pattern.to_owned()])
+	}
+
+	let dir = match pattern.parent() {
+		Some(dir) if !dir.as_os_str().is_empty() => dir,
+		_ => Path::new("."),
+	};
+	let mut files = Vec::new();
+	for result in read_dir(dir)? {
+		let item = result?;
+		if item.file_type()?.is_file() && item.file_name().to_str().is_some_and(|s| wildcard_matches(&name, s)) {
+			files.push(item.path());
+		}
+	}
+	files.sort();
+	Ok(files)
+}
+
+/// Return `true` if `name` matches `pattern` with `*` and `?` wildcards.
+fn wildcard_matches(pattern: &str, name: &str) -> bool {
+	let (pattern, name): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+	let (mut p, mut n) = (0, 0);
+	let mut backtrack = None;
+	while n < name.len() {
+		if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+			p += 1;
+			n += 1;
+		} else if p < pattern.len() && pattern[p] == '*' {
+			backtrack = Some((p, n));
+			p += 1;
+		} else if let Some((star, matched)) = backtrack {
+			p = star + 1;
+			n = matched + 1;
+			backtrack = Some((star, matched + 1));
+		} else {
+			return false
+		}
+	}
+	pattern[p..].iter().all(move |&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cmd_safe_values() {
+		for value in ["1", "0x10", "-1", "1.5e3", "C:\\lua\\lib", "/usr/local/share", "a,b", "x+y", "LONG_NAME"] {
+			assert!(is_cmd_safe(value), "{value:?} was rejected");
+		}
+		for value in ["\"lua\"", "a;b", "!/lua", "%PATH%", "(1)", "a b", "a&b", "a|b", "a<b", "a^b", "a=b"] {
+			assert!(!is_cmd_safe(value), "{value:?} was accepted");
+		}
+	}
+
+	#[test]
+	fn header_defines_only_for_cmd() {
+		let path = "\"!\\\\lua\\\\?.lua;.\\\\?.lua\"";
+		// `cl` and `clang-cl`.
+		assert!(define_needs_header(true, false, path));
+		assert!(define_needs_header(true, false, "(1 << 4)"));
+		assert!(define_needs_header(true, false, "unsigned long"));
+		assert!(!define_needs_header(true, false, "1"));
+		assert!(!define_needs_header(true, false, "C:\\lua"));
+		// GNU-style compilers, run directly or through a `.cmd` launcher.
+		assert!(!define_needs_header(false, false, path));
+		assert!(!define_needs_header(false, false, "(1 << 4)"));
+		assert!(define_needs_header(false, true, path));
+		assert!(!define_needs_header(false, true, "1"));
+	}
+
+	#[test]
+	fn plain_numbers() {
+		for (value, expected) in [("16", Some(16)), (" (16) ", Some(16)), ("0x10", Some(16)), ("(0X10u)", Some(16)), ("16UL", Some(16))] {
+			assert_eq!(plain_number(value), expected, "for {value:?}");
+		}
+		for value in ["(8 * 2)", "LUAI_MAXSTACK", "-1", ""] {
+			assert_eq!(plain_number(value), None, "for {value:?}");
+		}
+	}
+
+	#[test]
+	fn lua_conf_checks_numbers() {
+		let mut build = Build::new(platforms::Linux);
+		assert!(build.try_lua_conf(&LuaConf { id_size: Some("0x10"), ..LuaConf::default() }).is_ok());
+		for conf in [
+			LuaConf { id_size: Some("0x8"), ..LuaConf::default() },
+			LuaConf { id_size: Some("(8)"), ..LuaConf::default() },
+			LuaConf { max_stack: Some("0"), ..LuaConf::default() },
+			LuaConf { min_str_tab_size: Some("100"), ..LuaConf::default() },
+		] {
+			assert_eq!(build.try_lua_conf(&conf).err().map(move |e| e.kind()), Some(IoErrorKind::InvalidInput));
+		}
+		let conf = LuaConfBuilder::new().id_size_bytes(8).build();
+		assert!(build.try_lua_conf(&conf).is_err());
+		let conf = LuaConfBuilder::new().max_c_calls_depth(0).build();
+		assert!(build.try_lua_conf(&conf).is_err());
+	}
+
+	#[test]
+	fn defines_header_keeps_values() {
+		let header = generate::defines_header(&[("LUA_PATH_DEFAULT", "\"!\\\\lua\\\\?.lua;.\\\\?.lua\""), ("LUA_IDSIZE", "(60)")]);
+		assert!(header.contains("#undef LUA_PATH_DEFAULT\n#define LUA_PATH_DEFAULT \"!\\\\lua\\\\?.lua;.\\\\?.lua\"\n"), "{header}");
+		assert!(header.contains("#undef LUA_IDSIZE\n#define LUA_IDSIZE (60)\n"), "{header}");
 	}
 }
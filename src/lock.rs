@@ -0,0 +1,10 @@
+/// Enumeration of the implementations of the global lock of [`Build::global_lock`](crate::Build::global_lock).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LockImpl {
+	/// `pthread_mutex_t`, for POSIX systems.
+	Pthread,
+	/// `SRWLOCK`, for Windows Vista and later.
+	SrwLock,
+	/// `CRITICAL_SECTION`, for all versions of Windows.
+	CriticalSection,
+}
@@ -1,3 +1,21 @@
+use ::std::{
+	fs::read_to_string,
+	io::Error as IoError,
+	path::Path,
+};
+
+use crate::{
+	conf_gen::Define,
+	migrate::{
+		luaconf_defines, stock_defines,
+	},
+};
+
+#[cfg(feature = "serde")]
+use ::serde::{
+	Deserialize, Serialize,
+};
+
 /// Structure that represents additional configuration for Lua
 /// which cannot be done with command-line definitions.
 /// 
@@ -19,14 +37,17 @@
 /// #endif
 /// ```
 #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(deny_unknown_fields))]
 pub struct LuaConf<S> {
 	/// `true` to disable automatic coercion from numbers to strings.
 	/// 
 	/// This corresponds to `LUNKA_NOCVTN2S` for `LUA_NOCVTN2S`.
+	#[cfg_attr(feature = "serde", serde(default))]
 	pub no_number_to_string: bool,
 	/// `true` to disable automatic coercion from strings to numbers.
 	/// 
 	/// This corresponds to `LUNKA_NOCVTS2N` for `LUA_NOCVTS2N`.
+	#[cfg_attr(feature = "serde", serde(default))]
 	pub no_string_to_number: bool,
 	/// Size of the raw memory area associated with a Lua state with very fast access.
 	/// 
@@ -36,4 +57,541 @@ pub struct LuaConf<S> {
 	/// 
 	/// This corresponds to `LUNKA_IDSIZE` for `LUA_IDSIZE`.
 	pub id_size: Option<S>,
+	/// Maximum number of slots in the stack of a Lua thread, which also limits the depth of Lua calls.
+	/// 
+	/// This corresponds to `LUNKA_MAXSTACK` for `LUAI_MAXSTACK`.
+	pub max_stack: Option<S>,
+	/// Maximum depth of nested C calls, of nested syntactical constructs in the parser,
+	/// and of other features implemented through recursion in C,
+	/// which must fit in 16 bits and should be lowered along with the size of the C stack.
+	/// 
+	/// This corresponds to `LUNKA_MAXCCALLS` for `LUAI_MAXCCALLS`.
+	pub max_c_calls: Option<S>,
+	/// Initial size of the buffers of the auxiliary library, which are allocated on the C stack,
+	/// and which should be lowered from the default derived from the size of pointers and numbers for small stacks.
+	/// 
+	/// This corresponds to `LUNKA_BUFFERSIZE` for `LUAL_BUFFERSIZE`.
+	pub buffer_size: Option<S>,
+	/// Maximum length of short strings, which are interned,
+	/// and so are compared by address rather than by their contents.
+	/// 
+	/// This corresponds to `LUNKA_MAXSHORTLEN` for `LUAI_MAXSHORTLEN`.
+	pub max_short_len: Option<S>,
+	/// Minimum size of the table of interned strings, which must be a power of 2.
+	/// 
+	/// This corresponds to `LUNKA_MINSTRTABSIZE` for `MINSTRTABSIZE`.
+	pub min_str_tab_size: Option<S>,
+	/// Number of sets in the cache that `lua_pushstring` uses to avoid interning strings again,
+	/// with `53` being used for a [`LuaConf::str_cache_m`] without this field.
+	/// 
+	/// This corresponds to `LUNKA_STRCACHE_N` for `STRCACHE_N`.
+	pub str_cache_n: Option<S>,
+	/// Number of elements in each set of the cache that `lua_pushstring` uses to avoid interning strings again,
+	/// with `2` being used for a [`LuaConf::str_cache_n`] without this field.
+	/// 
+	/// This corresponds to `LUNKA_STRCACHE_M` for `STRCACHE_M`.
+	pub str_cache_m: Option<S>,
+	/// C type of the context of continuation functions, `lua_KContext`, which must be a numerical type,
+	/// and which is `intptr_t` by default, or `ptrdiff_t` for C89.
+	/// 
+	/// This corresponds to `LUNKA_KCONTEXT` for `LUA_KCONTEXT`.
+	/// [`Build::write_config_rs`](crate::Build::write_config_rs) and [`Build::write_link_stub`](crate::Build::write_link_stub)
+	/// only support the types from `<stdint.h>` and `<stddef.h>` and the standard integer types.
+	pub kcontext: Option<S>,
+	/// Default pause of the garbage collector in incremental mode, as a percentage of the memory in use
+	/// that must be reached before a new cycle starts.
+	/// 
+	/// This replaces `LUAI_GCPAUSE` from `lgc.h` in `lstate.c`, which needs no support in `luaconf.h`.
+	pub gc_pause: Option<S>,
+	/// Default step multiplier of the garbage collector in incremental mode, as a percentage.
+	/// 
+	/// This replaces `LUAI_GCMUL` from `lgc.h` in `lstate.c`, which needs no support in `luaconf.h`.
+	pub gc_mul: Option<S>,
+	/// Default base-2 logarithm of the step size of the garbage collector in incremental mode, in bytes.
+	/// 
+	/// This replaces `LUAI_GCSTEPSIZE` from `lgc.h` in `lstate.c`, which needs no support in `luaconf.h`.
+	pub gc_step_size: Option<S>,
+	/// Default minor multiplier of the garbage collector in generational mode, as a percentage.
+	/// 
+	/// This replaces `LUAI_GENMINORMUL` from `lgc.h` in `lstate.c`, which needs no support in `luaconf.h`.
+	pub gen_minor_mul: Option<S>,
+	/// Default major multiplier of the garbage collector in generational mode, as a percentage.
+	/// 
+	/// This replaces `LUAI_GENMAJORMUL` from `lgc.h` in `lstate.c`, which needs no support in `luaconf.h`.
+	pub gen_major_mul: Option<S>,
+	/// Seed for the hashes of strings in every Lua state, instead of one derived from the current time
+	/// and the addresses of the state and of `lua_newstate`,
+	/// which makes the order in which tables with string keys are traversed reproducible across runs.
+	/// 
+	/// This corresponds to `LUNKA_FIXEDSEED` for `luai_makeseed`.
+	pub fixed_seed: Option<S>,
+	/// Fields of a union that ensure the maximum alignment for the other items in it, separated by semicolons,
+	/// such as `lua_Number n; double u; void *s; lua_Integer i; long l`, which is the default,
+	/// and which sets the alignment of userdata memory blocks and of the extra space of Lua states.
+	/// 
+	/// This corresponds to `LUNKA_MAXALIGN` for `LUAI_MAXALIGN`.
+	pub max_align: Option<S>,
+	/// `true` to use 32-bit integers and floats, like [`Build::use_32_bits`](crate::Build::use_32_bits).
+	/// 
+	/// This corresponds to `LUNKA_32BITS` for `LUA_32BITS`,
+	/// and can't be combined with [`LuaConf::integer_type`] or [`LuaConf::float_type`].
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub lua_32bits: bool,
+	/// C type of Lua integers.
+	/// 
+	/// This corresponds to `LUNKA_INT_TYPE` for `LUA_INT_TYPE`,
+	/// which must be checked before the configuration for numbers rather than in the "local configuration" section.
+	pub integer_type: Option<IntType>,
+	/// C type of Lua floats.
+	/// 
+	/// This corresponds to `LUNKA_FLOAT_TYPE` for `LUA_FLOAT_TYPE`,
+	/// which must be checked before the configuration for numbers rather than in the "local configuration" section.
+	pub float_type: Option<FloatType>,
+}
+
+impl LuaConf<String> {
+	/// Read the configuration out of a modified `luaconf.h` at `path`,
+	/// such as one from a hand-maintained fork of Lua.
+	/// 
+	/// Only object-like definitions that differ from the ones in the stock `luaconf.h` and that have a field here are used,
+	/// and everything else in the header is ignored.
+	/// See [`Migration`](crate::migrate::Migration) for translating the other settings into builder calls.
+	/// 
+	/// The parameters of the garbage collector and [`LuaConf::fixed_seed`] aren't set in `luaconf.h`,
+	/// and so these are never read.
+	pub fn from_luaconf_header<P: AsRef<Path>>(path: P) -> Result<Self, IoError> {
+		let path = path.as_ref();
+		let text = read_to_string(path)?;
+		println!("cargo:rerun-if-changed={}", path.display());
+		let stock = stock_defines();
+		let mut conf = Self::default();
+		for (name, value) in luaconf_defines(&text) {
+			if !stock.iter().any(|(n, v)| *n == name && *v == value) {
+				conf.apply_define(&name, value);
+			}
+		}
+		Ok(conf)
+	}
+
+	/// Set the field that corresponds to the definition of `name` to `value`, if there is one.
+	fn apply_define(&mut self, name: &str, value: Option<String>) {
+		let field = match name {
+			"LUA_NOCVTN2S" => {
+				self.no_number_to_string = true;
+				return
+			}
+			"LUA_NOCVTS2N" => {
+				self.no_string_to_number = true;
+				return
+			}
+			"LUA_32BITS" => {
+				self.lua_32bits = value.as_deref().is_none_or(move |v| v.trim() != "0");
+				return
+			}
+			"LUA_INT_TYPE" | "LUA_INT_DEFAULT" => {
+				self.integer_type = match value.as_deref().map(str::trim) {
+					Some("LUA_INT_INT") => Some(IntType::Int),
+					Some("LUA_INT_LONG") => Some(IntType::Long),
+					Some("LUA_INT_LONGLONG") => Some(IntType::LongLong),
+					_ => self.integer_type,
+				};
+				return
+			}
+			"LUA_FLOAT_TYPE" | "LUA_FLOAT_DEFAULT" => {
+				self.float_type = match value.as_deref().map(str::trim) {
+					Some("LUA_FLOAT_FLOAT") => Some(FloatType::Float),
+					Some("LUA_FLOAT_DOUBLE") => Some(FloatType::Double),
+					Some("LUA_FLOAT_LONGDOUBLE") => Some(FloatType::LongDouble),
+					_ => self.float_type,
+				};
+				return
+			}
+			"LUA_EXTRASPACE" => &mut self.extra_space,
+			"LUA_IDSIZE" => &mut self.id_size,
+			"LUAI_MAXSTACK" => &mut self.max_stack,
+			"LUAI_MAXCCALLS" => &mut self.max_c_calls,
+			"LUAL_BUFFERSIZE" => &mut self.buffer_size,
+			"LUAI_MAXSHORTLEN" => &mut self.max_short_len,
+			"MINSTRTABSIZE" => &mut self.min_str_tab_size,
+			"STRCACHE_N" => &mut self.str_cache_n,
+			"STRCACHE_M" => &mut self.str_cache_m,
+			"LUA_KCONTEXT" => &mut self.kcontext,
+			"LUAI_MAXALIGN" => &mut self.max_align,
+			_ => return,
+		};
+		if value.is_some() {
+			*field = value;
+		}
+	}
+}
+
+impl<S: AsRef<str>> LuaConf<S> {
+	/// Return the `LUNKA_*` hooks that [`Build::lua_conf`](crate::Build::lua_conf) defines for this configuration,
+	/// along with their values, in the order that they're defined in.
+	/// 
+	/// The parameters of the garbage collector, from [`LuaConf::gc_pause`] to [`LuaConf::gen_major_mul`],
+	/// aren't included, as they aren't passed as definitions:
+	/// [`Build::lua_conf`](crate::Build::lua_conf) compiles `lstate.c` through a wrapper that replaces them instead,
+	/// so they only take effect in libraries that it builds.
+	pub fn to_defines(&self) -> Vec<Define> {
+		self.overrides().into_iter().map(move |(hook, _, value)| (hook.to_owned(), value.map(str::to_owned))).collect()
+	}
+
+	/// Return the hooks for this configuration along with the macros that they stand for and their values.
+	pub(crate) fn overrides(&self) -> Vec<(&'static str, &'static str, Option<&str>)> {
+		let mut overrides = Vec::new();
+		if self.no_number_to_string {
+			overrides.push(("LUNKA_NOCVTN2S", "LUA_NOCVTN2S", None));
+		}
+		if self.no_string_to_number {
+			overrides.push(("LUNKA_NOCVTS2N", "LUA_NOCVTS2N", None));
+		}
+		let values = [
+			("LUNKA_EXTRASPACE", "LUA_EXTRASPACE", &self.extra_space),
+			("LUNKA_IDSIZE", "LUA_IDSIZE", &self.id_size),
+			("LUNKA_MAXSTACK", "LUAI_MAXSTACK", &self.max_stack),
+			("LUNKA_MAXCCALLS", "LUAI_MAXCCALLS", &self.max_c_calls),
+			("LUNKA_BUFFERSIZE", "LUAL_BUFFERSIZE", &self.buffer_size),
+			("LUNKA_MAXSHORTLEN", "LUAI_MAXSHORTLEN", &self.max_short_len),
+			("LUNKA_MINSTRTABSIZE", "MINSTRTABSIZE", &self.min_str_tab_size),
+		];
+		for (hook, target, value) in values {
+			if let Some(value) = value.as_ref() {
+				overrides.push((hook, target, Some(value.as_ref())));
+			}
+		}
+		// `llimits.h` defines both sizes of the cache unless `STRCACHE_N` is defined, so they must be overridden together.
+		let str_cache_n = self.str_cache_n.as_ref().map(move |s| s.as_ref());
+		let str_cache_m = self.str_cache_m.as_ref().map(move |s| s.as_ref());
+		if str_cache_n.is_some() || str_cache_m.is_some() {
+			overrides.push(("LUNKA_STRCACHE_N", "STRCACHE_N", Some(str_cache_n.unwrap_or("53"))));
+			overrides.push(("LUNKA_STRCACHE_M", "STRCACHE_M", Some(str_cache_m.unwrap_or("2"))));
+		}
+		let values = [
+			("LUNKA_KCONTEXT", "LUA_KCONTEXT", &self.kcontext),
+			("LUNKA_FIXEDSEED", "luai_makeseed(L)", &self.fixed_seed),
+			("LUNKA_MAXALIGN", "LUAI_MAXALIGN", &self.max_align),
+		];
+		for (hook, target, value) in values {
+			if let Some(value) = value.as_ref() {
+				overrides.push((hook, target, Some(value.as_ref())));
+			}
+		}
+		if self.lua_32bits {
+			overrides.push(("LUNKA_32BITS", "LUA_32BITS", None));
+		}
+		if let Some(integer_type) = self.integer_type {
+			overrides.push(("LUNKA_INT_TYPE", "LUA_INT_TYPE", Some(integer_type.option())));
+		}
+		if let Some(float_type) = self.float_type {
+			overrides.push(("LUNKA_FLOAT_TYPE", "LUA_FLOAT_TYPE", Some(float_type.option())));
+		}
+		overrides
+	}
+
+	/// Return the names of the parameters of the garbage collector in `lgc.h` that are set, and their values.
+	pub(crate) fn gc_params(&self) -> impl Iterator<Item = (&'static str, &str)> {
+		[
+			("LUAI_GCPAUSE", &self.gc_pause),
+			("LUAI_GCMUL", &self.gc_mul),
+			("LUAI_GCSTEPSIZE", &self.gc_step_size),
+			("LUAI_GENMINORMUL", &self.gen_minor_mul),
+			("LUAI_GENMAJORMUL", &self.gen_major_mul),
+		].into_iter().filter_map(move |(name, value)| value.as_ref().map(move |value| (name, value.as_ref())))
+	}
+}
+
+/// Structure for putting together a [`LuaConf`] one setting at a time,
+/// such as from the features of a crate or the environment.
+/// 
+/// Builders of `LuaConf<String>` also have setters that take numbers,
+/// such as [`LuaConfBuilder::id_size_bytes`],
+/// whose values are always checked by [`Build::try_lua_conf`](crate::Build::try_lua_conf).
+#[derive(Default, Clone, PartialEq, Eq, Hash)]
+pub struct LuaConfBuilder<S> {
+	conf: LuaConf<S>,
+}
+
+impl<S> LuaConfBuilder<S> {
+	/// Create a new builder for a configuration with nothing set.
+	pub fn new() -> Self where S: Default {
+		Self::default()
+	}
+
+	/// Create a new builder for a configuration that starts out as `conf`.
+	pub const fn based_on(conf: LuaConf<S>) -> Self {
+		Self {
+			conf,
+		}
+	}
+
+	/// Set [`LuaConf::no_number_to_string`].
+	pub fn no_number_to_string(&mut self, no_number_to_string: bool) -> &mut Self {
+		self.conf.no_number_to_string = no_number_to_string;
+		self
+	}
+
+	/// Set [`LuaConf::no_string_to_number`].
+	pub fn no_string_to_number(&mut self, no_string_to_number: bool) -> &mut Self {
+		self.conf.no_string_to_number = no_string_to_number;
+		self
+	}
+
+	/// Set [`LuaConf::extra_space`].
+	pub fn extra_space(&mut self, extra_space: S) -> &mut Self {
+		self.conf.extra_space = Some(extra_space);
+		self
+	}
+
+	/// Set [`LuaConf::id_size`].
+	pub fn id_size(&mut self, id_size: S) -> &mut Self {
+		self.conf.id_size = Some(id_size);
+		self
+	}
+
+	/// Set [`LuaConf::max_stack`].
+	pub fn max_stack(&mut self, max_stack: S) -> &mut Self {
+		self.conf.max_stack = Some(max_stack);
+		self
+	}
+
+	/// Set [`LuaConf::max_c_calls`].
+	pub fn max_c_calls(&mut self, max_c_calls: S) -> &mut Self {
+		self.conf.max_c_calls = Some(max_c_calls);
+		self
+	}
+
+	/// Set [`LuaConf::buffer_size`].
+	pub fn buffer_size(&mut self, buffer_size: S) -> &mut Self {
+		self.conf.buffer_size = Some(buffer_size);
+		self
+	}
+
+	/// Set [`LuaConf::max_short_len`].
+	pub fn max_short_len(&mut self, max_short_len: S) -> &mut Self {
+		self.conf.max_short_len = Some(max_short_len);
+		self
+	}
+
+	/// Set [`LuaConf::min_str_tab_size`].
+	pub fn min_str_tab_size(&mut self, min_str_tab_size: S) -> &mut Self {
+		self.conf.min_str_tab_size = Some(min_str_tab_size);
+		self
+	}
+
+	/// Set [`LuaConf::str_cache_n`].
+	pub fn str_cache_n(&mut self, str_cache_n: S) -> &mut Self {
+		self.conf.str_cache_n = Some(str_cache_n);
+		self
+	}
+
+	/// Set [`LuaConf::str_cache_m`].
+	pub fn str_cache_m(&mut self, str_cache_m: S) -> &mut Self {
+		self.conf.str_cache_m = Some(str_cache_m);
+		self
+	}
+
+	/// Set [`LuaConf::kcontext`].
+	pub fn kcontext(&mut self, kcontext: S) -> &mut Self {
+		self.conf.kcontext = Some(kcontext);
+		self
+	}
+
+	/// Set [`LuaConf::gc_pause`].
+	pub fn gc_pause(&mut self, gc_pause: S) -> &mut Self {
+		self.conf.gc_pause = Some(gc_pause);
+		self
+	}
+
+	/// Set [`LuaConf::gc_mul`].
+	pub fn gc_mul(&mut self, gc_mul: S) -> &mut Self {
+		self.conf.gc_mul = Some(gc_mul);
+		self
+	}
+
+	/// Set [`LuaConf::gc_step_size`].
+	pub fn gc_step_size(&mut self, gc_step_size: S) -> &mut Self {
+		self.conf.gc_step_size = Some(gc_step_size);
+		self
+	}
+
+	/// Set [`LuaConf::gen_minor_mul`].
+	pub fn gen_minor_mul(&mut self, gen_minor_mul: S) -> &mut Self {
+		self.conf.gen_minor_mul = Some(gen_minor_mul);
+		self
+	}
+
+	/// Set [`LuaConf::gen_major_mul`].
+	pub fn gen_major_mul(&mut self, gen_major_mul: S) -> &mut Self {
+		self.conf.gen_major_mul = Some(gen_major_mul);
+		self
+	}
+
+	/// Set [`LuaConf::fixed_seed`].
+	pub fn fixed_seed(&mut self, fixed_seed: S) -> &mut Self {
+		self.conf.fixed_seed = Some(fixed_seed);
+		self
+	}
+
+	/// Set [`LuaConf::max_align`].
+	pub fn max_align(&mut self, max_align: S) -> &mut Self {
+		self.conf.max_align = Some(max_align);
+		self
+	}
+
+	/// Set [`LuaConf::lua_32bits`].
+	pub fn lua_32bits(&mut self, lua_32bits: bool) -> &mut Self {
+		self.conf.lua_32bits = lua_32bits;
+		self
+	}
+
+	/// Set [`LuaConf::integer_type`].
+	pub fn integer_type(&mut self, integer_type: IntType) -> &mut Self {
+		self.conf.integer_type = Some(integer_type);
+		self
+	}
+
+	/// Set [`LuaConf::float_type`].
+	pub fn float_type(&mut self, float_type: FloatType) -> &mut Self {
+		self.conf.float_type = Some(float_type);
+		self
+	}
+
+	/// Return the configuration that was put together.
+	pub fn build(&self) -> LuaConf<S> where S: Clone {
+		self.conf.clone()
+	}
+}
+
+impl LuaConfBuilder<String> {
+	/// Set [`LuaConf::extra_space`] to `bytes`.
+	pub fn extra_space_bytes(&mut self, bytes: usize) -> &mut Self {
+		self.extra_space(bytes.to_string())
+	}
+
+	/// Set [`LuaConf::id_size`] to `bytes`.
+	pub fn id_size_bytes(&mut self, bytes: usize) -> &mut Self {
+		self.id_size(bytes.to_string())
+	}
+
+	/// Set [`LuaConf::max_stack`] to `slots`.
+	pub fn max_stack_slots(&mut self, slots: usize) -> &mut Self {
+		self.max_stack(slots.to_string())
+	}
+
+	/// Set [`LuaConf::max_c_calls`] to `depth`.
+	pub fn max_c_calls_depth(&mut self, depth: u16) -> &mut Self {
+		self.max_c_calls(depth.to_string())
+	}
+
+	/// Set [`LuaConf::buffer_size`] to `bytes`.
+	pub fn buffer_size_bytes(&mut self, bytes: usize) -> &mut Self {
+		self.buffer_size(bytes.to_string())
+	}
+
+	/// Set [`LuaConf::max_short_len`] to `bytes`.
+	pub fn max_short_len_bytes(&mut self, bytes: usize) -> &mut Self {
+		self.max_short_len(bytes.to_string())
+	}
+
+	/// Set [`LuaConf::min_str_tab_size`] to `size`.
+	pub fn min_str_tab_size_slots(&mut self, size: usize) -> &mut Self {
+		self.min_str_tab_size(size.to_string())
+	}
+}
+
+/// C type of Lua integers, `lua_Integer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IntType {
+	/// `int`.
+	#[cfg_attr(feature = "serde", serde(rename = "int"))]
+	Int,
+	/// `long`, for C compilers that don't support `long long`.
+	#[cfg_attr(feature = "serde", serde(rename = "long"))]
+	Long,
+	/// `long long`, which is the default.
+	#[cfg_attr(feature = "serde", serde(rename = "long long"))]
+	LongLong,
+}
+
+impl IntType {
+	/// Return the `luaconf.h` option for this type, such as `LUA_INT_INT`.
+	pub const fn option(self) -> &'static str {
+		match self {
+			Self::Int => "LUA_INT_INT",
+			Self::Long => "LUA_INT_LONG",
+			Self::LongLong => "LUA_INT_LONGLONG",
+		}
+	}
+}
+
+/// C type of Lua floats, `lua_Number`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FloatType {
+	/// `float`, for targets that only have single-precision floating-point hardware.
+	#[cfg_attr(feature = "serde", serde(rename = "float"))]
+	Float,
+	/// `double`, which is the default.
+	#[cfg_attr(feature = "serde", serde(rename = "double"))]
+	Double,
+	/// `long double`, which has no equivalent in Rust.
+	#[cfg_attr(feature = "serde", serde(rename = "long double"))]
+	LongDouble,
+}
+
+impl FloatType {
+	/// Return the `luaconf.h` option for this type, such as `LUA_FLOAT_FLOAT`.
+	pub const fn option(self) -> &'static str {
+		match self {
+			Self::Float => "LUA_FLOAT_FLOAT",
+			Self::Double => "LUA_FLOAT_DOUBLE",
+			Self::LongDouble => "LUA_FLOAT_LONGDOUBLE",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn define(name: &str, value: Option<&str>) -> Define {
+		(name.to_owned(), value.map(str::to_owned))
+	}
+
+	#[test]
+	fn defines_in_order() {
+		let conf = LuaConf {
+			no_number_to_string: true,
+			id_size: Some("80"),
+			kcontext: Some("ptrdiff_t"),
+			integer_type: Some(IntType::LongLong),
+			..LuaConf::default()
+		};
+		assert_eq!(conf.to_defines(), [
+			define("LUNKA_NOCVTN2S", None),
+			define("LUNKA_IDSIZE", Some("80")),
+			define("LUNKA_KCONTEXT", Some("ptrdiff_t")),
+			define("LUNKA_INT_TYPE", Some("LUA_INT_LONGLONG")),
+		]);
+		assert!(LuaConf::<&str>::default().to_defines().is_empty());
+	}
+
+	#[test]
+	fn str_cache_sizes_default_together() {
+		let conf = LuaConf { str_cache_n: Some("97"), ..LuaConf::default() };
+		assert_eq!(conf.to_defines(), [define("LUNKA_STRCACHE_N", Some("97")), define("LUNKA_STRCACHE_M", Some("2"))]);
+		let conf = LuaConf { str_cache_m: Some("4"), ..LuaConf::default() };
+		assert_eq!(conf.to_defines(), [define("LUNKA_STRCACHE_N", Some("53")), define("LUNKA_STRCACHE_M", Some("4"))]);
+		let conf = LuaConf { str_cache_n: Some("97"), str_cache_m: Some("4"), ..LuaConf::default() };
+		assert_eq!(conf.to_defines(), [define("LUNKA_STRCACHE_N", Some("97")), define("LUNKA_STRCACHE_M", Some("4"))]);
+	}
+
+	#[test]
+	fn gc_params_are_not_defines() {
+		let conf = LuaConf { gc_pause: Some("150"), gen_major_mul: Some("80"), ..LuaConf::default() };
+		assert!(conf.to_defines().is_empty());
+		assert_eq!(conf.gc_params().collect::<Vec<_>>(), [("LUAI_GCPAUSE", "150"), ("LUAI_GENMAJORMUL", "80")]);
+	}
 }
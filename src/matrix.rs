@@ -0,0 +1,129 @@
+//! Building several configurations of Lua in one go.
+
+use ::std::{
+	env::var_os,
+	fmt,
+	path::PathBuf,
+};
+
+use crate::{
+	Artifacts, Build, CcError,
+};
+
+/// Collection of named [`Build`] configurations that are all built together.
+/// 
+/// Each configuration is compiled into its own subdirectory of the output directory,
+/// and into a library named `lua-{name}`,
+/// so that configurations don't overwrite each other's objects.
+/// Cargo metadata for linking is not emitted for any of the configurations;
+/// use [`Artifact::emit_link_metadata`] for the ones that should actually be linked.
+#[derive(Default)]
+pub struct Matrix {
+	configs: Vec<(String, Build)>,
+}
+
+impl Matrix {
+	/// Create a new, empty matrix.
+	pub const fn new() -> Self {
+		Self {
+			configs: Vec::new(),
+		}
+	}
+
+	/// Add a configuration named `name`.
+	/// 
+	/// `name` is used in file names, so it should only consist of characters that are valid for them.
+	pub fn config(&mut self, name: &str, build: Build) -> &mut Self {
+		self.configs.push((name.to_owned(), build));
+		self
+	}
+
+	/// Build all configurations, continuing even if some of them fail to build.
+	pub fn build_all(&mut self) -> Report {
+		let root = var_os("OUT_DIR").map(PathBuf::from).unwrap_or_default().join("lunka-matrix");
+		let artifacts = self.configs.iter_mut().map(move |(name, build)| {
+			let out_dir = root.join(&*name);
+			let lib_name = format!("lua-{name}");
+			build.out_dir(&out_dir).cargo_metadata(false);
+			let result = build.try_compile(&lib_name);
+			Artifact {
+				name: name.clone(),
+				lib_name,
+				out_dir,
+				result,
+			}
+		}).collect();
+		Report {
+			artifacts,
+		}
+	}
+}
+
+/// Result of building a single configuration of a [`Matrix`].
+#[derive(Debug)]
+pub struct Artifact {
+	/// Name of the configuration.
+	pub name: String,
+	/// Name of the compiled library.
+	pub lib_name: String,
+	/// Directory containing the compiled library and its objects.
+	pub out_dir: PathBuf,
+	/// Result of the compilation.
+	pub result: Result<Artifacts, CcError>,
+}
+
+impl Artifact {
+	/// Return `true` if the configuration was built successfully.
+	pub const fn is_ok(&self) -> bool {
+		self.result.is_ok()
+	}
+
+	/// Print the Cargo metadata needed to link to the compiled library,
+	/// or nothing if it failed to compile.
+	pub fn emit_link_metadata(&self) {
+		let Ok(artifacts) = self.result.as_ref() else {
+			return
+		};
+		println!("cargo:rustc-link-search=native={}", artifacts.lib_dir.display());
+		println!("cargo:rustc-link-lib=static={}", self.lib_name);
+		artifacts.emit_link_args();
+	}
+}
+
+/// Summary of building all configurations of a [`Matrix`].
+/// 
+/// The [`Display`](fmt::Display) implementation outputs a line for each configuration.
+#[derive(Debug)]
+pub struct Report {
+	/// Artifacts for each configuration, in the order that they were added.
+	pub artifacts: Vec<Artifact>,
+}
+
+impl Report {
+	/// Return `true` if all configurations were built successfully.
+	pub fn is_ok(&self) -> bool {
+		self.artifacts.iter().all(Artifact::is_ok)
+	}
+
+	/// Return the artifact for the configuration named `name`.
+	pub fn get(&self, name: &str) -> Option<&Artifact> {
+		self.artifacts.iter().find(move |a| a.name == name)
+	}
+
+	/// Iterate over the artifacts of configurations that failed to build.
+	pub fn failures(&self) -> impl Iterator<Item = &Artifact> {
+		self.artifacts.iter().filter(move |a| !a.is_ok())
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for artifact in self.artifacts.iter() {
+			match &artifact.result {
+				Ok(..) => writeln!(f, "{}: ok ({})", artifact.name, artifact.out_dir.display())?,
+				Err(e) => writeln!(f, "{}: failed: {e}", artifact.name)?,
+			}
+		}
+		write!(f, "{} of {} configurations built", self.artifacts.iter().filter(move |a| a.is_ok()).count(), self.artifacts.len())
+	}
+}
@@ -0,0 +1,287 @@
+//! Translation of the settings of an existing, manually configured Lua build into builder calls.
+//! 
+//! A [`Migration`] is collected from compiler flags, such as `MYCFLAGS` in the upstream Makefile,
+//! and from a modified `luaconf.h`.
+//! It can then be rendered as a Rust snippet with [`Migration::to_rust`],
+//! or printed as a report with [`Display`].
+
+use ::std::fmt::{
+	self, Display, Write,
+};
+
+use crate::header;
+
+/// Stock `luaconf.h` that modified ones are compared against.
+const STOCK_LUACONF: &str = include_str!("../lua-5.4.8/include/luaconf.h");
+
+/// Parse the definitions of `luaconf.h` `text`,
+/// leaving out the ones in `#if defined(LUNKA_*)` blocks, like those of the bundled one.
+pub(crate) fn luaconf_defines(luaconf: &str) -> Vec<(String, Option<String>)> {
+	let mut text = String::new();
+	let mut depth = 0usize;
+	for line in luaconf.lines() {
+		let directive = line.trim_start();
+		if depth == 0 && directive.starts_with("#if defined(LUNKA_") {
+			depth = 1;
+		} else if depth > 0 && directive.starts_with("#if") {
+			depth += 1;
+		} else if depth > 0 && directive.starts_with("#endif") {
+			depth -= 1;
+		} else if depth == 0 {
+			text.push_str(line);
+			text.push('\n');
+		}
+	}
+	header::parse_defines(&text)
+}
+
+/// Return the definitions of the stock `luaconf.h`.
+pub(crate) fn stock_defines() -> Vec<(String, Option<String>)> {
+	luaconf_defines(STOCK_LUACONF)
+}
+
+/// Defines that are already set by the [`Platform`](crate::platforms::Platform) of a build.
+const PLATFORM_DEFINES: &[&str] = &[
+	"LUA_USE_POSIX", "LUA_USE_DLOPEN", "LUA_USE_LINUX", "LUA_USE_MACOSX", "LUA_USE_IOS",
+	"LUA_USE_WINDOWS", "LUA_USE_C89", "LUA_DL_DLL", "LUA_BUILD_AS_DLL", "LUA_USE_READLINE",
+];
+
+/// Structure that holds the builder calls that reproduce the settings of an existing Lua build.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+	/// Builder calls, as Rust method call expressions such as `.compat_lua_5_3()`.
+	pub calls: Vec<String>,
+	/// Fields of [`LuaConf`](crate::LuaConf) to set, as Rust field initializers.
+	pub lua_conf: Vec<String>,
+	/// Settings that were covered by the platform or have no equivalent and are left out.
+	pub notes: Vec<String>,
+	/// Settings that have no equivalent and must be migrated by hand.
+	pub unhandled: Vec<String>,
+}
+
+impl Migration {
+	/// Create an empty migration.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Translate the compiler flags in Makefile `text`.
+	/// 
+	/// The flags are taken from assignments to `CFLAGS`, `MYCFLAGS` and `SYSCFLAGS`,
+	/// ignoring references to other variables.
+	pub fn makefile(&mut self, text: &str) -> &mut Self {
+		let text = text.replace("\\\n", " ");
+		for line in text.lines() {
+			let line = line.split('#').next().unwrap_or_default();
+			let Some((name, value)) = line.split_once('=') else {
+				continue
+			};
+			let name = name.trim().trim_end_matches([':', '+', '?']).trim();
+			if matches!(name, "CFLAGS" | "MYCFLAGS" | "SYSCFLAGS") {
+				self.flags(value);
+			}
+		}
+		self
+	}
+
+	/// Translate the space-separated compiler flags `text`.
+	pub fn flags(&mut self, text: &str) -> &mut Self {
+		let mut flags = text.split_ascii_whitespace();
+		while let Some(flag) = flags.next() {
+			if flag.starts_with("$(") || flag.starts_with("${") {
+				continue
+			}
+			if let Some(define) = flag.strip_prefix("-D") {
+				let (name, value) = match define.split_once('=') {
+					Some((name, value)) => (name, Some(value)),
+					None => (define, None),
+				};
+				self.define(name, value);
+			} else if let Some(dir) = flag.strip_prefix("-I") {
+				let dir = if dir.is_empty() { flags.next().unwrap_or_default() } else { dir };
+				self.call(format!(".include({dir:?})"));
+			} else if let Some(level) = flag.strip_prefix("-O") {
+				match level.parse::<u32>() {
+					Ok(level) => self.call(format!(".opt_level({level})")),
+					Err(_) => self.unhandled.push(flag.to_owned()),
+				}
+			} else if flag == "-g" {
+				self.call(String::from(".debug_info(true)"));
+			} else if flag == "-fvisibility=hidden" {
+				self.call(String::from(".private_symbols()"));
+			} else if flag.starts_with("-W") || flag.starts_with("-std=") {
+				self.notes.push(format!("{flag} is left out, as the builder selects warnings and the standard itself"));
+			} else {
+				self.unhandled.push(flag.to_owned());
+			}
+		}
+		self
+	}
+
+	/// Translate the definitions in a modified `luaconf.h` `text`
+	/// that differ from the ones in the stock one.
+	pub fn luaconf(&mut self, text: &str) -> &mut Self {
+		let stock = stock_defines();
+		for (name, value) in luaconf_defines(text) {
+			if !stock.iter().any(|(n, v)| *n == name && *v == value) {
+				self.define(&name, value.as_deref());
+			}
+		}
+		self
+	}
+
+	/// Translate a single definition of `name` to `value`.
+	pub fn define(&mut self, name: &str, value: Option<&str>) -> &mut Self {
+		let call = match (name, value) {
+			("LUA_COMPAT_5_3", _) => ".compat_lua_5_3()",
+			("LUA_COMPAT_MATHLIB", _) => ".compat_math_lib()",
+			("LUA_COMPAT_LT_LE", _) => ".compat_lt_le()",
+			("LUA_USE_APICHECK", _) => ".api_checks()",
+			("LUA_UCID", _) => ".unicode_identifiers()",
+			("LUA_32BITS", None | Some("1")) => ".use_32_bits()",
+			("LUA_32BITS", Some("0")) => return self,
+			("LUA_PATH_DEFAULT" | "LUA_CPATH_DEFAULT" | "LUA_DIRSEP", Some(value)) => {
+				let method = match name {
+					"LUA_PATH_DEFAULT" => "lua_lib_path",
+					"LUA_CPATH_DEFAULT" => "lua_c_lib_path",
+					_ => "dir_separator",
+				};
+				match c_string(value) {
+					Some(s) => self.call(format!(".{method}({s:?})")),
+					None => self.unhandled.push(format!("{name} {value}")),
+				}
+				return self
+			}
+			("LUAI_FUNC", Some(value)) => {
+				self.call(format!(".internal_function_mark({value:?})"));
+				return self
+			}
+			("LUA_NOCVTN2S", _) => {
+				self.lua_conf.push(String::from("no_number_to_string: true"));
+				return self
+			}
+			("LUA_NOCVTS2N", _) => {
+				self.lua_conf.push(String::from("no_string_to_number: true"));
+				return self
+			}
+			("LUA_EXTRASPACE" | "LUA_IDSIZE" | "LUAI_MAXSTACK" | "LUAI_MAXCCALLS" | "LUAL_BUFFERSIZE"
+				| "LUAI_MAXSHORTLEN" | "MINSTRTABSIZE" | "STRCACHE_N" | "STRCACHE_M" | "LUA_KCONTEXT"
+				| "LUAI_MAXALIGN", Some(value)) => {
+				let field = match name {
+					"LUA_EXTRASPACE" => "extra_space",
+					"LUA_IDSIZE" => "id_size",
+					"LUAI_MAXSTACK" => "max_stack",
+					"LUAI_MAXCCALLS" => "max_c_calls",
+					"LUAL_BUFFERSIZE" => "buffer_size",
+					"LUAI_MAXSHORTLEN" => "max_short_len",
+					"MINSTRTABSIZE" => "min_str_tab_size",
+					"STRCACHE_N" => "str_cache_n",
+					"STRCACHE_M" => "str_cache_m",
+					"LUA_KCONTEXT" => "kcontext",
+					_ => "max_align",
+				};
+				self.lua_conf.push(format!("{field}: Some({value:?})"));
+				return self
+			}
+			("LUA_INT_TYPE", Some(value)) => {
+				let variant = match value.trim() {
+					"LUA_INT_INT" => "Int",
+					"LUA_INT_LONG" => "Long",
+					"LUA_INT_LONGLONG" => "LongLong",
+					_ => {
+						self.unhandled.push(format!("{name} {value}"));
+						return self
+					}
+				};
+				self.lua_conf.push(format!("integer_type: Some(lunka_src::IntType::{variant})"));
+				return self
+			}
+			("LUA_FLOAT_TYPE", Some(value)) => {
+				let variant = match value.trim() {
+					"LUA_FLOAT_FLOAT" => "Float",
+					"LUA_FLOAT_DOUBLE" => "Double",
+					"LUA_FLOAT_LONGDOUBLE" => "LongDouble",
+					_ => {
+						self.unhandled.push(format!("{name} {value}"));
+						return self
+					}
+				};
+				self.lua_conf.push(format!("float_type: Some(lunka_src::FloatType::{variant})"));
+				return self
+			}
+			_ if PLATFORM_DEFINES.contains(&name) => {
+				self.notes.push(format!("{name} is left out, as it is set by the platform"));
+				return self
+			}
+			(name, Some(value)) => {
+				self.call(format!(".define_value({name:?}, {value:?})"));
+				return self
+			}
+			(name, None) => {
+				self.call(format!(".define({name:?})"));
+				return self
+			}
+		};
+		self.call(call.to_owned());
+		self
+	}
+
+	/// Add the builder `call` if it hasn't been added yet.
+	fn call(&mut self, call: String) {
+		if !self.calls.contains(&call) {
+			self.calls.push(call);
+		}
+	}
+
+	/// Render a Rust snippet that sets up a [`Build`](crate::Build) with the translated settings,
+	/// with comments for the settings that must be migrated by hand.
+	pub fn to_rust(&self) -> String {
+		let mut out = String::from("lunka_src::Build::for_current()\n\t.add_lunka_src()\n");
+		for call in self.calls.iter() {
+			let _ = writeln!(out, "\t{call}");
+		}
+		if !self.lua_conf.is_empty() {
+			out.push_str("\t.lua_conf(&lunka_src::LuaConf {\n");
+			for field in self.lua_conf.iter() {
+				let _ = writeln!(out, "\t\t{field},");
+			}
+			out.push_str("\t\t..Default::default()\n\t})\n");
+		}
+		for setting in self.unhandled.iter() {
+			let _ = writeln!(out, "\t// TODO: migrate `{setting}` by hand");
+		}
+		out.push_str("\t.compile(\"lua\");\n");
+		out
+	}
+}
+
+impl Display for Migration {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{} setting(s) translated", self.calls.len() + self.lua_conf.len())?;
+		for note in self.notes.iter() {
+			writeln!(f, "note: {note}")?;
+		}
+		for setting in self.unhandled.iter() {
+			writeln!(f, "unhandled: {setting}")?;
+		}
+		Ok(())
+	}
+}
+
+/// Return the contents of `value` if it's a single C string literal.
+fn c_string(value: &str) -> Option<String> {
+	let inner = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+	let mut out = String::with_capacity(inner.len());
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'"' => return None,
+			'\\' => match chars.next()? {
+				c @ ('\\' | '"') => out.push(c),
+				_ => return None,
+			},
+			c => out.push(c),
+		}
+	}
+	Some(out)
+}
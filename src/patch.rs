@@ -0,0 +1,246 @@
+use ::std::io::{
+	Error as IoError, ErrorKind as IoErrorKind,
+};
+
+/// Changes to a single file described by a unified diff.
+#[derive(Clone)]
+pub struct FilePatch {
+	/// Name of the file, without any directories.
+	pub name: String,
+	/// `true` if the file is created by the patch.
+	pub creates: bool,
+	/// `true` if the file is deleted by the patch.
+	pub deletes: bool,
+	hunks: Vec<Hunk>,
+}
+
+#[derive(Clone)]
+struct Hunk {
+	old_start: usize,
+	lines: Vec<(char, String)>,
+}
+
+fn invalid(message: String) -> IoError {
+	IoError::new(IoErrorKind::InvalidData, message)
+}
+
+/// Return the file name of a path in a `---` or `+++` line,
+/// or `None` if it is `/dev/null`.
+fn header_name(rest: &str) -> Option<String> {
+	let path = rest.split('\t').next().unwrap_or(rest).trim();
+	if path == "/dev/null" {
+		return None
+	}
+	Some(path.rsplit(['/', '\\']).next().unwrap_or(path).to_owned())
+}
+
+/// Parse a range like `12,7` or `12` from a hunk header into its start and length.
+fn hunk_range(range: &str) -> Option<(usize, usize)> {
+	match range.split_once(',') {
+		Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+		None => Some((range.parse().ok()?, 1)),
+	}
+}
+
+/// Parse the old start, old length and new length from a hunk header like `@@ -12,7 +12,8 @@`.
+fn hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+	let mut parts = line.strip_prefix("@@ -")?.split(' ');
+	let (old_start, old_len) = hunk_range(parts.next()?)?;
+	let (_, new_len) = hunk_range(parts.next()?.strip_prefix('+')?)?;
+	Some((old_start, old_len, new_len))
+}
+
+/// Parse all file patches out of the unified diff `text`.
+pub fn parse(text: &str) -> Result<Vec<FilePatch>, IoError> {
+	let mut patches = Vec::new();
+	let mut lines = text.lines().peekable();
+	while let Some(line) = lines.next() {
+		let Some(old) = line.strip_prefix("--- ") else {
+			continue
+		};
+		let Some(new) = lines.next().and_then(move |l| l.strip_prefix("+++ ")) else {
+			return Err(invalid(format!("expected `+++` after `{line}`")))
+		};
+		let (old, new) = (header_name(old), header_name(new));
+		let Some(name) = new.clone().or_else(|| old.clone()) else {
+			return Err(invalid("patch has `/dev/null` as both files".into()))
+		};
+
+		let mut hunks = Vec::new();
+		let mut creates = old.is_none();
+		while let Some(header) = lines.next_if(move |l| l.starts_with("@@")) {
+			let (old_start, mut old_left, mut new_left) = hunk_header(header)
+				.ok_or_else(move || invalid(format!("invalid hunk header `{header}`")))?;
+			// `diff -N` marks new files with an empty range instead of `/dev/null`.
+			creates |= old_start == 0 && old_left == 0;
+			let mut hunk_lines = Vec::new();
+			while old_left > 0 || new_left > 0 {
+				let Some(line) = lines.next() else {
+					return Err(invalid(format!("hunk `{header}` for {name} is cut short")))
+				};
+				let kind = line.chars().next().unwrap_or(' ');
+				match kind {
+					' ' => {
+						old_left = old_left.saturating_sub(1);
+						new_left = new_left.saturating_sub(1);
+					}
+					'-' => old_left = old_left.saturating_sub(1),
+					'+' => new_left = new_left.saturating_sub(1),
+					'\\' => continue,
+					_ => return Err(invalid(format!("invalid line in hunk `{header}` for {name}: `{line}`"))),
+				}
+				hunk_lines.push((kind, line.get(1..).unwrap_or("").to_owned()));
+			}
+			lines.next_if(move |l| l.starts_with('\\'));
+			hunks.push(Hunk {
+				old_start,
+				lines: hunk_lines,
+			});
+		}
+
+		patches.push(FilePatch {
+			name,
+			creates,
+			deletes: new.is_none(),
+			hunks,
+		});
+	}
+	Ok(patches)
+}
+
+impl FilePatch {
+	/// Apply this patch to the `original` contents of the file.
+	/// 
+	/// Hunks are looked for at the line they specify first,
+	/// and then at increasing distances from it, to allow for earlier changes to the file.
+	pub fn apply(&self, original: &str) -> Result<String, IoError> {
+		let mut lines: Vec<String> = original.lines().map(move |s| s.to_owned()).collect();
+		let mut offset: isize = 0;
+		for hunk in self.hunks.iter() {
+			let old: Vec<&str> = hunk.lines.iter()
+				.filter(move |(kind, _)| *kind != '+')
+				.map(move |(_, line)| line.as_str())
+				.collect();
+			let new: Vec<String> = hunk.lines.iter()
+				.filter(move |(kind, _)| *kind != '-')
+				.map(move |(_, line)| line.clone())
+				.collect();
+
+			let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+			let matches_at = |at: usize| {
+				at + old.len() <= lines.len() && lines[at..at + old.len()].iter().zip(old.iter()).all(move |(a, b)| a == b)
+			};
+			let found = (0..=lines.len()).find_map(move |distance| {
+				[expected.checked_add(distance), expected.checked_sub(distance)].into_iter()
+					.flatten()
+					.find(|&at| matches_at(at))
+			});
+			let Some(at) = found else {
+				return Err(invalid(format!("hunk at line {} does not apply to {}", hunk.old_start, self.name)))
+			};
+
+			offset += at as isize - expected as isize + new.len() as isize - old.len() as isize;
+			lines.splice(at..at + old.len(), new);
+		}
+
+		let mut out = lines.join("\n");
+		if !lines.is_empty() {
+			out.push('\n');
+		}
+		Ok(out)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn numbered(lines: impl IntoIterator<Item = usize>) -> String {
+		lines.into_iter().map(move |n| format!("line {n}\n")).collect()
+	}
+
+	#[test]
+	fn applies_hunks_with_drift() {
+		let diff = "\
+			--- a/src/lfile.c\n\
+			+++ b/src/lfile.c\n\
+			@@ -2,3 +2,4 @@\n \
+			line 2\n\
+			+added 1\n \
+			line 3\n \
+			line 4\n\
+			@@ -10,3 +11,2 @@\n \
+			line 10\n\
+			-line 11\n \
+			line 12\n\
+			@@ -18,2 +18,2 @@\n\
+			-line 18\n\
+			+changed 18\n \
+			line 19\n\
+		";
+		let patches = parse(diff).unwrap();
+		assert_eq!(patches.len(), 1);
+		assert_eq!(patches[0].name, "lfile.c");
+		// Three lines were inserted at the top since the diff was made.
+		let original = format!("extra\nextra\nextra\n{}", numbered(1..=20));
+		let patched = patches[0].apply(&original).unwrap();
+		let expected = format!(
+			"extra\nextra\nextra\n{}added 1\n{}{}changed 18\n{}",
+			numbered(1..=2), numbered(3..=10), numbered(12..=17), numbered(19..=20),
+		);
+		assert_eq!(patched, expected);
+	}
+
+	#[test]
+	fn no_newline_at_end_of_file() {
+		let diff = "\
+			--- a/lfile.c\n\
+			+++ b/lfile.c\n\
+			@@ -1,2 +1,2 @@\n \
+			line 1\n\
+			-line 2\n\
+			\\ No newline at end of file\n\
+			+line two\n\
+			\\ No newline at end of file\n\
+		";
+		let patches = parse(diff).unwrap();
+		assert_eq!(patches[0].apply("line 1\nline 2").unwrap(), "line 1\nline two\n");
+	}
+
+	#[test]
+	fn creates_and_deletes_files() {
+		let diff = "\
+			--- /dev/null\n\
+			+++ b/src/lnew.c\n\
+			@@ -0,0 +1,2 @@\n\
+			+line 1\n\
+			+line 2\n\
+			--- a/src/lold.c\n\
+			+++ /dev/null\n\
+			@@ -1,2 +0,0 @@\n\
+			-line 1\n\
+			-line 2\n\
+		";
+		let patches = parse(diff).unwrap();
+		assert_eq!(patches.len(), 2);
+		assert_eq!((patches[0].name.as_str(), patches[0].creates, patches[0].deletes), ("lnew.c", true, false));
+		assert_eq!(patches[0].apply("").unwrap(), numbered(1..=2));
+		assert_eq!((patches[1].name.as_str(), patches[1].creates, patches[1].deletes), ("lold.c", false, true));
+		assert_eq!(patches[1].apply(&numbered(1..=2)).unwrap(), "");
+	}
+
+	#[test]
+	fn rejects_hunks_that_do_not_apply() {
+		let diff = "\
+			--- a/lfile.c\n\
+			+++ b/lfile.c\n\
+			@@ -1,2 +1,2 @@\n \
+			line 1\n\
+			-line 2\n\
+			+line two\n\
+		";
+		let patches = parse(diff).unwrap();
+		let error = patches[0].apply("line 1\nline 3\n").unwrap_err();
+		assert_eq!(error.kind(), IoErrorKind::InvalidData);
+	}
+}
@@ -1,19 +1,45 @@
 //! Lua platform handling.
 
+use ::std::{
+	borrow::Cow,
+	error::Error,
+	fmt::{
+		self, Display,
+	},
+};
+
+use crate::CompilerFamily;
+
 /// Trait for a Lua platform.
 pub trait Platform {
 	fn defines(&self) -> &[&str];
 	fn standards(&self) -> &Standards<'_>;
+	/// Return the defines of this platform that have values, such as `("_XOPEN_SOURCE", "700")`,
+	/// which are set after [`Platform::defines`].
+	fn valued_defines(&self) -> &[(&str, &str)] {
+		&[]
+	}
+	/// Return the libraries that anything linked to Lua for this platform also needs, such as `m` or `dl`.
+	fn link_libs(&self) -> &[&str] {
+		&[]
+	}
+
+	/// Return this platform with `defines` added after its own, as an [`Overlay`].
+	fn with_defines<'a>(&'a self, defines: &[&'a str]) -> Overlay<'a, Self> where Self: Sized {
+		Overlay::new(self).with_defines(defines)
+	}
 }
 
 /// Trait for a known, constant Lua platform.
 pub trait ConstPlatform {
 	const DEFINES: &'static [&'static str];
+	const VALUED_DEFINES: &'static [(&'static str, &'static str)] = &[];
+	const LINK_LIBS: &'static [&'static str] = &[];
 	const STANDARDS: Standards<'static> = Standards {
 		gnu: Some("gnu99"),
 		clang: Some("gnu99"),
 		msvc: Some("c99"),
-		clang_cl: Some("gnu99"),
+		clang_cl: Some("c99"),
 	};
 }
 impl<T: ConstPlatform> Platform for T {
@@ -23,6 +49,12 @@ impl<T: ConstPlatform> Platform for T {
 	fn standards(&self) -> &Standards<'_> {
 		&Self::STANDARDS
 	}
+	fn valued_defines(&self) -> &[(&str, &str)] {
+		Self::VALUED_DEFINES
+	}
+	fn link_libs(&self) -> &[&str] {
+		Self::LINK_LIBS
+	}
 }
 
 macro_rules! platform {
@@ -30,6 +62,8 @@ macro_rules! platform {
 		$(#[$attr:meta])*
 		$vis:vis struct $name:ident;
 		DEFINES = $defines:expr;
+		$(VALUED_DEFINES = $valued_defines:expr;)?
+		$(LINK_LIBS = $link_libs:expr;)?
 		$(STANDARDS = $standards:expr;)?
 	} => {
 		#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -38,6 +72,8 @@ macro_rules! platform {
 
 		impl ConstPlatform for $name {
 			const DEFINES: &[&str] = $defines;
+			$(const VALUED_DEFINES: &[(&str, &str)] = $valued_defines;)?
+			$(const LINK_LIBS: &[&str] = $link_libs;)?
 			$(const STANDARDS: Standards<'_> = $standards;)?
 		}
 	};
@@ -49,14 +85,37 @@ platform! {
 		"LUA_USE_POSIX",
 		"LUA_USE_DLOPEN",
 	];
+	LINK_LIBS = &[
+		"m",
+		"dl",
+	];
+}
+
+platform! {
+	/// Android, with the Bionic C library of the NDK.
+	/// 
+	/// See also [`Build::android_api_level`](crate::Build::android_api_level).
+	pub struct Android;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+	];
+	LINK_LIBS = &[
+		"m",
+		"dl",
+	];
 }
 
 platform! {
+	/// Any BSD, with only the interfaces that they all share.
 	pub struct Bsd;
 	DEFINES = &[
 		"LUA_USE_POSIX",
 		"LUA_USE_DLOPEN",
 	];
+	LINK_LIBS = &[
+		"m",
+	];
 }
 
 platform! {
@@ -65,6 +124,9 @@ platform! {
 		"LUA_USE_POSIX",
 		"LUA_USE_DLOPEN",
 	];
+	LINK_LIBS = &[
+		"m",
+	];
 	STANDARDS = Standards {
 		gnu: Some("c89"),
 		clang: Some("c89"),
@@ -73,11 +135,111 @@ platform! {
 	};
 }
 
+platform! {
+	/// Emscripten, which compiles to WebAssembly for browsers and Node.js.
+	/// 
+	/// Loading C modules with `dlopen` is left out,
+	/// as it only works if the final module is linked with `-sMAIN_MODULE`.
+	/// 
+	/// See also [`Build::emscripten_setting`](crate::Build::emscripten_setting).
+	pub struct Emscripten;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+	];
+}
+
+platform! {
+	/// Cygwin, for `*-cygwin` targets,
+	/// where modules are loaded with `dlopen` from `cygwin1.dll` rather than with `LoadLibrary`.
+	pub struct Cygwin;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+	];
+}
+
+platform! {
+	/// DragonFly BSD.
+	pub struct DragonFly;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+	];
+	LINK_LIBS = &[
+		"m",
+	];
+}
+
 platform! {
 	pub struct FreeBsd;
 	DEFINES = &[
 		"LUA_USE_LINUX",
 	];
+	LINK_LIBS = &[
+		"m",
+	];
+}
+
+platform! {
+	/// Bare-metal targets without an operating system, such as `thumbv7em-none-eabihf`,
+	/// with a C library such as newlib or picolibc.
+	/// 
+	/// Only ISO C89 features are used, so integers are `long` and floats are `double`,
+	/// and the decimal point is always `.`.
+	/// `os.execute` and `os.tmpname` always fail, and `os.setlocale` always returns `nil`.
+	/// Disabling these relies on hooks in the `luaconf.h` bundled with this crate.
+	pub struct Freestanding;
+	DEFINES = &[
+		"LUA_USE_C89",
+		"LUNKA_NOSYSTEM",
+		"LUNKA_NOTMPNAM",
+		"LUNKA_NOLOCALE",
+	];
+	LINK_LIBS = &[
+		"m",
+	];
+}
+
+platform! {
+	/// Haiku.
+	/// 
+	/// The math library, `dlopen` and the rest of POSIX are all part of `libroot`,
+	/// which is always linked, so unlike sockets in `libnetwork` or the BSD extensions in `libbsd`,
+	/// Lua doesn't need any extra libraries on Haiku.
+	pub struct Haiku;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+	];
+}
+
+platform! {
+	/// Horizon, the operating system of the Nintendo Switch, for homebrew built with libnx and newlib.
+	/// 
+	/// There is no `dlopen`, `popen` or `system` on Horizon,
+	/// so `package.loadlib`, `io.popen` and `os.execute` fail at runtime.
+	/// Disabling `system` relies on hooks in the `luaconf.h` bundled with this crate.
+	pub struct Horizon;
+	DEFINES = &[
+		"LUNKA_NOSYSTEM",
+	];
+	LINK_LIBS = &[
+		"m",
+	];
+}
+
+platform! {
+	/// illumos, such as OmniOS and SmartOS, which shares its interfaces with Solaris.
+	pub struct Illumos;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+		"_REENTRANT",
+	];
+	LINK_LIBS = &[
+		"m",
+		"dl",
+	];
 }
 
 platform! {
@@ -92,6 +254,10 @@ platform! {
 	DEFINES = &[
 		"LUA_USE_LINUX",
 	];
+	LINK_LIBS = &[
+		"m",
+		"dl",
+	];
 }
 
 platform! {
@@ -108,11 +274,38 @@ platform! {
 	];
 }
 
+platform! {
+	/// NetBSD.
+	pub struct NetBsd;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+	];
+	LINK_LIBS = &[
+		"m",
+	];
+}
+
+platform! {
+	/// OpenBSD.
+	pub struct OpenBsd;
+	DEFINES = &[
+		"LUA_USE_POSIX",
+		"LUA_USE_DLOPEN",
+	];
+	LINK_LIBS = &[
+		"m",
+	];
+}
+
 platform! {
 	pub struct Posix;
 	DEFINES = &[
 		"LUA_USE_POSIX",
 	];
+	LINK_LIBS = &[
+		"m",
+	];
 }
 
 platform! {
@@ -122,6 +315,36 @@ platform! {
 		"LUA_USE_DLOPEN",
 		"_REENTRANT",
 	];
+	LINK_LIBS = &[
+		"m",
+		"dl",
+	];
+}
+
+platform! {
+	/// WASI, for `wasm32-wasip1` and `wasm32-wasip2`.
+	/// 
+	/// `dlopen`, `system`, `popen` and temporary file names are unavailable in WASI,
+	/// so `package.loadlib`, `os.execute`, `io.popen` and `os.tmpname` fail at runtime,
+	/// and signals are only used by the standalone interpreter, which isn't built.
+	/// Disabling `system` and `os.tmpname` relies on hooks in the `luaconf.h` bundled with this crate.
+	/// 
+	/// Errors are raised with `setjmp`, which the WASI SDK only supports
+	/// with `-mllvm -wasm-enable-sjlj` and by linking to `libsetjmp`.
+	pub struct Wasi;
+	DEFINES = &[
+		"LUNKA_NOSYSTEM",
+		"LUNKA_NOTMPNAM",
+	];
+}
+
+platform! {
+	/// WebAssembly without an operating system or C library, for `wasm32-unknown-unknown`.
+	/// 
+	/// See `Build::wasm_libc` for the C library that this needs,
+	/// which is only available with the `wasm-libc` feature.
+	pub struct WasmUnknown;
+	DEFINES = &[];
 }
 
 platform! {
@@ -132,6 +355,9 @@ platform! {
 }
 
 /// Collection of C standard identifiers for different kinds of compilers.
+/// 
+/// The standards for MSVC and `clang-cl` are translated into values of their `/std:` option,
+/// so that GNU dialects become the ISO standards, and C89 and C99 pass no option at all.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Standards<'a> {
 	pub gnu: Option<&'a str>,
@@ -140,53 +366,460 @@ pub struct Standards<'a> {
 	pub clang_cl: Option<&'a str>,
 }
 
-struct DynPlatform {
-	pub defines: &'static [&'static str],
-	pub standards: &'static Standards<'static>,
+impl Standards<'static> {
+	/// Return standards that select C11 for every compiler.
+	pub const fn c11() -> Self {
+		Self {
+			gnu: Some("c11"),
+			clang: Some("c11"),
+			msvc: Some("c11"),
+			clang_cl: Some("c11"),
+		}
+	}
+
+	/// Return standards that select C17 for every compiler.
+	pub const fn c17() -> Self {
+		Self {
+			gnu: Some("c17"),
+			clang: Some("c17"),
+			msvc: Some("c17"),
+			clang_cl: Some("c17"),
+		}
+	}
+
+	/// Return standards that select C23 for every compiler.
+	/// 
+	/// GCC and Clang get `c2x`, which versions from before C23 was finished understand too,
+	/// and MSVC and `clang-cl` get `clatest`.
+	pub const fn c23() -> Self {
+		Self {
+			gnu: Some("c2x"),
+			clang: Some("c2x"),
+			msvc: Some("clatest"),
+			clang_cl: Some("clatest"),
+		}
+	}
+}
+
+impl<'a> Standards<'a> {
+	/// Return the standard for compilers of `family`.
+	/// 
+	/// The family describes the syntax of the driver rather than the ABI of the target,
+	/// so Clang that targets `*-pc-windows-msvc` through its GCC-like driver uses [`Standards::clang`],
+	/// while `clang-cl` uses [`Standards::clang_cl`] and never [`Standards::msvc`].
+	pub const fn select(&self, family: CompilerFamily) -> Option<&'a str> {
+		match family {
+			CompilerFamily::Gnu => self.gnu,
+			CompilerFamily::Clang => self.clang,
+			CompilerFamily::Msvc => self.msvc,
+			CompilerFamily::ClangCl => self.clang_cl,
+		}
+	}
+}
+
+/// [`Platform`] that is described at runtime,
+/// either copied from a [`ConstPlatform`] or put together with a [`DynPlatformBuilder`].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynPlatform<'a> {
+	/// Defines that configure Lua for this platform, such as `LUA_USE_POSIX`.
+	pub defines: Cow<'a, [&'a str]>,
+	/// Defines with values, such as `("_XOPEN_SOURCE", "700")`.
+	pub valued_defines: Cow<'a, [(&'a str, &'a str)]>,
+	/// C standards that Lua is compiled with for this platform.
+	pub standards: Standards<'a>,
+	/// Libraries that anything linked to Lua for this platform also needs, such as `m` or `dl`.
+	pub link_libs: Cow<'a, [&'a str]>,
 }
 
-impl DynPlatform {
+impl DynPlatform<'static> {
 	/// Collect information about a [`ConstPlatform`] into this structure.
 	pub const fn new<P: ConstPlatform>() -> Self {
 		Self {
-			defines: P::DEFINES,
-			standards: &P::STANDARDS,
+			defines: Cow::Borrowed(P::DEFINES),
+			valued_defines: Cow::Borrowed(P::VALUED_DEFINES),
+			standards: P::STANDARDS,
+			link_libs: Cow::Borrowed(P::LINK_LIBS),
+		}
+	}
+}
+
+impl Platform for DynPlatform<'_> {
+	fn defines(&self) -> &[&str] {
+		&self.defines
+	}
+	fn standards(&self) -> &Standards<'_> {
+		&self.standards
+	}
+	fn valued_defines(&self) -> &[(&str, &str)] {
+		&self.valued_defines
+	}
+	fn link_libs(&self) -> &[&str] {
+		&self.link_libs
+	}
+}
+
+/// Structure for putting together a [`DynPlatform`] at runtime,
+/// such as from a configuration file or an environment variable.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynPlatformBuilder<'a> {
+	platform: DynPlatform<'a>,
+}
+
+impl<'a> DynPlatformBuilder<'a> {
+	/// Create a new builder for a platform with no defines and no standards.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create a new builder for a platform that starts out with the defines, standards and libraries of `platform`.
+	pub fn based_on<P: Platform>(platform: &'a P) -> Self {
+		Self {
+			platform: DynPlatform {
+				defines: Cow::Borrowed(platform.defines()),
+				valued_defines: Cow::Borrowed(platform.valued_defines()),
+				standards: *platform.standards(),
+				link_libs: Cow::Borrowed(platform.link_libs()),
+			},
+		}
+	}
+
+	/// Add the define `name`, such as `LUA_USE_POSIX`.
+	pub fn define(&mut self, name: &'a str) -> &mut Self {
+		self.platform.defines.to_mut().push(name);
+		self
+	}
+
+	/// Add all of the defines in `names`.
+	pub fn defines<I: IntoIterator<Item = &'a str>>(&mut self, names: I) -> &mut Self {
+		self.platform.defines.to_mut().extend(names);
+		self
+	}
+
+	/// Add the define `name` with `value`, such as `WINVER` with `0x0601`.
+	pub fn define_value(&mut self, name: &'a str, value: &'a str) -> &mut Self {
+		self.platform.valued_defines.to_mut().push((name, value));
+		self
+	}
+
+	/// Set the C standards of the platform.
+	pub fn standards(&mut self, standards: Standards<'a>) -> &mut Self {
+		self.platform.standards = standards;
+		self
+	}
+
+	/// Add the library `name`, such as `m`, to link anything that links to Lua with.
+	pub fn link_lib(&mut self, name: &'a str) -> &mut Self {
+		self.platform.link_libs.to_mut().push(name);
+		self
+	}
+
+	/// Return the platform that was put together.
+	pub fn build(&self) -> DynPlatform<'a> {
+		self.platform.clone()
+	}
+}
+
+/// [`Platform`] that is another platform with extra defines or different standards,
+/// such as [`Linux`] with `_GNU_SOURCE` and `_FILE_OFFSET_BITS` set to `64`.
+#[derive(Debug, Clone)]
+pub struct Overlay<'a, P> {
+	base: &'a P,
+	defines: Vec<&'a str>,
+	valued_defines: Vec<(&'a str, &'a str)>,
+	standards: Standards<'a>,
+}
+
+impl<'a, P: Platform> Overlay<'a, P> {
+	/// Create an overlay that starts out the same as `base`.
+	pub fn new(base: &'a P) -> Self {
+		Self {
+			base,
+			defines: base.defines().to_vec(),
+			valued_defines: base.valued_defines().to_vec(),
+			standards: *base.standards(),
 		}
 	}
+
+	/// Add `defines` after the defines of the platform.
+	pub fn with_defines(mut self, defines: &[&'a str]) -> Self {
+		self.defines.extend_from_slice(defines);
+		self
+	}
+
+	/// Add `defines` with values after the defines with values of the platform,
+	/// such as `("_FILE_OFFSET_BITS", "64")`.
+	pub fn with_valued_defines(mut self, defines: &[(&'a str, &'a str)]) -> Self {
+		self.valued_defines.extend_from_slice(defines);
+		self
+	}
+
+	/// Replace the standards of the platform with `standards`.
+	pub fn with_standards(mut self, standards: Standards<'a>) -> Self {
+		self.standards = standards;
+		self
+	}
+
+	/// Return the platform that this overlay is based on.
+	pub fn base(&self) -> &'a P {
+		self.base
+	}
 }
 
-impl Platform for DynPlatform {
+impl<P: Platform> Platform for Overlay<'_, P> {
 	fn defines(&self) -> &[&str] {
-		self.defines
+		&self.defines
 	}
 	fn standards(&self) -> &Standards<'_> {
-		self.standards
+		&self.standards
+	}
+	fn valued_defines(&self) -> &[(&str, &str)] {
+		&self.valued_defines
+	}
+	fn link_libs(&self) -> &[&str] {
+		self.base.link_libs()
 	}
 }
 
 /// Current target triple.
 pub const CURRENT_TRIPLE: &str = current_platform::CURRENT_PLATFORM;
 
+/// Get the platform with the name of its type in this module, such as `"Linux"` or `"MacOsX"`,
+/// ignoring case, or [`None`] if there is no such platform.
+pub fn from_name(name: &str) -> Option<DynPlatform<'static>> {
+	const NAMED: &[(&str, DynPlatform<'static>)] = &[
+		("Aix", DynPlatform::new::<Aix>()),
+		("Android", DynPlatform::new::<Android>()),
+		("Bsd", DynPlatform::new::<Bsd>()),
+		("C89", DynPlatform::new::<C89>()),
+		("Cygwin", DynPlatform::new::<Cygwin>()),
+		("DragonFly", DynPlatform::new::<DragonFly>()),
+		("Emscripten", DynPlatform::new::<Emscripten>()),
+		("FreeBsd", DynPlatform::new::<FreeBsd>()),
+		("Freestanding", DynPlatform::new::<Freestanding>()),
+		("Haiku", DynPlatform::new::<Haiku>()),
+		("Horizon", DynPlatform::new::<Horizon>()),
+		("Illumos", DynPlatform::new::<Illumos>()),
+		("Ios", DynPlatform::new::<Ios>()),
+		("Linux", DynPlatform::new::<Linux>()),
+		("MacOsX", DynPlatform::new::<MacOsX>()),
+		("MinGw", DynPlatform::new::<MinGw>()),
+		("NetBsd", DynPlatform::new::<NetBsd>()),
+		("OpenBsd", DynPlatform::new::<OpenBsd>()),
+		("Posix", DynPlatform::new::<Posix>()),
+		("Solaris", DynPlatform::new::<Solaris>()),
+		("Wasi", DynPlatform::new::<Wasi>()),
+		("WasmUnknown", DynPlatform::new::<WasmUnknown>()),
+		("Windows", DynPlatform::new::<Windows>()),
+	];
+	NAMED.iter().find(move |(n, _)| n.eq_ignore_ascii_case(name)).map(move |(_, platform)| platform.clone())
+}
+
 /// Get an appropriate [`Platform`] for the target triple used for compilation.
-pub fn from_current_triple() -> Option<impl Platform> {
+pub fn from_current_triple() -> Result<impl Platform, UnknownTargetError> {
 	from_target_triple(CURRENT_TRIPLE)
 }
 
 /// Get an appropriate [`Platform`] for the given target triple.
-pub fn from_target_triple(target: &str) -> Option<impl Platform> {
-	if target.contains("linux") {
-		Some(DynPlatform::new::<Linux>())
+/// 
+/// `*-windows-gnu` triples are always native MinGW targets, even when building in the POSIX environment of MSYS2,
+/// so only `*-cygwin` triples get [`Cygwin`].
+/// 
+/// If no known platform matches the triple, then the returned [`UnknownTargetError`]
+/// describes the triple and the platforms that come closest to it.
+pub fn from_target_triple(target: &str) -> Result<impl Platform, UnknownTargetError> {
+	if target.contains("wasi") {
+		Ok(DynPlatform::new::<Wasi>())
+	} else if target.starts_with("wasm") && target.ends_with("-unknown-unknown") {
+		Ok(DynPlatform::new::<WasmUnknown>())
+	} else if target.contains("nintendo-switch") {
+		Ok(DynPlatform::new::<Horizon>())
+	} else if target.split('-').any(move |component| component == "none") {
+		Ok(DynPlatform::new::<Freestanding>())
+	} else if target.contains("emscripten") {
+		Ok(DynPlatform::new::<Emscripten>())
+	} else if target.contains("android") {
+		Ok(DynPlatform::new::<Android>())
+	} else if target.contains("linux") {
+		Ok(DynPlatform::new::<Linux>())
+	} else if target.ends_with("freebsd") {
+		Ok(DynPlatform::new::<FreeBsd>())
+	} else if target.ends_with("openbsd") {
+		Ok(DynPlatform::new::<OpenBsd>())
+	} else if target.ends_with("netbsd") {
+		Ok(DynPlatform::new::<NetBsd>())
+	} else if target.ends_with("dragonfly") {
+		Ok(DynPlatform::new::<DragonFly>())
 	} else if target.ends_with("bsd") {
-		Some(DynPlatform::new::<FreeBsd>())
+		Ok(DynPlatform::new::<Bsd>())
+	} else if target.ends_with("haiku") {
+		Ok(DynPlatform::new::<Haiku>())
 	} else if target.ends_with("apple-darwin") {
-		Some(DynPlatform::new::<MacOsX>())
+		Ok(DynPlatform::new::<MacOsX>())
 	} else if target.ends_with("apple-ios") {
-		Some(DynPlatform::new::<Ios>())
+		Ok(DynPlatform::new::<Ios>())
+	} else if target.ends_with("illumos") {
+		Ok(DynPlatform::new::<Illumos>())
 	} else if target.ends_with("solaris") {
-		Some(DynPlatform::new::<Solaris>())
+		Ok(DynPlatform::new::<Solaris>())
+	} else if target.contains("cygwin") {
+		Ok(DynPlatform::new::<Cygwin>())
 	} else if target.contains("windows") {
-		Some(DynPlatform::new::<Windows>())
+		Ok(DynPlatform::new::<Windows>())
 	} else {
-		None
+		Err(UnknownTargetError::new(target))
+	}
+}
+
+/// Error returned by [`from_target_triple`] for a target triple that no known platform matches.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownTargetError {
+	/// Target triple that was looked up.
+	pub triple: String,
+	/// Operating system component of the triple, such as `redox` or `none`, if it has one.
+	pub os: Option<String>,
+	/// Environment component of the triple, such as `gnu` or `eabihf`, if it has one.
+	pub env: Option<String>,
+	/// Names of the known platforms that come closest to the target, best first,
+	/// such as `"Ios"` for `arm64-apple-tvos`, or `"Posix"` if none of its components are known,
+	/// which can be passed to [`from_name`].
+	pub closest: Vec<&'static str>,
+}
+
+/// Vendor components of target triples,
+/// which tell apart `<arch>-<vendor>-<os>` from `<arch>-<os>-<env>`.
+const VENDORS: &[&str] = &[
+	"unknown", "pc", "apple", "nintendo", "sony", "nvidia", "fortanix", "wrs", "sun", "kmc", "esp", "uwp", "win7",
+];
+
+/// Prefixes of the operating system components of target triples, and the names of the platforms for them.
+const OS_PLATFORMS: &[(&str, &str)] = &[
+	("linux", "Linux"),
+	("android", "Android"),
+	("freebsd", "FreeBsd"),
+	("openbsd", "OpenBsd"),
+	("netbsd", "NetBsd"),
+	("dragonfly", "DragonFly"),
+	("haiku", "Haiku"),
+	("illumos", "Illumos"),
+	("solaris", "Solaris"),
+	("darwin", "MacOsX"),
+	("macos", "MacOsX"),
+	("ios", "Ios"),
+	("tvos", "Ios"),
+	("watchos", "Ios"),
+	("visionos", "Ios"),
+	("aix", "Aix"),
+	("cygwin", "Cygwin"),
+	("windows", "Windows"),
+	("wasi", "Wasi"),
+	("emscripten", "Emscripten"),
+];
+
+impl UnknownTargetError {
+	/// Describe the target triple `triple`, guessing the closest platforms from its components.
+	/// 
+	/// Platforms for the operating system and environment components come first,
+	/// such as [`Linux`] for `*-linux-*` or [`Windows`] for `*-msvc`,
+	/// followed by [`WasmUnknown`] for WebAssembly architectures,
+	/// and then by [`Freestanding`] for bare-metal targets or [`Posix`] for others, and [`C89`].
+	pub fn new(triple: &str) -> Self {
+		let parts: Vec<&str> = triple.split('-').collect();
+		let (os, env) = match parts.as_slice() {
+			[_, _, os, env, ..] => (Some(*os), Some(*env)),
+			[_, vendor, os] if VENDORS.contains(vendor) => (Some(*os), None),
+			[_, os, env] => (Some(*os), Some(*env)),
+			[_, os] => (Some(*os), None),
+			_ => (None, None),
+		};
+		let bare = os.is_none_or(move |os| os == "unknown" || os == "elf")
+			|| env.is_some_and(move |env| env.contains("eabi") || env.contains("elf") || env.contains("newlib"));
+		let mut closest = Vec::new();
+		for component in os.into_iter().chain(env) {
+			let known = OS_PLATFORMS.iter().find(move |(prefix, _)| component.starts_with(prefix)).map(move |&(_, name)| name)
+				.or_else(move || (component == "msvc").then_some("Windows"));
+			if let Some(name) = known && !closest.contains(&name) {
+				closest.push(name);
+			}
+		}
+		if parts.first().is_some_and(move |arch| arch.starts_with("wasm")) {
+			closest.push("WasmUnknown");
+		}
+		closest.extend(match bare {
+			true => ["Freestanding", "C89"],
+			false => ["Posix", "C89"],
+		});
+		Self {
+			triple: triple.to_owned(),
+			os: os.map(str::to_owned),
+			env: env.map(str::to_owned),
+			closest,
+		}
+	}
+
+	/// Return the closest known platform to the target,
+	/// for when a best guess is better than failing.
+	pub fn fallback(&self) -> Option<impl Platform + use<>> {
+		self.closest.iter().find_map(move |name| from_name(name))
+	}
+}
+
+impl Display for UnknownTargetError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "couldn't determine platform for target triple {:?}", self.triple)?;
+		if let Some(os) = self.os.as_deref() {
+			write!(f, " (os {os:?}")?;
+			if let Some(env) = self.env.as_deref() {
+				write!(f, ", env {env:?}")?;
+			}
+			write!(f, ")")?;
+		}
+		if !self.closest.is_empty() {
+			write!(f, "; closest known platforms: {}", self.closest.join(", "))?;
+		}
+		Ok(())
+	}
+}
+
+impl Error for UnknownTargetError {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn standards_select_by_family() {
+		let standards = Standards {
+			gnu: Some("gnu99"),
+			clang: Some("gnu11"),
+			msvc: Some("c11"),
+			clang_cl: Some("c17"),
+		};
+		assert_eq!(standards.select(CompilerFamily::Gnu), Some("gnu99"));
+		assert_eq!(standards.select(CompilerFamily::Clang), Some("gnu11"));
+		assert_eq!(standards.select(CompilerFamily::Msvc), Some("c11"));
+		assert_eq!(standards.select(CompilerFamily::ClangCl), Some("c17"));
+		assert_eq!(Standards::default().select(CompilerFamily::Gnu), None);
+	}
+
+	#[test]
+	fn closest_platforms_by_component() {
+		let closest = move |triple: &str| UnknownTargetError::new(triple).closest;
+		assert_eq!(closest("x86_64-unknown-linux-kernel"), ["Linux", "Posix", "C89"]);
+		assert_eq!(closest("arm64-apple-tvos"), ["Ios", "Posix", "C89"]);
+		assert_eq!(closest("x86_64-pc-solaris2"), ["Solaris", "Posix", "C89"]);
+		assert_eq!(closest("aarch64-unknown-freebsd-elf"), ["FreeBsd", "Freestanding", "C89"]);
+		assert_eq!(closest("x86_64-uwp-windows-msvc"), ["Windows", "Posix", "C89"]);
+		assert_eq!(closest("wasm64-unknown-custom"), ["WasmUnknown", "Posix", "C89"]);
+		assert_eq!(closest("x86_64-unknown-redox"), ["Posix", "C89"]);
+		assert_eq!(closest("riscv32imc-unknown-elf"), ["Freestanding", "C89"]);
+	}
+
+	#[test]
+	fn default_standards() {
+		let standards = Windows.standards();
+		assert_eq!(standards.select(CompilerFamily::Gnu), Some("gnu99"));
+		assert_eq!(standards.select(CompilerFamily::Clang), Some("gnu99"));
+		assert_eq!(standards.select(CompilerFamily::Msvc), Some("c99"));
+		assert_eq!(standards.select(CompilerFamily::ClangCl), Some("c99"));
 	}
 }
@@ -0,0 +1,16 @@
+/// Enumeration of curated bundles of settings,
+/// applied with [`Build::preset`](crate::Build::preset).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Preset {
+	/// Small footprint for constrained devices:
+	/// 32-bit numbers, a 256-byte `luaL_Buffer` and no `io` or `os` libraries.
+	Embedded,
+	/// Environment for untrusted code:
+	/// no `io`, `os` or `debug` libraries, and no loading of C modules from dynamic libraries.
+	Sandboxed,
+	/// Full optimization (`-O3`) without debug information.
+	Performance,
+	/// API checks and internal assertions (`LUA_USE_APICHECK` and `LUAI_ASSERT`),
+	/// no optimization (`-O0`) and debug information.
+	Debugging,
+}
@@ -0,0 +1,89 @@
+//! Provenance notes that identify the configuration a library was built from.
+//! 
+//! With [`Build::provenance`](crate::Build::provenance) enabled,
+//! the compiled library contains a string that starts with [`MARKER`],
+//! which survives into the binaries that link to it.
+//! [`Provenance::find`] can then recover it from the bytes of such a binary.
+
+use ::std::{
+	fmt::{
+		self, Display,
+	},
+	fs::read,
+	io::Error as IoError,
+	path::Path,
+};
+
+/// Prefix of the provenance string embedded in libraries.
+pub const MARKER: &str = "LUNKA-PROVENANCE:";
+
+/// Version of Lua that this crate builds.
+pub const LUA_VERSION: &str = "5.4.8";
+
+/// Structure that describes where a Lua library came from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Provenance {
+	/// Version of `lunka-src` that built the library.
+	pub crate_version: String,
+	/// Version of Lua.
+	pub lua_version: String,
+	/// Hash of the configuration of the build,
+	/// which only depends on the settings that affect the compiled code.
+	pub config_hash: u64,
+}
+
+impl Provenance {
+	/// Create a note for the current crate version with configuration hash `config_hash`.
+	pub fn new(config_hash: u64) -> Self {
+		Self {
+			crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+			lua_version: LUA_VERSION.to_owned(),
+			config_hash,
+		}
+	}
+
+	/// Find the first provenance note in `bytes`, which are usually the contents of an executable or library.
+	pub fn find(bytes: &[u8]) -> Option<Self> {
+		let marker = MARKER.as_bytes();
+		let start = bytes.windows(marker.len()).position(move |w| w == marker)? + marker.len();
+		let rest = &bytes[start..];
+		let end = rest.iter().position(move |&b| b == 0)?;
+		Self::parse(str::from_utf8(&rest[..end]).ok()?)
+	}
+
+	/// Find the first provenance note in the file at `path`.
+	pub fn find_in_file<P: AsRef<Path>>(path: P) -> Result<Option<Self>, IoError> {
+		read(path).map(move |bytes| Self::find(&bytes))
+	}
+
+	/// Parse the fields of a note, which come after [`MARKER`].
+	fn parse(fields: &str) -> Option<Self> {
+		let (mut crate_version, mut lua_version, mut config_hash) = (None, None, None);
+		for field in fields.split(';') {
+			match field.split_once('=') {
+				Some(("crate", v)) => crate_version = Some(v.to_owned()),
+				Some(("lua", v)) => lua_version = Some(v.to_owned()),
+				Some(("config", v)) => config_hash = u64::from_str_radix(v, 16).ok(),
+				_ => {}
+			}
+		}
+		Some(Self {
+			crate_version: crate_version?,
+			lua_version: lua_version?,
+			config_hash: config_hash?,
+		})
+	}
+}
+
+impl Display for Provenance {
+	/// Format this note as it's embedded, including [`MARKER`].
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{MARKER}crate={};lua={};config={:016x}", self.crate_version, self.lua_version, self.config_hash)
+	}
+}
+
+/// Return the 64-bit FNV-1a hash of `bytes`,
+/// which unlike the hashers of `std` is stable across Rust versions.
+pub(crate) fn hash(bytes: &[u8]) -> u64 {
+	bytes.iter().fold(0xcbf29ce484222325, move |h, &b| (h ^ u64::from(b)).wrapping_mul(0x100000001b3))
+}
@@ -0,0 +1,87 @@
+use ::std::{
+	env::var_os,
+	ffi::OsString,
+	path::{
+		Path, PathBuf,
+	},
+	process::Command,
+};
+
+/// Line editing library for the interactive mode of the standalone interpreter, `lua.c`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Readline {
+	/// No line editing.
+	#[default]
+	None,
+	/// GNU Readline.
+	Gnu,
+	/// libedit, the BSD-licensed replacement for GNU Readline.
+	LibEdit,
+}
+
+impl Readline {
+	/// Return the name of the `pkg-config` package and of the library of this implementation.
+	const fn names(self) -> Option<(&'static str, &'static str)> {
+		match self {
+			Self::None => None,
+			Self::Gnu => Some(("readline", "readline")),
+			Self::LibEdit => Some(("libedit", "edit")),
+		}
+	}
+}
+
+/// Include directories, library directories and libraries for linking to a [`Readline`] implementation.
+#[derive(Default)]
+pub(crate) struct Probed {
+	pub include_dirs: Vec<PathBuf>,
+	pub lib_dirs: Vec<PathBuf>,
+	pub link_libs: Vec<String>,
+}
+
+/// Find `readline` with `pkg-config`, unless cross-compiling without `PKG_CONFIG_ALLOW_CROSS`,
+/// falling back to linking to the library by its name.
+pub(crate) fn probe(readline: Readline, cross: bool) -> Probed {
+	let Some((package, lib)) = readline.names() else {
+		return Probed::default()
+	};
+	for var in ["PKG_CONFIG", "PKG_CONFIG_PATH", "PKG_CONFIG_ALLOW_CROSS"] {
+		println!("cargo:rerun-if-env-changed={var}");
+	}
+	let allowed = !cross || var_os("PKG_CONFIG_ALLOW_CROSS").is_some_and(move |allow| allow == "1");
+	if allowed
+		&& let Some(probed) = pkg_config(package)
+	{
+		return probed
+	}
+
+	let mut probed = Probed {
+		link_libs: vec![lib.to_owned()],
+		..Probed::default()
+	};
+	// libedit installs its `readline/readline.h` here on the BSDs, as the upstream Makefile expects.
+	let edit_dir = Path::new("/usr/include/edit");
+	if readline == Readline::LibEdit && !cross && edit_dir.is_dir() {
+		probed.include_dirs.push(edit_dir.to_owned());
+	}
+	probed
+}
+
+/// Run `pkg-config` for `package`, returning [`None`] if it isn't installed or doesn't know the package.
+fn pkg_config(package: &str) -> Option<Probed> {
+	let program = var_os("PKG_CONFIG").unwrap_or_else(move || OsString::from("pkg-config"));
+	let output = Command::new(program).args(["--cflags", "--libs", package]).output().ok()?;
+	if !output.status.success() {
+		return None
+	}
+	let mut probed = Probed::default();
+	for arg in String::from_utf8_lossy(&output.stdout).split_whitespace() {
+		if let Some(dir) = arg.strip_prefix("-I") {
+			probed.include_dirs.push(PathBuf::from(dir));
+		} else if let Some(dir) = arg.strip_prefix("-L") {
+			probed.lib_dirs.push(PathBuf::from(dir));
+		} else if let Some(lib) = arg.strip_prefix("-l") {
+			probed.link_libs.push(lib.to_owned());
+		}
+	}
+	Some(probed)
+}
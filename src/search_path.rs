@@ -0,0 +1,114 @@
+use ::std::io::{
+	Error as IoError, ErrorKind as IoErrorKind,
+};
+
+/// Structure that describes a directory in which `require` looks for modules,
+/// for assembling `LUA_PATH_DEFAULT` and `LUA_CPATH_DEFAULT`.
+/// 
+/// The templates are rendered with the directory separator of the target platform,
+/// `?` (`LUA_PATH_MARK`) as the placeholder for the module name,
+/// and `;` (`LUA_PATH_SEP`) between templates.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SearchPath {
+	base: Base,
+	components: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Base {
+	ExecDir,
+	CurrentDir,
+	Root(String),
+}
+
+impl SearchPath {
+	/// Create a search path relative to the directory of the executable that loaded Lua.
+	/// 
+	/// This is rendered as `!` (`LUA_EXEC_DIR`),
+	/// which Lua only replaces on Windows.
+	pub const fn exec_dir() -> Self {
+		Self {
+			base: Base::ExecDir,
+			components: Vec::new(),
+		}
+	}
+
+	/// Create a search path relative to the current working directory.
+	pub const fn current_dir() -> Self {
+		Self {
+			base: Base::CurrentDir,
+			components: Vec::new(),
+		}
+	}
+
+	/// Create a search path relative to the directory `root`, such as `/usr/local`.
+	pub fn root<S: Into<String>>(root: S) -> Self {
+		Self {
+			base: Base::Root(root.into()),
+			components: Vec::new(),
+		}
+	}
+
+	/// Append the directory `component` to this search path.
+	pub fn join<S: Into<String>>(mut self, component: S) -> Self {
+		self.components.push(component.into());
+		self
+	}
+
+	/// Append a `major.minor` version directory to this search path,
+	/// like the `LUA_VDIR` component of the default paths.
+	pub fn semver(self, major: u32, minor: u32) -> Self {
+		self.join(format!("{major}.{minor}"))
+	}
+
+	/// Render this search path as a directory with a trailing separator.
+	/// 
+	/// Returns an error if the path contains one of the placeholders or separators of path templates,
+	/// or if it's relative to the executable directory and `windows` is `false`.
+	pub fn render_dir(&self, windows: bool) -> Result<String, IoError> {
+		let sep = if windows { '\\' } else { '/' };
+		let mut out = match &self.base {
+			Base::ExecDir if !windows => return Err(IoError::new(
+				IoErrorKind::InvalidInput, "the executable directory placeholder is only supported on Windows",
+			)),
+			Base::ExecDir => String::from("!"),
+			Base::CurrentDir => String::from("."),
+			Base::Root(root) => {
+				check_component(root)?;
+				root.trim_end_matches(['/', '\\']).to_owned()
+			}
+		};
+		for component in self.components.iter() {
+			check_component(component)?;
+			out.push(sep);
+			out.push_str(component.trim_matches(['/', '\\']));
+		}
+		out.push(sep);
+		Ok(out)
+	}
+
+	/// Render the templates for Lua modules in this search path,
+	/// that is, `?.lua` and `?/init.lua`, separated by `;`.
+	pub fn lua_path(&self, windows: bool) -> Result<String, IoError> {
+		let dir = self.render_dir(windows)?;
+		let sep = if windows { '\\' } else { '/' };
+		Ok(format!("{dir}?.lua;{dir}?{sep}init.lua"))
+	}
+
+	/// Render the template for C modules in this search path,
+	/// which is either `?.dll` or `?.so` depending on `windows`.
+	pub fn c_path(&self, windows: bool) -> Result<String, IoError> {
+		let dir = self.render_dir(windows)?;
+		Ok(format!("{dir}?.{}", if windows { "dll" } else { "so" }))
+	}
+}
+
+fn check_component(component: &str) -> Result<(), IoError> {
+	if component.contains([';', '?', '!']) {
+		Err(IoError::new(
+			IoErrorKind::InvalidInput, format!("search path component {component:?} contains a template placeholder or separator"),
+		))
+	} else {
+		Ok(())
+	}
+}
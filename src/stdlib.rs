@@ -0,0 +1,80 @@
+/// Library of the Lua standard library that can be left out of a build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StdLib {
+	/// Basic functions (`_G`).
+	Base,
+	/// Module loading (`package` and `require`).
+	Package,
+	/// Coroutine manipulation (`coroutine`).
+	Coroutine,
+	/// Table manipulation (`table`).
+	Table,
+	/// Input and output facilities (`io`).
+	Io,
+	/// Operating system facilities (`os`).
+	Os,
+	/// String manipulation (`string`).
+	String,
+	/// Mathematical functions (`math`).
+	Math,
+	/// UTF-8 support (`utf8`).
+	Utf8,
+	/// Debug facilities (`debug`).
+	Debug,
+}
+
+impl StdLib {
+	/// All libraries, in the order that `luaL_openlibs` opens them in.
+	pub const ALL: [Self; 10] = [
+		Self::Base, Self::Package, Self::Coroutine, Self::Table, Self::Io,
+		Self::Os, Self::String, Self::Math, Self::Utf8, Self::Debug,
+	];
+
+	/// Return the name of the source file that implements this library.
+	pub const fn source_file(self) -> &'static str {
+		match self {
+			Self::Base => "lbaselib.c",
+			Self::Package => "loadlib.c",
+			Self::Coroutine => "lcorolib.c",
+			Self::Table => "ltablib.c",
+			Self::Io => "liolib.c",
+			Self::Os => "loslib.c",
+			Self::String => "lstrlib.c",
+			Self::Math => "lmathlib.c",
+			Self::Utf8 => "lutf8lib.c",
+			Self::Debug => "ldblib.c",
+		}
+	}
+
+	/// Return the macro for the name of this library, as defined in `lualib.h`.
+	pub const fn name_macro(self) -> &'static str {
+		match self {
+			Self::Base => "LUA_GNAME",
+			Self::Package => "LUA_LOADLIBNAME",
+			Self::Coroutine => "LUA_COLIBNAME",
+			Self::Table => "LUA_TABLIBNAME",
+			Self::Io => "LUA_IOLIBNAME",
+			Self::Os => "LUA_OSLIBNAME",
+			Self::String => "LUA_STRLIBNAME",
+			Self::Math => "LUA_MATHLIBNAME",
+			Self::Utf8 => "LUA_UTF8LIBNAME",
+			Self::Debug => "LUA_DBLIBNAME",
+		}
+	}
+
+	/// Return the name of the function that opens this library.
+	pub const fn open_function(self) -> &'static str {
+		match self {
+			Self::Base => "luaopen_base",
+			Self::Package => "luaopen_package",
+			Self::Coroutine => "luaopen_coroutine",
+			Self::Table => "luaopen_table",
+			Self::Io => "luaopen_io",
+			Self::Os => "luaopen_os",
+			Self::String => "luaopen_string",
+			Self::Math => "luaopen_math",
+			Self::Utf8 => "luaopen_utf8",
+			Self::Debug => "luaopen_debug",
+		}
+	}
+}
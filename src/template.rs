@@ -0,0 +1,88 @@
+use ::std::{
+	collections::{
+		BTreeMap, BTreeSet,
+	},
+	io::{
+		Error as IoError, ErrorKind as IoErrorKind,
+	},
+};
+
+use crate::{
+	Build, CcError,
+};
+
+/// Shared configuration that several [`Build`]s can be derived from,
+/// such as one instrumented with sanitizers for tests and one optimized for release.
+/// 
+/// Derived builds are checked to have the same definitions and [`LuaConf`](crate::LuaConf) settings as the template,
+/// except for the ones that are explicitly allowed to vary with [`ConfigTemplate::vary`].
+/// Settings that don't change the Lua API, such as optimization or instrumentation, are free to differ.
+/// 
+/// Each derived build should be compiled into a library with a different name,
+/// or into a different output directory,
+/// so that they don't overwrite each other.
+#[derive(Clone)]
+pub struct ConfigTemplate {
+	base: Build,
+	varied: Vec<String>,
+}
+
+impl ConfigTemplate {
+	/// Create a new template from the configuration of `base`.
+	pub const fn new(base: Build) -> Self {
+		Self {
+			base,
+			varied: Vec::new(),
+		}
+	}
+
+	/// Allow for the definition of `name` to differ between derived builds.
+	/// 
+	/// `name` is the name of the macro, such as `LUA_USE_APICHECK` or `LUA_IDSIZE`.
+	pub fn vary(&mut self, name: &str) -> &mut Self {
+		self.varied.push(name.to_owned());
+		self
+	}
+
+	/// Return the build that derived builds start from.
+	pub const fn base(&self) -> &Build {
+		&self.base
+	}
+
+	/// Derive a build by applying `f` to a copy of the template,
+	/// panicking if it changes definitions that aren't allowed to vary.
+	/// 
+	/// See also [`ConfigTemplate::try_derive`] for the non-panicking version.
+	pub fn derive<F: FnOnce(&mut Build)>(&self, f: F) -> Build {
+		match self.try_derive(f) {
+			Ok(build) => build,
+			Err(e) => panic!("{e}"),
+		}
+	}
+
+	/// Derive a build by applying `f` to a copy of the template,
+	/// returning an error if it changes definitions that aren't allowed to vary.
+	pub fn try_derive<F: FnOnce(&mut Build)>(&self, f: F) -> Result<Build, CcError> {
+		let mut build = self.base.clone();
+		f(&mut build);
+
+		let (expected, actual) = (define_set(&self.base), define_set(&build));
+		let changed = expected.keys().chain(actual.keys())
+			.filter(|name| expected.get(*name) != actual.get(*name))
+			.filter(|name| !self.varied.iter().any(move |v| v == *name))
+			.copied()
+			.collect::<BTreeSet<_>>();
+		if !changed.is_empty() {
+			let message = format!("derived build changes definitions that aren't allowed to vary: {}", Vec::from_iter(changed).join(", "));
+			return Err(IoError::new(IoErrorKind::InvalidInput, message).into())
+		}
+		Ok(build)
+	}
+}
+
+/// Return the last value of each definition and override of `build`, by macro name.
+fn define_set(build: &Build) -> BTreeMap<&str, Option<&str>> {
+	let defines = build.defines.iter().map(move |(name, value)| (name.as_str(), value.as_deref()));
+	let overrides = build.overrides.iter().map(move |o| (o.target, o.value.as_deref()));
+	defines.chain(overrides).collect()
+}
@@ -0,0 +1,56 @@
+/// Structure that holds the C functions that `os.time`, `os.clock` and `os.date` use instead of the ones of `<time.h>`,
+/// for targets that have its types but not its functions, such as many RTOSes.
+/// 
+/// Each function must be defined elsewhere, such as in a board support package
+/// or in Rust with `#[unsafe(no_mangle)]`.
+/// `<time.h>` is still required for `time_t`, `struct tm` and `strftime`.
+/// 
+/// See [`Build::time_hooks`](crate::Build::time_hooks).
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeHooks<S> {
+	/// Function that returns the current calendar time, used by `os.time` and `os.date`.
+	/// 
+	/// This is called as `time_t f(time_t *t)`, like `time`.
+	pub time: Option<S>,
+	/// Function that returns the processor time used by the program, in seconds, used by `os.clock`.
+	/// 
+	/// This is called as `double f(void)`, unlike `clock`, which counts in `CLOCKS_PER_SEC`.
+	pub clock: Option<S>,
+	/// Function that breaks down a time as UTC, used by `os.date` with a leading `!` in the format.
+	/// 
+	/// This is called as `struct tm *f(const time_t *t, struct tm *result)`, like `gmtime_r`.
+	pub gmtime: Option<S>,
+	/// Function that breaks down a time as local time, used by `os.date`.
+	/// 
+	/// This is called as `struct tm *f(const time_t *t, struct tm *result)`, like `localtime_r`.
+	pub localtime: Option<S>,
+	/// Function that converts a broken-down local time to a calendar time, used by `os.time` with a table.
+	/// 
+	/// This is called as `time_t f(struct tm *tm)`, like `mktime`.
+	pub mktime: Option<S>,
+}
+
+impl<S: AsRef<str>> TimeHooks<S> {
+	/// Return an iterator over the names of the fields that are set, along with their values.
+	pub(crate) fn functions(&self) -> impl Iterator<Item = (&'static str, &str)> {
+		[
+			("time", &self.time),
+			("clock", &self.clock),
+			("gmtime", &self.gmtime),
+			("localtime", &self.localtime),
+			("mktime", &self.mktime),
+		].into_iter().filter_map(move |(name, value)| value.as_ref().map(move |value| (name, value.as_ref())))
+	}
+
+	/// Return a copy of these hooks with owned strings.
+	pub(crate) fn to_owned(&self) -> TimeHooks<String> {
+		let owned = move |field: &Option<S>| field.as_ref().map(move |s| s.as_ref().to_owned());
+		TimeHooks {
+			time: owned(&self.time),
+			clock: owned(&self.clock),
+			gmtime: owned(&self.gmtime),
+			localtime: owned(&self.localtime),
+			mktime: owned(&self.mktime),
+		}
+	}
+}
@@ -0,0 +1,92 @@
+use ::cc::{
+	Build as CcBuild,
+	Tool,
+};
+
+/// Group of sources that can be compiled with their own [`Warnings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SourceGroup {
+	/// Sources of the bundled Lua tree added with [`Build::add_lunka_src`](crate::Build::add_lunka_src),
+	/// and sources generated by this crate.
+	Bundled,
+	/// Sources of trees added with [`Build::add_lua_src`](crate::Build::add_lua_src).
+	Added,
+	/// Sources of C modules added with [`Build::add_c_module`](crate::Build::add_c_module).
+	Modules,
+}
+
+/// Warning settings for a [`SourceGroup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Warnings {
+	/// `true` to enable the common set of warnings, such as `-Wall`.
+	/// If `false`, all warnings are disabled.
+	pub enabled: bool,
+	/// `true` to enable extra warnings, such as `-Wextra`.
+	pub extra: bool,
+	/// `true` to turn warnings into errors.
+	pub into_errors: bool,
+}
+
+impl Warnings {
+	/// Common and extra warnings, which Lua's own sources are clean of.
+	pub const FULL: Self = Self {
+		enabled: true,
+		extra: true,
+		into_errors: false,
+	};
+	/// Only the common warnings.
+	pub const RELAXED: Self = Self {
+		enabled: true,
+		extra: false,
+		into_errors: false,
+	};
+	/// No warnings at all.
+	pub const NONE: Self = Self {
+		enabled: false,
+		extra: false,
+		into_errors: false,
+	};
+}
+
+/// Warnings that GCC may emit for Lua's sources,
+/// mostly as false positives of flow analysis at higher optimization levels.
+const GNU_BENIGN: &[&str] = &[
+	"maybe-uninitialized",
+	"stringop-overflow",
+	"stringop-truncation",
+];
+
+/// Warnings that Clang may emit for Lua's sources.
+const CLANG_BENIGN: &[&str] = &[
+	"unused-but-set-variable",
+	"string-plus-int",
+];
+
+/// Warnings that MSVC emits for Lua's sources:
+/// - C4310 (cast truncates constant value) in `lvm.c`;
+/// - C4324 (structure was padded due to alignment specifier);
+/// - C4702 (unreachable code).
+const MSVC_BENIGN: &[&str] = &[
+	"4310",
+	"4324",
+	"4702",
+];
+
+/// Stop Lua's known benign warnings from being treated as errors with the compiler `tool`.
+/// 
+/// MSVC cannot downgrade a single warning if `/WX` is in effect, so those warnings are disabled instead.
+pub fn downgrade_benign(cc: &mut CcBuild, tool: &Tool) {
+	if tool.is_like_clang_cl() || tool.is_like_clang() {
+		for warning in CLANG_BENIGN {
+			cc.flag_if_supported(format!("-Wno-error={warning}"));
+		}
+	} else if tool.is_like_gnu() {
+		for warning in GNU_BENIGN {
+			cc.flag_if_supported(format!("-Wno-error={warning}"));
+		}
+	} else if tool.is_like_msvc() {
+		for warning in MSVC_BENIGN {
+			cc.flag(format!("/wd{warning}"));
+		}
+	}
+}
@@ -1,4 +1,12 @@
 use ::lunka_src::*;
+use ::std::{
+	env::var,
+	fs::write,
+	path::{
+		Path, PathBuf,
+	},
+	process::Command,
+};
 
 fn main() {
 	let lua_conf = LuaConf::<&'static str> {
@@ -6,6 +14,7 @@ fn main() {
 		no_string_to_number: true,
 		extra_space: None,
 		id_size: None,
+		..LuaConf::default()
 	};
 
 	Build::for_current()
@@ -13,5 +22,67 @@ fn main() {
 		.lua_conf(&lua_conf)
 		.compat_lua_5_3()
 		.unicode_identifiers()
+		// A path with characters that `cmd.exe` and `cl` treat specially.
+		.define_string("LUA_PATH_DEFAULT", "!\\lunka\\?.lua;./?.lua")
 		.compile("lua");
+
+	let out_dir = PathBuf::from(var("OUT_DIR").unwrap());
+
+	// `symbol_prefix` and `memory_limit` together.
+	let artifacts = Build::for_current()
+		.add_lunka_src()
+		.out_dir(out_dir.join("prefixed"))
+		.cargo_metadata(false)
+		.symbol_prefix("prefixed_")
+		.memory_limit(1 << 20)
+		.compile("lua-prefixed");
+	link_check(&out_dir, &artifacts, "prefixed_");
+
+	// C++ exceptions with objects built through a command hook.
+	let artifacts = Build::for_current()
+		.add_lunka_src()
+		.out_dir(out_dir.join("cpp-hook"))
+		.cargo_metadata(false)
+		.errors_via_cpp_exceptions()
+		.command_hook(move |_| true)
+		.compile("lua-cpp-hook");
+	link_check(&out_dir, &artifacts, "");
+}
+
+/// Link a C program that raises and catches an error to the library of `artifacts`,
+/// whose symbols start with `prefix`, and run it.
+/// 
+/// This is done for builds that can't be linked into the crate,
+/// as their internal symbols would clash with the ones of the main build.
+fn link_check(out_dir: &Path, artifacts: &Artifacts, prefix: &str) {
+	let tool = ::cc::Build::new().get_compiler();
+	if var("HOST").unwrap() != var("TARGET").unwrap() || tool.is_like_msvc() {
+		return
+	}
+	let name = &artifacts.lib_name;
+	let source = out_dir.join(format!("{name}-check.c"));
+	write(&source, format!(
+		"struct lua_State;\n\
+		struct lua_State *{prefix}luaL_newstate (void);\n\
+		int {prefix}luaL_loadstring (struct lua_State *L, const char *s);\n\
+		int {prefix}lua_pcallk (struct lua_State *L, int nargs, int nresults, int msgh, long ctx, void *k);\n\
+		void {prefix}lua_close (struct lua_State *L);\n\
+		int main (void) {{\n\
+		\tstruct lua_State *L = {prefix}luaL_newstate();\n\
+		\tif (L == 0 || {prefix}luaL_loadstring(L, \"error('boom')\") != 0) return 1;\n\
+		\tif ({prefix}lua_pcallk(L, 0, 0, 0, 0, 0) == 0) return 1;\n\
+		\t{prefix}lua_close(L);\n\
+		\treturn 0;\n\
+		}}\n"
+	)).unwrap();
+	let exe = out_dir.join(format!("{name}-check"));
+	let libs = artifacts.link_libs.iter().map(move |lib| format!("-l{}", lib.strip_prefix("static=").unwrap_or(lib)));
+	let status = tool.to_command()
+		.arg(&source)
+		.arg(artifacts.lib_path.as_ref().unwrap())
+		.args(libs)
+		.arg("-o").arg(&exe)
+		.status().unwrap();
+	assert!(status.success(), "failed to link a program to {name}");
+	assert!(Command::new(&exe).status().unwrap().success(), "the program linked to {name} failed");
 }
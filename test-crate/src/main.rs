@@ -123,11 +123,19 @@ impl Thread {
 }
 
 fn main() {
+	// `package.path` comes from the environment if these are set, instead of from `LUA_PATH_DEFAULT`.
+	// SAFETY: No other threads are running yet.
+	unsafe {
+		std::env::remove_var("LUA_PATH_5_4");
+		std::env::remove_var("LUA_PATH");
+	}
+
 	std::panic::catch_unwind(move || unsafe {
 		let mut lua = Lua::new().expect("failed to create Lua state");
 	
 		lua.open_libs();
 		assert!(lua.do_string(cr#"print("Hello, world!")"#));
+		assert!(lua.do_string(cr#"assert(package.path:find("\\lunka\\?.lua;./?.lua", 1, true), package.path)"#));
 
 		assert!(!lua.do_string(cr#"nonexistent()"#));
 		let error = lua.val_to_bytes(-1).unwrap();
@@ -0,0 +1,7 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#undef assert
+#if defined(NDEBUG)
+#define assert(e) ((void)0)
+#else
+#define assert(e) ((e) ? (void)0 : __builtin_trap())
+#endif
@@ -0,0 +1,19 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#ifndef LUNKA_LIBC_CTYPE_H
+#define LUNKA_LIBC_CTYPE_H
+
+int isalnum(int c);
+int isalpha(int c);
+int iscntrl(int c);
+int isdigit(int c);
+int isgraph(int c);
+int islower(int c);
+int isprint(int c);
+int ispunct(int c);
+int isspace(int c);
+int isupper(int c);
+int isxdigit(int c);
+int tolower(int c);
+int toupper(int c);
+
+#endif
@@ -0,0 +1,16 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#ifndef LUNKA_LIBC_ERRNO_H
+#define LUNKA_LIBC_ERRNO_H
+
+extern int errno;
+
+#define ENOENT 2
+#define EIO 5
+#define EBADF 9
+#define ENOMEM 12
+#define EINVAL 22
+#define EDOM 33
+#define ERANGE 34
+#define ENOSYS 38
+
+#endif
@@ -0,0 +1,35 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#ifndef LUNKA_LIBC_MATH_H
+#define LUNKA_LIBC_MATH_H
+
+#define HUGE_VAL (__builtin_huge_val())
+#define HUGE_VALF (__builtin_huge_valf())
+#define INFINITY (__builtin_inff())
+#define NAN (__builtin_nanf(""))
+
+#define isnan(x) ((x) != (x))
+#define isinf(x) (!isnan(x) && isnan((x) - (x)))
+#define isfinite(x) (!isnan((x) - (x)))
+#define signbit(x) (__builtin_signbit(x))
+
+double acos(double x);
+double asin(double x);
+double atan(double x);
+double atan2(double y, double x);
+double ceil(double x);
+double cos(double x);
+double exp(double x);
+double fabs(double x);
+double floor(double x);
+double fmod(double x, double y);
+double frexp(double x, int *e);
+double ldexp(double x, int e);
+double log(double x);
+double log10(double x);
+double log2(double x);
+double pow(double x, double y);
+double sin(double x);
+double sqrt(double x);
+double tan(double x);
+
+#endif
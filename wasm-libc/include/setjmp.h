@@ -0,0 +1,12 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** There is no way to unwind the stack without exception handling,
+** so `longjmp` traps, and so does every Lua error. */
+#ifndef LUNKA_LIBC_SETJMP_H
+#define LUNKA_LIBC_SETJMP_H
+
+typedef int jmp_buf[1];
+
+int setjmp(jmp_buf env);
+_Noreturn void longjmp(jmp_buf env, int val);
+
+#endif
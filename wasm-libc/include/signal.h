@@ -0,0 +1,7 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#ifndef LUNKA_LIBC_SIGNAL_H
+#define LUNKA_LIBC_SIGNAL_H
+
+typedef int sig_atomic_t;
+
+#endif
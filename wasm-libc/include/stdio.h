@@ -0,0 +1,47 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Files can't be opened, reading from `stdin` always reaches the end of the file,
+** and writing to `stdout` and `stderr` calls `lunka_libc_write`. */
+#ifndef LUNKA_LIBC_STDIO_H
+#define LUNKA_LIBC_STDIO_H
+
+#include <stdarg.h>
+#include <stddef.h>
+
+typedef struct lunka_file FILE;
+
+#define EOF (-1)
+#define BUFSIZ 512
+#define SEEK_SET 0
+#define SEEK_CUR 1
+#define SEEK_END 2
+
+extern FILE *stdin;
+extern FILE *stdout;
+extern FILE *stderr;
+
+FILE *fopen(const char *path, const char *mode);
+FILE *freopen(const char *path, const char *mode, FILE *f);
+int fclose(FILE *f);
+int fflush(FILE *f);
+int getc(FILE *f);
+int fgetc(FILE *f);
+int ungetc(int c, FILE *f);
+char *fgets(char *s, int n, FILE *f);
+size_t fread(void *p, size_t size, size_t n, FILE *f);
+size_t fwrite(const void *p, size_t size, size_t n, FILE *f);
+int fputs(const char *s, FILE *f);
+int feof(FILE *f);
+int ferror(FILE *f);
+void clearerr(FILE *f);
+
+int fprintf(FILE *f, const char *format, ...);
+int vfprintf(FILE *f, const char *format, va_list ap);
+int printf(const char *format, ...);
+int snprintf(char *s, size_t n, const char *format, ...);
+int vsnprintf(char *s, size_t n, const char *format, va_list ap);
+
+/* Write `n` bytes at `s` to the file descriptor `fd`.
+** The default definition discards them, and it can be replaced by one with the same signature. */
+void lunka_libc_write(int fd, const char *s, size_t n);
+
+#endif
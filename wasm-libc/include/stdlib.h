@@ -0,0 +1,22 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#ifndef LUNKA_LIBC_STDLIB_H
+#define LUNKA_LIBC_STDLIB_H
+
+#include <stddef.h>
+
+#define EXIT_SUCCESS 0
+#define EXIT_FAILURE 1
+
+void *malloc(size_t size);
+void *calloc(size_t n, size_t size);
+void *realloc(void *p, size_t size);
+void free(void *p);
+
+_Noreturn void abort(void);
+_Noreturn void exit(int status);
+char *getenv(const char *name);
+
+double strtod(const char *s, char **end);
+int abs(int x);
+
+#endif
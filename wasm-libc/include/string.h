@@ -0,0 +1,28 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`. */
+#ifndef LUNKA_LIBC_STRING_H
+#define LUNKA_LIBC_STRING_H
+
+#include <stddef.h>
+
+void *memchr(const void *p, int c, size_t n);
+int memcmp(const void *a, const void *b, size_t n);
+void *memcpy(void *restrict dst, const void *restrict src, size_t n);
+void *memmove(void *dst, const void *src, size_t n);
+void *memset(void *p, int c, size_t n);
+
+char *strcat(char *restrict dst, const char *restrict src);
+char *strchr(const char *s, int c);
+int strcmp(const char *a, const char *b);
+int strcoll(const char *a, const char *b);
+char *strcpy(char *restrict dst, const char *restrict src);
+size_t strcspn(const char *s, const char *reject);
+char *strerror(int n);
+size_t strlen(const char *s);
+int strncmp(const char *a, const char *b, size_t n);
+char *strncpy(char *restrict dst, const char *restrict src, size_t n);
+char *strpbrk(const char *s, const char *accept);
+char *strrchr(const char *s, int c);
+size_t strspn(const char *s, const char *accept);
+char *strstr(const char *s, const char *find);
+
+#endif
@@ -0,0 +1,16 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** There is no clock, so `time` and `clock` always fail. */
+#ifndef LUNKA_LIBC_TIME_H
+#define LUNKA_LIBC_TIME_H
+
+#include <stddef.h>
+
+typedef long long time_t;
+typedef long clock_t;
+
+#define CLOCKS_PER_SEC 1000000L
+
+time_t time(time_t *t);
+clock_t clock(void);
+
+#endif
@@ -0,0 +1,126 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Arbitrary-precision unsigned integers for floating-point conversions and argument reduction. */
+#include "internal.h"
+
+static void trim(lunka_big *a) {
+	while (a->n > 0 && a->d[a->n - 1] == 0)
+		a->n--;
+}
+
+void lunka_big_set(lunka_big *a, uint64_t v) {
+	a->n = 0;
+	while (v != 0) {
+		a->d[a->n++] = (uint32_t)v;
+		v >>= 32;
+	}
+}
+
+void lunka_big_mul_small(lunka_big *a, uint32_t m) {
+	uint64_t carry = 0;
+	for (int i = 0; i < a->n; i++) {
+		uint64_t t = (uint64_t)a->d[i] * m + carry;
+		a->d[i] = (uint32_t)t;
+		carry = t >> 32;
+	}
+	if (carry != 0 && a->n < LUNKA_BIG_LIMBS)
+		a->d[a->n++] = (uint32_t)carry;
+}
+
+void lunka_big_add_small(lunka_big *a, uint32_t v) {
+	uint64_t carry = v;
+	for (int i = 0; carry != 0 && i < a->n; i++) {
+		uint64_t t = (uint64_t)a->d[i] + carry;
+		a->d[i] = (uint32_t)t;
+		carry = t >> 32;
+	}
+	if (carry != 0 && a->n < LUNKA_BIG_LIMBS)
+		a->d[a->n++] = (uint32_t)carry;
+}
+
+void lunka_big_mul_pow5(lunka_big *a, int k) {
+	/* 5^13 is the largest power of 5 that fits in a limb. */
+	for (; k >= 13; k -= 13)
+		lunka_big_mul_small(a, 1220703125u);
+	uint32_t m = 1;
+	for (; k > 0; k--)
+		m *= 5;
+	lunka_big_mul_small(a, m);
+}
+
+void lunka_big_shl(lunka_big *a, int s) {
+	if (a->n == 0 || s <= 0)
+		return;
+	int limbs = s / 32, bits = s % 32;
+	int n = a->n + limbs + 1;
+	if (n > LUNKA_BIG_LIMBS)
+		n = LUNKA_BIG_LIMBS;
+	for (int i = n - 1; i >= 0; i--) {
+		int j = i - limbs;
+		uint32_t hi = j >= 0 && j < a->n ? a->d[j] : 0;
+		uint32_t lo = j >= 1 && j - 1 < a->n ? a->d[j - 1] : 0;
+		a->d[i] = bits == 0 ? hi : (hi << bits) | (lo >> (32 - bits));
+	}
+	a->n = n;
+	trim(a);
+}
+
+void lunka_big_shr1(lunka_big *a) {
+	for (int i = 0; i < a->n; i++)
+		a->d[i] = (a->d[i] >> 1) | (i + 1 < a->n ? a->d[i + 1] << 31 : 0);
+	trim(a);
+}
+
+int lunka_big_bits(const lunka_big *a) {
+	if (a->n == 0)
+		return 0;
+	return (a->n - 1) * 32 + (32 - __builtin_clz(a->d[a->n - 1]));
+}
+
+int lunka_big_bit(const lunka_big *a, int k) {
+	if (k < 0 || k / 32 >= a->n)
+		return 0;
+	return (a->d[k / 32] >> (k % 32)) & 1;
+}
+
+int lunka_big_cmp(const lunka_big *a, const lunka_big *b) {
+	if (a->n != b->n)
+		return a->n < b->n ? -1 : 1;
+	for (int i = a->n - 1; i >= 0; i--) {
+		if (a->d[i] != b->d[i])
+			return a->d[i] < b->d[i] ? -1 : 1;
+	}
+	return 0;
+}
+
+void lunka_big_sub(lunka_big *a, const lunka_big *b) {
+	int64_t borrow = 0;
+	for (int i = 0; i < a->n; i++) {
+		int64_t t = (int64_t)a->d[i] - (i < b->n ? b->d[i] : 0) - borrow;
+		borrow = t < 0;
+		a->d[i] = (uint32_t)(t + (borrow << 32));
+	}
+	trim(a);
+}
+
+uint32_t lunka_big_div_small(lunka_big *a, uint32_t m) {
+	uint64_t rem = 0;
+	for (int i = a->n - 1; i >= 0; i--) {
+		uint64_t t = (rem << 32) | a->d[i];
+		a->d[i] = (uint32_t)(t / m);
+		rem = t % m;
+	}
+	trim(a);
+	return (uint32_t)rem;
+}
+
+uint64_t lunka_big_extract(const lunka_big *a, int shift, int *sticky) {
+	uint64_t v = 0;
+	for (int k = shift + 63; k >= shift; k--)
+		v = (v << 1) | (uint64_t)lunka_big_bit(a, k);
+	int limb = shift / 32;
+	for (int i = 0; shift > 0 && i < limb && i < a->n; i++)
+		*sticky |= a->d[i] != 0;
+	if (shift > 0 && limb < a->n)
+		*sticky |= (a->d[limb] & ((1u << (shift % 32)) - 1)) != 0;
+	return v;
+}
@@ -0,0 +1,310 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Exact conversions between decimal strings and binary floating-point numbers. */
+#include <ctype.h>
+#include <errno.h>
+#include <math.h>
+#include <stdlib.h>
+
+#include "internal.h"
+
+/* Largest number of significant decimal digits that are kept when parsing.
+** Later digits only decide the rounding. */
+#define PARSE_DIGITS_MAX 800
+
+int lunka_libc_digits(double x, char *buf, int *point) {
+	uint64_t bits = lunka_libc_bits(x);
+	int exponent = (int)(bits >> 52 & 0x7ff);
+	uint64_t mant = bits & (((uint64_t)1 << 52) - 1);
+	int e2;
+	if (exponent == 0) {
+		e2 = -1074;
+	} else {
+		mant |= (uint64_t)1 << 52;
+		e2 = exponent - 1075;
+	}
+	for (; (mant & 1) == 0; mant >>= 1)
+		e2++;
+
+	/* x = n * 10^-k exactly. */
+	lunka_big n;
+	int k = 0;
+	lunka_big_set(&n, mant);
+	if (e2 >= 0) {
+		lunka_big_shl(&n, e2);
+	} else {
+		k = -e2;
+		lunka_big_mul_pow5(&n, k);
+	}
+
+	uint32_t chunks[LUNKA_DIGITS_MAX / 9 + 1];
+	int nchunks = 0;
+	while (n.n != 0)
+		chunks[nchunks++] = lunka_big_div_small(&n, 1000000000u);
+
+	int len = 0;
+	for (int i = nchunks - 1; i >= 0; i--) {
+		char tmp[9];
+		uint32_t c = chunks[i];
+		for (int j = 8; j >= 0; j--) {
+			tmp[j] = (char)('0' + c % 10);
+			c /= 10;
+		}
+		int j = 0;
+		if (i == nchunks - 1) {
+			while (tmp[j] == '0')
+				j++;
+		}
+		for (; j < 9; j++)
+			buf[len++] = tmp[j];
+	}
+	*point = len - k;
+	while (buf[len - 1] == '0')
+		len--;
+	return len;
+}
+
+double lunka_libc_make_double(uint64_t mant, long e2, int sticky) {
+	if (mant == 0)
+		return 0.0;
+	for (; (mant >> 63) == 0; mant <<= 1)
+		e2--;
+	/* mant * 2^e2 is in [2^e, 2^(e + 1)). */
+	long e = e2 + 63;
+	if (e > 1023) {
+		errno = ERANGE;
+		return HUGE_VAL;
+	}
+	int shift = 11;
+	if (e < -1022) {
+		if (-1022 - e > 53) {
+			errno = ERANGE;
+			return 0.0;
+		}
+		shift += (int)(-1022 - e);
+	}
+
+	uint64_t kept, rem, half;
+	if (shift == 64) {
+		kept = 0;
+		rem = mant;
+	} else {
+		kept = mant >> shift;
+		rem = mant & (((uint64_t)1 << shift) - 1);
+	}
+	half = (uint64_t)1 << (shift - 1);
+	if (rem > half || (rem == half && (sticky || (kept & 1))))
+		kept++;
+
+	uint64_t bits;
+	if (e >= -1022) {
+		if (kept >> 53) {
+			kept >>= 1;
+			e++;
+			if (e > 1023) {
+				errno = ERANGE;
+				return HUGE_VAL;
+			}
+		}
+		bits = (uint64_t)(e + 1023) << 52 | (kept & (((uint64_t)1 << 52) - 1));
+	} else {
+		bits = kept;
+		if (bits == 0)
+			errno = ERANGE;
+	}
+	return lunka_libc_from_bits(bits);
+}
+
+/* Return the exact value of the digits `d` times 10^q, rounded to nearest. */
+static double decimal_to_double(const char *d, int nd, long q, int sticky) {
+	static const double powers[] = {
+		1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11,
+		1e12, 1e13, 1e14, 1e15, 1e16, 1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+	};
+	if (nd == 0)
+		return 0.0;
+	if (nd + q > 310) {
+		errno = ERANGE;
+		return HUGE_VAL;
+	}
+	if (nd + q < -330) {
+		errno = ERANGE;
+		return 0.0;
+	}
+
+	/* Both the digits and the power of 10 are exact, so there is a single rounding. */
+	if (!sticky && nd <= 15 && q >= -22 && q <= 22) {
+		double v = 0;
+		for (int i = 0; i < nd; i++)
+			v = v * 10 + (d[i] - '0');
+		return q < 0 ? v / powers[-q] : v * powers[q];
+	}
+
+	lunka_big n;
+	lunka_big_set(&n, 0);
+	for (int i = 0; i < nd; i++) {
+		lunka_big_mul_small(&n, 10);
+		lunka_big_add_small(&n, (uint32_t)(d[i] - '0'));
+	}
+
+	if (q >= 0) {
+		lunka_big_mul_pow5(&n, (int)q);
+		lunka_big_shl(&n, (int)q);
+		int shift = lunka_big_bits(&n) - 64;
+		if (shift < 0)
+			shift = 0;
+		uint64_t mant = lunka_big_extract(&n, shift, &sticky);
+		return lunka_libc_make_double(mant, shift, sticky);
+	}
+
+	/* Divide by 10^-q, scaling so that the quotient has 63 or 64 bits. */
+	lunka_big m;
+	lunka_big_set(&m, 1);
+	lunka_big_mul_pow5(&m, (int)-q);
+	lunka_big_shl(&m, (int)-q);
+	int s = lunka_big_bits(&m) - lunka_big_bits(&n) + 63;
+	if (s > 0)
+		lunka_big_shl(&n, s);
+	else
+		lunka_big_shl(&m, -s);
+	uint64_t quot = 0;
+	lunka_big_shl(&m, 63);
+	for (int i = 63; i >= 0; i--) {
+		if (lunka_big_cmp(&n, &m) >= 0) {
+			lunka_big_sub(&n, &m);
+			quot |= (uint64_t)1 << i;
+		}
+		lunka_big_shr1(&m);
+	}
+	return lunka_libc_make_double(quot, -s, sticky || n.n != 0);
+}
+
+static int hex_value(int c) {
+	return isdigit(c) ? c - '0' : (c | 0x20) - 'a' + 10;
+}
+
+/* Parse a binary exponent or decimal exponent after `p`, if there is one. */
+static const char *parse_exponent(const char *p, long *exp) {
+	const char *q = p + 1;
+	int negative = 0;
+	if (*q == '+' || *q == '-')
+		negative = *q++ == '-';
+	if (!isdigit((unsigned char)*q))
+		return p;
+	long e = 0;
+	for (; isdigit((unsigned char)*q); q++) {
+		if (e < 100000)
+			e = e * 10 + (*q - '0');
+	}
+	*exp += negative ? -e : e;
+	return q;
+}
+
+static double parse_hex(const char *p, const char **end) {
+	uint64_t mant = 0;
+	long e2 = 0;
+	int sticky = 0, fraction = 0;
+	for (;; p++) {
+		if (*p == '.' && !fraction) {
+			fraction = 1;
+			continue;
+		}
+		if (!isxdigit((unsigned char)*p))
+			break;
+		int h = hex_value(*p);
+		if (mant >> 60 == 0) {
+			mant = mant << 4 | (uint64_t)h;
+			if (fraction)
+				e2 -= 4;
+		} else {
+			sticky |= h != 0;
+			if (!fraction)
+				e2 += 4;
+		}
+	}
+	if ((*p | 0x20) == 'p')
+		p = parse_exponent(p, &e2);
+	*end = p;
+	return lunka_libc_make_double(mant, e2, sticky);
+}
+
+static double parse_decimal(const char *p, const char **end) {
+	char digits[PARSE_DIGITS_MAX];
+	int nd = 0, sticky = 0, fraction = 0, any = 0;
+	long q = 0;
+	for (;; p++) {
+		if (*p == '.' && !fraction) {
+			fraction = 1;
+			continue;
+		}
+		if (!isdigit((unsigned char)*p))
+			break;
+		any = 1;
+		if (nd == 0 && *p == '0') {
+			if (fraction)
+				q--;
+		} else if (nd < PARSE_DIGITS_MAX) {
+			digits[nd++] = *p;
+			if (fraction)
+				q--;
+		} else {
+			sticky |= *p != '0';
+			if (!fraction)
+				q++;
+		}
+	}
+	if (!any) {
+		*end = NULL;
+		return 0.0;
+	}
+	if ((*p | 0x20) == 'e')
+		p = parse_exponent(p, &q);
+	*end = p;
+	for (; nd > 0 && digits[nd - 1] == '0'; nd--)
+		q++;
+	return decimal_to_double(digits, nd, q, sticky);
+}
+
+/* Return the end of `word` at the start of `p`, ignoring case, or `NULL` if it's not there. */
+static const char *match_word(const char *p, const char *word) {
+	for (; *word != '\0'; p++, word++) {
+		if (tolower((unsigned char)*p) != *word)
+			return NULL;
+	}
+	return p;
+}
+
+double strtod(const char *s, char **end) {
+	const char *p = s, *after;
+	while (isspace((unsigned char)*p))
+		p++;
+	int negative = 0;
+	if (*p == '+' || *p == '-')
+		negative = *p++ == '-';
+
+	double r;
+	if ((after = match_word(p, "infinity")) != NULL || (after = match_word(p, "inf")) != NULL) {
+		r = HUGE_VAL;
+	} else if ((after = match_word(p, "nan")) != NULL) {
+		r = NAN;
+		if (*after == '(') {
+			const char *q = after + 1;
+			while (isalnum((unsigned char)*q) || *q == '_')
+				q++;
+			if (*q == ')')
+				after = q + 1;
+		}
+	} else if (p[0] == '0' && (p[1] | 0x20) == 'x'
+		&& (isxdigit((unsigned char)p[2]) || (p[2] == '.' && isxdigit((unsigned char)p[3])))) {
+		r = parse_hex(p + 2, &after);
+	} else {
+		r = parse_decimal(p, &after);
+		if (after == NULL) {
+			if (end != NULL)
+				*end = (char *)s;
+			return 0.0;
+		}
+	}
+	if (end != NULL)
+		*end = (char *)after;
+	return negative ? -r : r;
+}
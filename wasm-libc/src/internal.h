@@ -0,0 +1,60 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Declarations shared between the sources of the library. */
+#ifndef LUNKA_LIBC_INTERNAL_H
+#define LUNKA_LIBC_INTERNAL_H
+
+#include <stdint.h>
+
+/* Keep the compiler from turning the loops of a function into calls to `memcpy` or `memset`,
+** for the functions that implement them. */
+#if defined(__clang__)
+#define LUNKA_NO_BUILTIN __attribute__((no_builtin))
+#elif defined(__GNUC__)
+#define LUNKA_NO_BUILTIN __attribute__((optimize("no-tree-loop-distribute-patterns")))
+#else
+#define LUNKA_NO_BUILTIN
+#endif
+
+static inline uint64_t lunka_libc_bits(double x) {
+	union { double f; uint64_t i; } u = { x };
+	return u.i;
+}
+
+static inline double lunka_libc_from_bits(uint64_t i) {
+	union { uint64_t i; double f; } u = { i };
+	return u.f;
+}
+
+/* Unsigned integer that is large enough for exact conversions between decimal and binary floating-point numbers. */
+#define LUNKA_BIG_LIMBS 160
+
+typedef struct lunka_big {
+	/* Limbs, from the least significant one. */
+	uint32_t d[LUNKA_BIG_LIMBS];
+	/* Number of limbs in use, without leading zero limbs. */
+	int n;
+} lunka_big;
+
+void lunka_big_set(lunka_big *a, uint64_t v);
+void lunka_big_mul_small(lunka_big *a, uint32_t m);
+void lunka_big_add_small(lunka_big *a, uint32_t v);
+void lunka_big_mul_pow5(lunka_big *a, int k);
+void lunka_big_shl(lunka_big *a, int s);
+void lunka_big_shr1(lunka_big *a);
+int lunka_big_bits(const lunka_big *a);
+int lunka_big_bit(const lunka_big *a, int k);
+int lunka_big_cmp(const lunka_big *a, const lunka_big *b);
+void lunka_big_sub(lunka_big *a, const lunka_big *b);
+uint32_t lunka_big_div_small(lunka_big *a, uint32_t m);
+uint64_t lunka_big_extract(const lunka_big *a, int shift, int *sticky);
+
+/* Write the shortest exact decimal digits of the finite, positive `x` to `buf`, and return their number.
+** `*point` is set so that `x` is `0.DIGITS * 10^*point`.
+** `buf` must have space for `LUNKA_DIGITS_MAX` digits. */
+#define LUNKA_DIGITS_MAX 800
+int lunka_libc_digits(double x, char *buf, int *point);
+
+/* Return `mant * 2^e2`, rounded to nearest, where `sticky` is set if the exact value is slightly larger. */
+double lunka_libc_make_double(uint64_t mant, long e2, int sticky);
+
+#endif
@@ -0,0 +1,160 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** First-fit allocator over a free list that is kept sorted by address,
+** so that neighbouring free blocks can be merged.
+** Memory is taken from `memory.grow` and never given back. */
+#include <stdint.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* Alignment of every allocation, and of the size of every block. */
+#define ALIGN 16
+/* Smallest amount of memory to request at once. */
+#define CHUNK_SIZE ((size_t)1 << 20)
+
+typedef struct block {
+	/* Size of the block including this header. */
+	size_t size;
+	/* Next free block, if this block is free. */
+	struct block *next;
+} block;
+
+#define HEADER_SIZE ((sizeof(block) + ALIGN - 1) & ~(size_t)(ALIGN - 1))
+
+static block *free_list = NULL;
+
+#if defined(__wasm__)
+static void *more_core(size_t size) {
+	size_t pages = (size + 0xffff) >> 16;
+	size_t old = __builtin_wasm_memory_grow(0, pages);
+	if (old == (size_t)-1)
+		return NULL;
+	return (void *)(old << 16);
+}
+#else
+/* Fixed arena for builds that aren't for WebAssembly, such as tests of this library. */
+static unsigned char arena[(size_t)64 << 20] __attribute__((aligned(ALIGN)));
+static size_t arena_used = 0;
+
+static void *more_core(size_t size) {
+	size = (size + 0xffff) & ~(size_t)0xffff;
+	if (sizeof(arena) - arena_used < size)
+		return NULL;
+	arena_used += size;
+	return arena + arena_used - size;
+}
+#endif
+
+/* Insert the free block `b` into the free list, merging it with its neighbours. */
+static void release(block *b) {
+	block *prev = NULL, *next = free_list;
+	while (next != NULL && next < b) {
+		prev = next;
+		next = next->next;
+	}
+	if (next != NULL && (unsigned char *)b + b->size == (unsigned char *)next) {
+		b->size += next->size;
+		next = next->next;
+	}
+	b->next = next;
+	if (prev != NULL && (unsigned char *)prev + prev->size == (unsigned char *)b) {
+		prev->size += b->size;
+		prev->next = b->next;
+	} else if (prev != NULL) {
+		prev->next = b;
+	} else {
+		free_list = b;
+	}
+}
+
+/* Shrink the used block `b` to `size` bytes, releasing the rest if it's large enough for a block. */
+static void trim(block *b, size_t size) {
+	if (b->size - size >= HEADER_SIZE + ALIGN) {
+		block *rest = (block *)((unsigned char *)b + size);
+		rest->size = b->size - size;
+		b->size = size;
+		release(rest);
+	}
+}
+
+/* Return the size of a block that can hold `n` bytes, or 0 if it's too large. */
+static size_t block_size(size_t n) {
+	if (n > SIZE_MAX - HEADER_SIZE - ALIGN)
+		return 0;
+	return (n + HEADER_SIZE + ALIGN - 1) & ~(size_t)(ALIGN - 1);
+}
+
+void *malloc(size_t n) {
+	size_t size = block_size(n);
+	if (size == 0)
+		return NULL;
+	for (int attempt = 0; attempt < 2; attempt++) {
+		block *prev = NULL;
+		for (block *b = free_list; b != NULL; prev = b, b = b->next) {
+			if (b->size >= size) {
+				if (prev != NULL)
+					prev->next = b->next;
+				else
+					free_list = b->next;
+				trim(b, size);
+				return (unsigned char *)b + HEADER_SIZE;
+			}
+		}
+		size_t core_size = size > CHUNK_SIZE ? size : CHUNK_SIZE;
+		block *b = more_core(core_size);
+		if (b == NULL)
+			return NULL;
+		b->size = (core_size + 0xffff) & ~(size_t)0xffff;
+		release(b);
+	}
+	return NULL;
+}
+
+void *calloc(size_t n, size_t size) {
+	if (size != 0 && n > SIZE_MAX / size)
+		return NULL;
+	void *p = malloc(n * size);
+	if (p != NULL)
+		memset(p, 0, n * size);
+	return p;
+}
+
+void *realloc(void *p, size_t n) {
+	if (p == NULL)
+		return malloc(n);
+	block *b = (block *)((unsigned char *)p - HEADER_SIZE);
+	size_t size = block_size(n);
+	if (size == 0)
+		return NULL;
+	if (b->size >= size) {
+		trim(b, size);
+		return p;
+	}
+
+	/* Try to grow into the free block right after this one. */
+	block *prev = NULL, *next = free_list;
+	while (next != NULL && next < b) {
+		prev = next;
+		next = next->next;
+	}
+	if (next != NULL && (unsigned char *)b + b->size == (unsigned char *)next && b->size + next->size >= size) {
+		if (prev != NULL)
+			prev->next = next->next;
+		else
+			free_list = next->next;
+		b->size += next->size;
+		trim(b, size);
+		return p;
+	}
+
+	void *q = malloc(n);
+	if (q != NULL) {
+		memcpy(q, p, b->size - HEADER_SIZE);
+		free(p);
+	}
+	return q;
+}
+
+void free(void *p) {
+	if (p != NULL)
+		release((block *)((unsigned char *)p - HEADER_SIZE));
+}
@@ -0,0 +1,1048 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Elementary functions, ported from FreeBSD's msun (originally fdlibm, by Sun Microsystems).
+**
+** Copyright (C) 1993-2004 by Sun Microsystems, Inc. All rights reserved.
+** Developed at SunSoft, a Sun Microsystems, Inc. business.
+** Permission to use, copy, modify, and distribute this software is freely granted,
+** provided that this notice is preserved. */
+#include <math.h>
+#include <stdint.h>
+
+#include "internal.h"
+
+#define asuint64 lunka_libc_bits
+#define asdouble lunka_libc_from_bits
+
+static uint32_t high_word(double x) {
+	return (uint32_t)(asuint64(x) >> 32);
+}
+
+static uint32_t low_word(double x) {
+	return (uint32_t)asuint64(x);
+}
+
+static double with_high_word(double x, uint32_t hi) {
+	return asdouble((uint64_t)hi << 32 | (asuint64(x) & 0xffffffff));
+}
+
+static double with_low_word(double x, uint32_t lo) {
+	return asdouble((asuint64(x) & 0xffffffff00000000ull) | lo);
+}
+
+double fabs(double x) {
+	return asdouble(asuint64(x) & ~((uint64_t)1 << 63));
+}
+
+double sqrt(double x) {
+	/* This is a single instruction on WebAssembly. */
+	return __builtin_sqrt(x);
+}
+
+static const double toint = 4503599627370496.0; /* 2^52 */
+
+double floor(double x) {
+	uint64_t i = asuint64(x);
+	int e = (int)(i >> 52 & 0x7ff);
+	double y;
+	if (e >= 0x3ff + 52 || x == 0)
+		return x;
+	/* y = int(x) - x, where int(x) is an integer neighbor of x. */
+	if (i >> 63)
+		y = x - toint + toint - x;
+	else
+		y = x + toint - toint - x;
+	if (e <= 0x3ff - 1)
+		return i >> 63 ? -1.0 : 0.0;
+	if (y > 0)
+		return x + y - 1;
+	return x + y;
+}
+
+double ceil(double x) {
+	uint64_t i = asuint64(x);
+	int e = (int)(i >> 52 & 0x7ff);
+	double y;
+	if (e >= 0x3ff + 52 || x == 0)
+		return x;
+	if (i >> 63)
+		y = x - toint + toint - x;
+	else
+		y = x + toint - toint - x;
+	if (e <= 0x3ff - 1)
+		return i >> 63 ? -0.0 : 1.0;
+	if (y < 0)
+		return x + y + 1;
+	return x + y;
+}
+
+double frexp(double x, int *e) {
+	uint64_t i = asuint64(x);
+	int ee = (int)(i >> 52 & 0x7ff);
+	if (ee == 0) {
+		if (x != 0) {
+			x = frexp(x * 0x1p64, e);
+			*e -= 64;
+		} else {
+			*e = 0;
+		}
+		return x;
+	} else if (ee == 0x7ff) {
+		*e = 0;
+		return x;
+	}
+	*e = ee - 0x3fe;
+	i &= 0x800fffffffffffffull;
+	i |= 0x3fe0000000000000ull;
+	return asdouble(i);
+}
+
+double ldexp(double x, int n) {
+	double y = x;
+	if (n > 1023) {
+		y *= 0x1p1023;
+		n -= 1023;
+		if (n > 1023) {
+			y *= 0x1p1023;
+			n -= 1023;
+			if (n > 1023)
+				n = 1023;
+		}
+	} else if (n < -1022) {
+		/* Keep the final n below -53 to avoid double rounding in the subnormal range. */
+		y *= 0x1p-1022 * 0x1p53;
+		n += 1022 - 53;
+		if (n < -1022) {
+			y *= 0x1p-1022 * 0x1p53;
+			n += 1022 - 53;
+			if (n < -1022)
+				n = -1022;
+		}
+	}
+	return y * asdouble((uint64_t)(0x3ff + n) << 52);
+}
+
+double fmod(double x, double y) {
+	uint64_t uxi = asuint64(x), uyi = asuint64(y), i;
+	int ex = (int)(uxi >> 52 & 0x7ff), ey = (int)(uyi >> 52 & 0x7ff);
+	int sx = (int)(uxi >> 63);
+
+	if (uyi << 1 == 0 || isnan(y) || ex == 0x7ff)
+		return (x * y) / (x * y);
+	if (uxi << 1 <= uyi << 1) {
+		if (uxi << 1 == uyi << 1)
+			return 0 * x;
+		return x;
+	}
+
+	/* Normalize x and y. */
+	if (ex == 0) {
+		for (i = uxi << 12; i >> 63 == 0; ex--, i <<= 1) {}
+		uxi <<= -ex + 1;
+	} else {
+		uxi &= -1ull >> 12;
+		uxi |= 1ull << 52;
+	}
+	if (ey == 0) {
+		for (i = uyi << 12; i >> 63 == 0; ey--, i <<= 1) {}
+		uyi <<= -ey + 1;
+	} else {
+		uyi &= -1ull >> 12;
+		uyi |= 1ull << 52;
+	}
+
+	for (; ex > ey; ex--) {
+		i = uxi - uyi;
+		if (i >> 63 == 0) {
+			if (i == 0)
+				return 0 * x;
+			uxi = i;
+		}
+		uxi <<= 1;
+	}
+	i = uxi - uyi;
+	if (i >> 63 == 0) {
+		if (i == 0)
+			return 0 * x;
+		uxi = i;
+	}
+	for (; uxi >> 52 == 0; uxi <<= 1, ex--) {}
+
+	/* Scale the result. */
+	if (ex > 0) {
+		uxi -= 1ull << 52;
+		uxi |= (uint64_t)ex << 52;
+	} else {
+		uxi >>= -ex + 1;
+	}
+	uxi |= (uint64_t)sx << 63;
+	return asdouble(uxi);
+}
+
+static const double
+ln2_hi = 6.93147180369123816490e-01, /* 0x3fe62e42, 0xfee00000 */
+ln2_lo = 1.90821492927058770002e-10, /* 0x3dea39ef, 0x35793c76 */
+invln2 = 1.44269504088896338700e+00, /* 0x3ff71547, 0x652b82fe */
+P1 = 1.66666666666666019037e-01, /* 0x3FC55555, 0x5555553E */
+P2 = -2.77777777770155933842e-03, /* 0xBF66C16C, 0x16BEBD93 */
+P3 = 6.61375632143793436117e-05, /* 0x3F11566A, 0xAF25DE2C */
+P4 = -1.65339022054652515390e-06, /* 0xBEBBBD41, 0xC5D26BF1 */
+P5 = 4.13813679705723846039e-08; /* 0x3E663769, 0x72BEA4D0 */
+
+double exp(double x) {
+	static const double half[2] = { 0.5, -0.5 };
+	double hi = 0, lo = 0, c, xx, y;
+	int k, sign;
+	uint32_t hx = high_word(x);
+	sign = (int)(hx >> 31);
+	hx &= 0x7fffffff;
+
+	/* Special cases. */
+	if (hx >= 0x4086232b) { /* |x| >= 708.39 or NaN */
+		if (isnan(x))
+			return x;
+		if (x > 709.782712893383973096)
+			return x * 0x1p1023; /* Overflow. */
+		if (x < -745.13321910194110842)
+			return 0; /* Underflow. */
+	}
+
+	/* Argument reduction. */
+	if (hx > 0x3fd62e42) { /* |x| > 0.5 ln2 */
+		if (hx >= 0x3ff0a2b2) /* |x| >= 1.5 ln2 */
+			k = (int)(invln2 * x + half[sign]);
+		else
+			k = 1 - sign - sign;
+		hi = x - k * ln2_hi; /* k*ln2_hi is exact here. */
+		lo = k * ln2_lo;
+		x = hi - lo;
+	} else if (hx > 0x3e300000) { /* |x| > 2^-28 */
+		k = 0;
+		hi = x;
+		lo = 0;
+	} else {
+		return 1 + x;
+	}
+
+	/* x is now in the primary range. */
+	xx = x * x;
+	c = x - xx * (P1 + xx * (P2 + xx * (P3 + xx * (P4 + xx * P5))));
+	y = 1 + (x * c / (2 - c) - lo + hi);
+	if (k == 0)
+		return y;
+	return ldexp(y, k);
+}
+
+static const double
+Lg1 = 6.666666666666735130e-01, /* 3FE55555 55555593 */
+Lg2 = 3.999999999940941908e-01, /* 3FD99999 9997FA04 */
+Lg3 = 2.857142874366239149e-01, /* 3FD24924 94229359 */
+Lg4 = 2.222219843214978396e-01, /* 3FCC71C5 1D8E78AF */
+Lg5 = 1.818357216161805012e-01, /* 3FC74664 96CB03DE */
+Lg6 = 1.531383769920937332e-01, /* 3FC39A09 D078C69F */
+Lg7 = 1.479819860511658591e-01; /* 3FC2F112 DF3E5244 */
+
+/* Logarithm of x = 2^k * (1 + f), where 1 + f is in [sqrt(2)/2, sqrt(2)]. */
+typedef struct log_parts {
+	int k;
+	double f, s, hfsq, r;
+} log_parts;
+
+/* Return the result of a logarithm for a special `x`, which is zero, negative, infinite or NaN. */
+static int log_special(double x, double *r) {
+	uint64_t i = asuint64(x);
+	if (i << 1 == 0) {
+		*r = -1 / (x * x);
+		return 1;
+	}
+	if (i >> 63) {
+		*r = (x - x) / 0.0;
+		return 1;
+	}
+	if (i >> 52 >= 0x7ff) {
+		*r = x;
+		return 1;
+	}
+	return 0;
+}
+
+static log_parts log_reduce(double x) {
+	log_parts p;
+	p.k = 0;
+	uint32_t hx = high_word(x);
+	if (hx < 0x00100000) {
+		/* Scale up subnormal numbers. */
+		p.k -= 54;
+		x *= 0x1p54;
+		hx = high_word(x);
+	}
+	hx += 0x3ff00000 - 0x3fe6a09e;
+	p.k += (int)(hx >> 20) - 0x3ff;
+	hx = (hx & 0x000fffff) + 0x3fe6a09e;
+	x = with_high_word(x, hx);
+
+	p.f = x - 1.0;
+	p.hfsq = 0.5 * p.f * p.f;
+	p.s = p.f / (2.0 + p.f);
+	double z = p.s * p.s, w = z * z;
+	double t1 = w * (Lg2 + w * (Lg4 + w * Lg6));
+	double t2 = z * (Lg1 + w * (Lg3 + w * (Lg5 + w * Lg7)));
+	p.r = t2 + t1;
+	return p;
+}
+
+double log(double x) {
+	double r;
+	if (log_special(x, &r))
+		return r;
+	log_parts p = log_reduce(x);
+	double dk = p.k;
+	return p.s * (p.hfsq + p.r) + dk * ln2_lo - p.hfsq + p.f + dk * ln2_hi;
+}
+
+/* Split log(1 + f) into `hi + lo`, where `hi` has its low word cleared. */
+static void log_split(const log_parts *p, double *hi, double *lo) {
+	*hi = with_low_word(p->f - p->hfsq, 0);
+	*lo = p->f - *hi - p->hfsq + p->s * (p->hfsq + p->r);
+}
+
+double log2(double x) {
+	static const double
+	ivln2hi = 1.44269504072144627571e+00, /* 0x3ff71547, 0x65200000 */
+	ivln2lo = 1.67517131648865118353e-10; /* 0x3de705fc, 0x2eefa200 */
+	double r, hi, lo;
+	if (log_special(x, &r))
+		return r;
+	log_parts p = log_reduce(x);
+	log_split(&p, &hi, &lo);
+	double val_hi = hi * ivln2hi;
+	double val_lo = (lo + hi) * ivln2lo + lo * ivln2hi;
+	double y = p.k, w = y + val_hi;
+	val_lo += (y - w) + val_hi;
+	val_hi = w;
+	return val_lo + val_hi;
+}
+
+double log10(double x) {
+	static const double
+	ivln10hi = 4.34294481878168880939e-01, /* 0x3fdbcb7b, 0x15200000 */
+	ivln10lo = 2.50829467116452752298e-11, /* 0x3dbb9438, 0xca9aadd5 */
+	log10_2hi = 3.01029995663611771306e-01, /* 0x3FD34413, 0x509F6000 */
+	log10_2lo = 3.69423907715893078616e-13; /* 0x3D59FEF3, 0x11F12B36 */
+	double r, hi, lo;
+	if (log_special(x, &r))
+		return r;
+	log_parts p = log_reduce(x);
+	log_split(&p, &hi, &lo);
+	double val_hi = hi * ivln10hi;
+	double dk = p.k, y = dk * log10_2hi;
+	double val_lo = dk * log10_2lo + (lo + hi) * ivln10lo + lo * ivln10hi;
+	double w = y + val_hi;
+	val_lo += (y - w) + val_hi;
+	val_hi = w;
+	return val_lo + val_hi;
+}
+
+double pow(double x, double y) {
+	static const double
+	bp[] = { 1.0, 1.5 },
+	dp_h[] = { 0.0, 5.84962487220764160156e-01 }, /* 0x3FE2B803, 0x40000000 */
+	dp_l[] = { 0.0, 1.35003920212974897128e-08 }, /* 0x3E4CFDEB, 0x43CFD006 */
+	two53 = 9007199254740992.0, /* 0x43400000, 0x00000000 */
+	huge = 1.0e300,
+	tiny = 1.0e-300,
+	/* Coefficients for (3/2)*(log(x)-2s-2/3*s**3). */
+	L1 = 5.99999999999994648725e-01, /* 0x3FE33333, 0x33333303 */
+	L2 = 4.28571428578550184252e-01, /* 0x3FDB6DB6, 0xDB6FABFF */
+	L3 = 3.33333329818377432918e-01, /* 0x3FD55555, 0x518F264D */
+	L4 = 2.72728123808534006489e-01, /* 0x3FD17460, 0xA91D4101 */
+	L5 = 2.30660745775561754067e-01, /* 0x3FCD864A, 0x93C9DB65 */
+	L6 = 2.06975017800338417784e-01, /* 0x3FCA7E28, 0x4A454EEF */
+	lg2 = 6.93147180559945286227e-01, /* 0x3FE62E42, 0xFEFA39EF */
+	lg2_h = 6.93147182464599609375e-01, /* 0x3FE62E43, 0x00000000 */
+	lg2_l = -1.90465429995776804525e-09, /* 0xBE205C61, 0x0CA86C39 */
+	ovt = 8.0085662595372944372e-17, /* -(1024-log2(ovfl+.5ulp)) */
+	cp = 9.61796693925975554329e-01, /* 0x3FEEC709, 0xDC3A03FD = 2/(3ln2) */
+	cp_h = 9.61796700954437255859e-01, /* 0x3FEEC709, 0xE0000000 = (float)cp */
+	cp_l = -7.02846165095275826516e-09, /* 0xBE3E2FE0, 0x145B01F5 = tail of cp_h */
+	ivln2 = 1.44269504088896338700e+00, /* 0x3FF71547, 0x652B82FE = 1/ln2 */
+	ivln2_h = 1.44269502162933349609e+00, /* 0x3FF71547, 0x60000000 = 24b 1/ln2 */
+	ivln2_l = 1.92596299112661746887e-08; /* 0x3E54AE0B, 0xF85DDF44 = 1/ln2 tail */
+
+	double z, ax, z_h, z_l, p_h, p_l;
+	double y1, t1, t2, r, s, t, u, v, w;
+	int32_t i, j, k, yisint, n;
+	int32_t hx, hy, ix, iy;
+	uint32_t lx, ly;
+
+	hx = (int32_t)high_word(x);
+	lx = low_word(x);
+	hy = (int32_t)high_word(y);
+	ly = low_word(y);
+	ix = hx & 0x7fffffff;
+	iy = hy & 0x7fffffff;
+
+	/* x**0 = 1, even if x is NaN. */
+	if ((iy | ly) == 0)
+		return 1.0;
+	/* 1**y = 1, even if y is NaN. */
+	if (hx == 0x3ff00000 && lx == 0)
+		return 1.0;
+	/* NaN if either argument is NaN. */
+	if (ix > 0x7ff00000 || (ix == 0x7ff00000 && lx != 0) || iy > 0x7ff00000 || (iy == 0x7ff00000 && ly != 0))
+		return x + y;
+
+	/* Determine if y is an odd integer when x < 0:
+	** yisint = 0 if y is not an integer, 1 if y is an odd integer, and 2 if y is an even integer. */
+	yisint = 0;
+	if (hx < 0) {
+		if (iy >= 0x43400000) {
+			yisint = 2;
+		} else if (iy >= 0x3ff00000) {
+			k = (iy >> 20) - 0x3ff;
+			if (k > 20) {
+				uint32_t jj = ly >> (52 - k);
+				if ((jj << (52 - k)) == ly)
+					yisint = 2 - (int32_t)(jj & 1);
+			} else if (ly == 0) {
+				uint32_t jj = (uint32_t)iy >> (20 - k);
+				if ((jj << (20 - k)) == (uint32_t)iy)
+					yisint = 2 - (int32_t)(jj & 1);
+			}
+		}
+	}
+
+	/* Special values of y. */
+	if (ly == 0) {
+		if (iy == 0x7ff00000) { /* y is +-inf */
+			if (((ix - 0x3ff00000) | (int32_t)lx) == 0)
+				return 1.0; /* (-1)**+-inf is 1. */
+			else if (ix >= 0x3ff00000)
+				return hy >= 0 ? y : 0.0; /* (|x|>1)**+-inf = inf,0 */
+			else
+				return hy >= 0 ? 0.0 : -y; /* (|x|<1)**+-inf = 0,inf */
+		}
+		if (iy == 0x3ff00000) /* y is +-1 */
+			return hy >= 0 ? x : 1.0 / x;
+		if (hy == 0x40000000) /* y is 2 */
+			return x * x;
+		if (hy == 0x3fe00000 && hx >= 0) /* y is 0.5 and x >= +0 */
+			return sqrt(x);
+	}
+
+	ax = fabs(x);
+	/* Special values of x. */
+	if (lx == 0 && (ix == 0x7ff00000 || ix == 0 || ix == 0x3ff00000)) { /* x is +-0, +-inf or +-1 */
+		z = ax;
+		if (hy < 0)
+			z = 1.0 / z; /* z = (1/|x|) */
+		if (hx < 0) {
+			if (((ix - 0x3ff00000) | yisint) == 0)
+				z = (z - z) / (z - z); /* (-1)**non-int is NaN. */
+			else if (yisint == 1)
+				z = -z; /* (x<0)**odd = -(|x|**odd) */
+		}
+		return z;
+	}
+
+	s = 1.0; /* Sign of the result. */
+	if (hx < 0) {
+		if (yisint == 0) /* (x<0)**(non-int) is NaN. */
+			return (x - x) / (x - x);
+		if (yisint == 1) /* (x<0)**(odd int) */
+			s = -1.0;
+	}
+
+	if (iy > 0x41e00000) { /* |y| > 2**31 */
+		if (iy > 0x43f00000) { /* |y| > 2**64, must overflow or underflow */
+			if (ix <= 0x3fefffff)
+				return hy < 0 ? huge * huge : tiny * tiny;
+			if (ix >= 0x3ff00000)
+				return hy > 0 ? huge * huge : tiny * tiny;
+		}
+		/* Overflow or underflow if x is not close to one. */
+		if (ix < 0x3fefffff)
+			return hy < 0 ? s * huge * huge : s * tiny * tiny;
+		if (ix > 0x3ff00000)
+			return hy > 0 ? s * huge * huge : s * tiny * tiny;
+		/* Now |1-x| is tiny <= 2**-20, which suffices to compute log(x) by x-x^2/2+x^3/3-x^4/4. */
+		t = ax - 1.0; /* t has 20 trailing zeros. */
+		w = (t * t) * (0.5 - t * (0.3333333333333333333333 - t * 0.25));
+		u = ivln2_h * t; /* ivln2_h has 21 significant bits. */
+		v = t * ivln2_l - w * ivln2;
+		t1 = with_low_word(u + v, 0);
+		t2 = v - (t1 - u);
+	} else {
+		double ss, s2, s_h, s_l, t_h, t_l;
+		n = 0;
+		/* Take care of subnormal numbers. */
+		if (ix < 0x00100000) {
+			ax *= two53;
+			n -= 53;
+			ix = (int32_t)high_word(ax);
+		}
+		n += (ix >> 20) - 0x3ff;
+		j = ix & 0x000fffff;
+		/* Determine the interval. */
+		ix = j | 0x3ff00000; /* Normalize ix. */
+		if (j <= 0x3988E) { /* |x|<sqrt(3/2) */
+			k = 0;
+		} else if (j < 0xBB67A) { /* |x|<sqrt(3) */
+			k = 1;
+		} else {
+			k = 0;
+			n += 1;
+			ix -= 0x00100000;
+		}
+		ax = with_high_word(ax, (uint32_t)ix);
+
+		/* Compute ss = s_h+s_l = (x-1)/(x+1) or (x-1.5)/(x+1.5). */
+		u = ax - bp[k];
+		v = 1.0 / (ax + bp[k]);
+		ss = u * v;
+		s_h = with_low_word(ss, 0);
+		/* t_h=ax+bp[k] High */
+		t_h = with_high_word(0.0, (uint32_t)(((ix >> 1) | 0x20000000) + 0x00080000 + (k << 18)));
+		t_l = ax - (t_h - bp[k]);
+		s_l = v * ((u - s_h * t_h) - s_h * t_l);
+		/* Compute log(ax). */
+		s2 = ss * ss;
+		r = s2 * s2 * (L1 + s2 * (L2 + s2 * (L3 + s2 * (L4 + s2 * (L5 + s2 * L6)))));
+		r += s_l * (s_h + ss);
+		s2 = s_h * s_h;
+		t_h = with_low_word(3.0 + s2 + r, 0);
+		t_l = r - ((t_h - 3.0) - s2);
+		/* u+v = ss*(1+...) */
+		u = s_h * t_h;
+		v = s_l * t_h + t_l * ss;
+		/* 2/(3log2)*(ss+...) */
+		p_h = with_low_word(u + v, 0);
+		p_l = v - (p_h - u);
+		z_h = cp_h * p_h; /* cp_h+cp_l = 2/(3*log2) */
+		z_l = cp_l * p_h + p_l * cp + dp_l[k];
+		/* log2(ax) = (ss+..)*2/(3*log2) = n + dp_h + z_h + z_l */
+		t = (double)n;
+		t1 = with_low_word(((z_h + z_l) + dp_h[k]) + t, 0);
+		t2 = z_l - (((t1 - t) - dp_h[k]) - z_h);
+	}
+
+	/* Split up y into y1+y2 and compute (y1+y2)*(t1+t2). */
+	y1 = with_low_word(y, 0);
+	p_l = (y - y1) * t1 + y * t2;
+	p_h = y1 * t1;
+	z = p_l + p_h;
+	j = (int32_t)high_word(z);
+	i = (int32_t)low_word(z);
+	if (j >= 0x40900000) { /* z >= 1024 */
+		if (((j - 0x40900000) | i) != 0) /* z > 1024 */
+			return s * huge * huge;
+		if (p_l + ovt > z - p_h)
+			return s * huge * huge;
+	} else if ((j & 0x7fffffff) >= 0x4090cc00) { /* z <= -1075 */
+		if ((((uint32_t)j - 0xc090cc00) | (uint32_t)i) != 0) /* z < -1075 */
+			return s * tiny * tiny;
+		if (p_l <= z - p_h)
+			return s * tiny * tiny;
+	}
+
+	/* Compute 2**(p_h+p_l). */
+	i = j & 0x7fffffff;
+	k = (i >> 20) - 0x3ff;
+	n = 0;
+	if (i > 0x3fe00000) { /* |z| > 0.5, set n = [z+0.5] */
+		n = j + (0x00100000 >> (k + 1));
+		k = ((n & 0x7fffffff) >> 20) - 0x3ff; /* New k for n. */
+		t = with_high_word(0.0, (uint32_t)(n & ~(0x000fffff >> k)));
+		n = ((n & 0x000fffff) | 0x00100000) >> (20 - k);
+		if (j < 0)
+			n = -n;
+		p_h -= t;
+	}
+	t = with_low_word(p_l + p_h, 0);
+	u = t * lg2_h;
+	v = (p_l - (t - p_h)) * lg2 + t * lg2_l;
+	z = u + v;
+	w = v - (z - u);
+	t = z * z;
+	t1 = z - t * (P1 + t * (P2 + t * (P3 + t * (P4 + t * P5))));
+	r = (z * t1) / (t1 - 2.0) - (w + z * w);
+	z = 1.0 - (r - z);
+	j = (int32_t)((uint32_t)high_word(z) + ((uint32_t)n << 20));
+	if ((j >> 20) <= 0) /* Subnormal output. */
+		z = ldexp(z, n);
+	else
+		z = with_high_word(z, (uint32_t)j);
+	return s * z;
+}
+
+static const double
+pio2_hi = 1.57079632679489655800e+00, /* 0x3FF921FB, 0x54442D18 */
+pio2_lo = 6.12323399573676603587e-17; /* 0x3C91A626, 0x33145C07 */
+
+/* Bits of pi/2 times 2^1200, from the least significant limb. */
+static const uint32_t pio2_bits[] = {
+	0xe6ac410a, 0x5b2c38c5, 0xba47b947, 0x9ebf06ca, 0x7f51fa49, 0xa734d22c,
+	0x906c38ab, 0x7e0b31b4, 0xf97142c7, 0xb67b8400, 0xcca3d9c8, 0x3fcc9250,
+	0x4822f896, 0x3f4b5d3e, 0xd7f6b513, 0x6fdbdc70, 0xb96de80d, 0xdad617fe,
+	0x85d34c6f, 0xfd8de898, 0x6aecc4bc, 0x848bc90b, 0x6adadaa3, 0x286e9fc2,
+	0x14dbe4be, 0x86366056, 0x33679a74, 0x09bbdf2a, 0x10f31c68, 0x3644a294,
+	0x7d4c7627, 0x98e80417, 0x9c1114cf, 0x39a25204, 0xc51701b8, 0x8469898c,
+	0xb54442d1, 0x0001921f,
+};
+#define PIO2_SCALE 1200
+
+/* Reduce the finite, huge x to r = x - n*pi/2 in [-pi/4, pi/4] exactly, and return n mod 4.
+** r is returned as y[0] + y[1]. */
+static int rem_pio2_large(double x, double *y) {
+	uint64_t bits = asuint64(x);
+	int e2 = (int)(bits >> 52 & 0x7ff) - 1075;
+	uint64_t mant = (bits & (((uint64_t)1 << 52) - 1)) | ((uint64_t)1 << 52);
+
+	lunka_big n, p;
+	lunka_big_set(&n, mant);
+	lunka_big_shl(&n, e2 + PIO2_SCALE);
+	p.n = (int)(sizeof(pio2_bits) / sizeof(pio2_bits[0]));
+	for (int i = 0; i < p.n; i++)
+		p.d[i] = pio2_bits[i];
+
+	/* Long division, keeping only the low bits of the quotient. */
+	int q = 0, shift = lunka_big_bits(&n) - lunka_big_bits(&p);
+	if (shift < 0)
+		shift = 0;
+	lunka_big t = p;
+	lunka_big_shl(&t, shift);
+	for (int i = shift; i >= 0; i--) {
+		if (lunka_big_cmp(&n, &t) >= 0) {
+			lunka_big_sub(&n, &t);
+			if (i < 2)
+				q |= 1 << i;
+		}
+		lunka_big_shr1(&t);
+	}
+
+	/* Take the nearest multiple if the remainder is more than half of pi/2. */
+	lunka_big twice = n;
+	lunka_big_shl(&twice, 1);
+	int negative = 0;
+	if (lunka_big_cmp(&twice, &p) > 0) {
+		t = p;
+		lunka_big_sub(&t, &n);
+		n = t;
+		q++;
+		negative = 1;
+	}
+
+	int sticky = 0;
+	int rshift = lunka_big_bits(&n) - 64;
+	if (rshift < 0)
+		rshift = 0;
+	uint64_t top = lunka_big_extract(&n, rshift, &sticky);
+	double hi = ldexp((double)(top >> 11), rshift + 11 - PIO2_SCALE);
+	double lo = ldexp((double)(top & 0x7ff), rshift - PIO2_SCALE);
+	y[0] = hi + lo;
+	y[1] = lo - (y[0] - hi);
+	if (negative) {
+		y[0] = -y[0];
+		y[1] = -y[1];
+	}
+	if (bits >> 63) {
+		y[0] = -y[0];
+		y[1] = -y[1];
+		q = -q;
+	}
+	return q & 3;
+}
+
+/* Reduce x to r = x - n*pi/2 in about [-pi/4, pi/4], returning n mod 4, with r as y[0] + y[1]. */
+static int rem_pio2(double x, double *y) {
+	static const double
+	toint_1_5 = 6755399441055744.0, /* 1.5 * 2^52 */
+	pio4 = 0x1.921fb6p-1,
+	invpio2 = 6.36619772367581382433e-01, /* 0x3FE45F30, 0x6DC9C883 */
+	pio2_1 = 1.57079632673412561417e+00, /* 0x3FF921FB, 0x54400000 */
+	pio2_1t = 6.07710050650619224932e-11, /* 0x3DD0B461, 0x1A626331 */
+	pio2_2 = 6.07710050630396597660e-11, /* 0x3DD0B461, 0x1A600000 */
+	pio2_2t = 2.02226624879595063154e-21, /* 0x3BA3198A, 0x2E037073 */
+	pio2_3 = 2.02226624871116645580e-21, /* 0x3BA3198A, 0x2E000000 */
+	pio2_3t = 8.47842766036889956997e-32; /* 0x397B839A, 0x252049C1 */
+
+	uint32_t ix = high_word(x) & 0x7fffffff;
+	if (ix >= 0x413921fb) /* |x| ~>= 2^20*(pi/2) */
+		return rem_pio2_large(x, y);
+
+	double fn = x * invpio2 + toint_1_5 - toint_1_5;
+	int32_t n = (int32_t)fn;
+	double r = x - fn * pio2_1;
+	double w = fn * pio2_1t; /* First round, good to 85 bits. */
+	/* Correct a rounding error in fn, which makes r slightly outside of [-pi/4, pi/4]. */
+	if (r - w < -pio4) {
+		n--;
+		fn--;
+		r = x - fn * pio2_1;
+		w = fn * pio2_1t;
+	} else if (r - w > pio4) {
+		n++;
+		fn++;
+		r = x - fn * pio2_1;
+		w = fn * pio2_1t;
+	}
+	y[0] = r - w;
+	int ex = (int)(ix >> 20), ey = (int)(high_word(y[0]) >> 20 & 0x7ff);
+	if (ex - ey > 16) { /* Second round, good to 118 bits. */
+		double t = r;
+		w = fn * pio2_2;
+		r = t - w;
+		w = fn * pio2_2t - ((t - r) - w);
+		y[0] = r - w;
+		ey = (int)(high_word(y[0]) >> 20 & 0x7ff);
+		if (ex - ey > 49) { /* Third round, good to 151 bits, which covers all cases. */
+			t = r;
+			w = fn * pio2_3;
+			r = t - w;
+			w = fn * pio2_3t - ((t - r) - w);
+			y[0] = r - w;
+		}
+	}
+	y[1] = (r - y[0]) - w;
+	return n & 3;
+}
+
+/* sin(x + y) for |x| ~<= pi/4, where y is the tail of x if iy is set. */
+static double kernel_sin(double x, double y, int iy) {
+	static const double
+	S1 = -1.66666666666666324348e-01, /* 0xBFC55555, 0x55555549 */
+	S2 = 8.33333333332248946124e-03, /* 0x3F811111, 0x1110F8A6 */
+	S3 = -1.98412698298579493134e-04, /* 0xBF2A01A0, 0x19C161D5 */
+	S4 = 2.75573137070700676789e-06, /* 0x3EC71DE3, 0x57B1FE7D */
+	S5 = -2.50507602534068634195e-08, /* 0xBE5AE5E6, 0x8A2B9CEB */
+	S6 = 1.58969099521155010221e-10; /* 0x3DE5D93A, 0x5ACFD57C */
+	double z = x * x, w = z * z;
+	double r = S2 + z * (S3 + z * S4) + z * w * (S5 + z * S6);
+	double v = z * x;
+	if (iy == 0)
+		return x + v * (S1 + z * r);
+	return x - ((z * (0.5 * y - v * r) - y) - v * S1);
+}
+
+/* cos(x + y) for |x| ~<= pi/4, where y is the tail of x. */
+static double kernel_cos(double x, double y) {
+	static const double
+	C1 = 4.16666666666666019037e-02, /* 0x3FA55555, 0x5555554C */
+	C2 = -1.38888888888741095749e-03, /* 0xBF56C16C, 0x16C15177 */
+	C3 = 2.48015872894767294178e-05, /* 0x3EFA01A0, 0x19CB1590 */
+	C4 = -2.75573143513906633035e-07, /* 0xBE927E4F, 0x809C52AD */
+	C5 = 2.08757232129817482790e-09, /* 0x3E21EE9E, 0xBDB4B1C4 */
+	C6 = -1.13596475577881948265e-11; /* 0xBDA8FAE9, 0xBE8838D4 */
+	double z = x * x, w = z * z;
+	double r = z * (C1 + z * (C2 + z * C3)) + w * w * (C4 + z * (C5 + z * C6));
+	double hz = 0.5 * z;
+	w = 1.0 - hz;
+	return w + (((1.0 - w) - hz) + (z * r - x * y));
+}
+
+/* tan(x + y), or -1/tan(x + y) if odd is set, for |x| ~<= pi/4, where y is the tail of x. */
+static double kernel_tan(double x, double y, int odd) {
+	static const double T[] = {
+		3.33333333333334091986e-01, /* 3FD55555, 55555563 */
+		1.33333333333201242699e-01, /* 3FC11111, 1110FE7A */
+		5.39682539762260521377e-02, /* 3FABA1BA, 1BB341FE */
+		2.18694882948595424599e-02, /* 3F9664F4, 8406D637 */
+		8.86323982359930005737e-03, /* 3F8226E3, E96E8493 */
+		3.59207910759131235356e-03, /* 3F6D6D22, C9560328 */
+		1.45620945432529025516e-03, /* 3F57DBC8, FEE08315 */
+		5.88041240820264096874e-04, /* 3F4344D8, F2F26501 */
+		2.46463134818469906812e-04, /* 3F3026F7, 1A8D1068 */
+		7.81794442939557092300e-05, /* 3F147E88, A03792A6 */
+		7.14072491382608190305e-05, /* 3F12B80F, 32F0A7E9 */
+		-1.85586374855275456654e-05, /* BEF375CB, DB605373 */
+		2.59073051863633712884e-05, /* 3EFB2A70, 74BF7AD4 */
+	};
+	static const double
+	pio4 = 7.85398163397448278999e-01, /* 3FE921FB, 54442D18 */
+	pio4lo = 3.06161699786838301793e-17; /* 3C81A626, 33145C07 */
+	double z, r, v, w, s, a, w0, a0;
+	uint32_t hx = high_word(x);
+	int big = (hx & 0x7fffffff) >= 0x3FE59428; /* |x| >= 0.6744 */
+	int sign = 0;
+	if (big) {
+		sign = (int)(hx >> 31);
+		if (sign) {
+			x = -x;
+			y = -y;
+		}
+		x = (pio4 - x) + (pio4lo - y);
+		y = 0.0;
+	}
+	z = x * x;
+	w = z * z;
+	r = T[1] + w * (T[3] + w * (T[5] + w * (T[7] + w * (T[9] + w * T[11]))));
+	v = z * (T[2] + w * (T[4] + w * (T[6] + w * (T[8] + w * (T[10] + w * T[12])))));
+	s = z * x;
+	r = y + z * (s * (r + v) + y) + s * T[0];
+	w = x + r;
+	if (big) {
+		s = 1 - 2 * odd;
+		v = s - 2.0 * (x + (r - w * w / (w + s)));
+		return sign ? -v : v;
+	}
+	if (!odd)
+		return w;
+	/* -1.0/(x+r) has up to 2ulp error, so compute it accurately. */
+	w0 = with_low_word(w, 0);
+	v = r - (w0 - x); /* w0+v = r+x */
+	a = -1.0 / w;
+	a0 = with_low_word(a, 0);
+	return a0 + a * (1.0 + a0 * w0 + a0 * v);
+}
+
+double sin(double x) {
+	double y[2];
+	uint32_t ix = high_word(x) & 0x7fffffff;
+	if (ix <= 0x3fe921fb) { /* |x| ~< pi/4 */
+		if (ix < 0x3e500000) /* |x| < 2**-26 */
+			return x;
+		return kernel_sin(x, 0.0, 0);
+	}
+	if (ix >= 0x7ff00000) /* sin(Inf or NaN) is NaN. */
+		return x - x;
+	switch (rem_pio2(x, y)) {
+		case 0: return kernel_sin(y[0], y[1], 1);
+		case 1: return kernel_cos(y[0], y[1]);
+		case 2: return -kernel_sin(y[0], y[1], 1);
+		default: return -kernel_cos(y[0], y[1]);
+	}
+}
+
+double cos(double x) {
+	double y[2];
+	uint32_t ix = high_word(x) & 0x7fffffff;
+	if (ix <= 0x3fe921fb) { /* |x| ~< pi/4 */
+		if (ix < 0x3e46a09e) /* |x| < 2**-27 * sqrt(2) */
+			return 1.0;
+		return kernel_cos(x, 0);
+	}
+	if (ix >= 0x7ff00000) /* cos(Inf or NaN) is NaN. */
+		return x - x;
+	switch (rem_pio2(x, y)) {
+		case 0: return kernel_cos(y[0], y[1]);
+		case 1: return -kernel_sin(y[0], y[1], 1);
+		case 2: return -kernel_cos(y[0], y[1]);
+		default: return kernel_sin(y[0], y[1], 1);
+	}
+}
+
+double tan(double x) {
+	double y[2];
+	uint32_t ix = high_word(x) & 0x7fffffff;
+	if (ix <= 0x3fe921fb) { /* |x| ~< pi/4 */
+		if (ix < 0x3e400000) /* |x| < 2**-27 */
+			return x;
+		return kernel_tan(x, 0.0, 0);
+	}
+	if (ix >= 0x7ff00000) /* tan(Inf or NaN) is NaN. */
+		return x - x;
+	int n = rem_pio2(x, y);
+	return kernel_tan(y[0], y[1], n & 1);
+}
+
+static double asin_r(double z) {
+	static const double
+	pS0 = 1.66666666666666657415e-01, /* 0x3FC55555, 0x55555555 */
+	pS1 = -3.25565818622400915405e-01, /* 0xBFD4D612, 0x03EB6F7D */
+	pS2 = 2.01212532134862925881e-01, /* 0x3FC9C155, 0x0E884455 */
+	pS3 = -4.00555345006794114027e-02, /* 0xBFA48228, 0xB5688F3B */
+	pS4 = 7.91534994289814532176e-04, /* 0x3F49EFE0, 0x7501B288 */
+	pS5 = 3.47933107596021167570e-05, /* 0x3F023DE1, 0x0DFDF709 */
+	qS1 = -2.40339491173441421878e+00, /* 0xC0033A27, 0x1C8A2D4B */
+	qS2 = 2.02094576023350569471e+00, /* 0x40002AE5, 0x9C598AC8 */
+	qS3 = -6.88283971605453293030e-01, /* 0xBFE6066C, 0x1B8D0159 */
+	qS4 = 7.70381505559019352791e-02; /* 0x3FB3B8C5, 0xB12E9282 */
+	double p = z * (pS0 + z * (pS1 + z * (pS2 + z * (pS3 + z * (pS4 + z * pS5)))));
+	double q = 1.0 + z * (qS1 + z * (qS2 + z * (qS3 + z * qS4)));
+	return p / q;
+}
+
+double asin(double x) {
+	double z, r, s;
+	uint32_t hx = high_word(x), ix = hx & 0x7fffffff;
+	if (ix >= 0x3ff00000) { /* |x| >= 1 or NaN */
+		if (((ix - 0x3ff00000) | low_word(x)) == 0)
+			return x * pio2_hi; /* asin(1) = +-pi/2 */
+		return 0 / (x - x);
+	}
+	if (ix < 0x3fe00000) { /* |x| < 0.5 */
+		if (ix < 0x3e500000 && ix >= 0x00100000)
+			return x;
+		return x + x * asin_r(x * x);
+	}
+	/* 1 > |x| >= 0.5 */
+	z = (1 - fabs(x)) * 0.5;
+	s = sqrt(z);
+	r = asin_r(z);
+	if (ix >= 0x3fef3333) { /* |x| > 0.975 */
+		x = pio2_hi - (2 * (s + s * r) - pio2_lo);
+	} else {
+		/* f+c = sqrt(z) */
+		double f = with_low_word(s, 0);
+		double c = (z - f * f) / (s + f);
+		x = 0.5 * pio2_hi - (2 * s * r - (pio2_lo - 2 * c) - (0.5 * pio2_hi - 2 * f));
+	}
+	return hx >> 31 ? -x : x;
+}
+
+double acos(double x) {
+	double z, w, s, c, df;
+	uint32_t hx = high_word(x), ix = hx & 0x7fffffff;
+	if (ix >= 0x3ff00000) { /* |x| >= 1 or NaN */
+		if (((ix - 0x3ff00000) | low_word(x)) == 0)
+			return hx >> 31 ? 2 * pio2_hi : 0; /* acos(1) = 0, acos(-1) = pi */
+		return 0 / (x - x);
+	}
+	if (ix < 0x3fe00000) { /* |x| < 0.5 */
+		if (ix <= 0x3c600000) /* |x| < 2**-57 */
+			return pio2_hi;
+		return pio2_hi - (x - (pio2_lo - x * asin_r(x * x)));
+	}
+	if (hx >> 31) { /* x < -0.5 */
+		z = (1.0 + x) * 0.5;
+		s = sqrt(z);
+		w = asin_r(z) * s - pio2_lo;
+		return 2 * (pio2_hi - (s + w));
+	}
+	/* x > 0.5 */
+	z = (1.0 - x) * 0.5;
+	s = sqrt(z);
+	df = with_low_word(s, 0);
+	c = (z - df * df) / (s + df);
+	w = asin_r(z) * s + c;
+	return 2 * (df + w);
+}
+
+double atan(double x) {
+	static const double atanhi[] = {
+		4.63647609000806093515e-01, /* atan(0.5)hi 0x3FDDAC67, 0x0561BB4F */
+		7.85398163397448278999e-01, /* atan(1.0)hi 0x3FE921FB, 0x54442D18 */
+		9.82793723247329054082e-01, /* atan(1.5)hi 0x3FEF730B, 0xD281F69B */
+		1.57079632679489655800e+00, /* atan(inf)hi 0x3FF921FB, 0x54442D18 */
+	};
+	static const double atanlo[] = {
+		2.26987774529616870924e-17, /* atan(0.5)lo 0x3C7A2B7F, 0x222F65E2 */
+		3.06161699786838301793e-17, /* atan(1.0)lo 0x3C81A626, 0x33145C07 */
+		1.39033110312309984516e-17, /* atan(1.5)lo 0x3C700788, 0x7AF0CBBD */
+		6.12323399573676603587e-17, /* atan(inf)lo 0x3C91A626, 0x33145C07 */
+	};
+	static const double aT[] = {
+		3.33333333333329318027e-01, /* 0x3FD55555, 0x5555550D */
+		-1.99999999998764832476e-01, /* 0xBFC99999, 0x9998EBC4 */
+		1.42857142725034663711e-01, /* 0x3FC24924, 0x920083FF */
+		-1.11111104054623557880e-01, /* 0xBFBC71C6, 0xFE231671 */
+		9.09088713343650656196e-02, /* 0x3FB745CD, 0xC54C206E */
+		-7.69187620504482999495e-02, /* 0xBFB3B0F2, 0xAF749A6D */
+		6.66107313738753120669e-02, /* 0x3FB10D66, 0xA0D03D51 */
+		-5.83357013379057348645e-02, /* 0xBFADDE2D, 0x52DEFD9A */
+		4.97687799461593236017e-02, /* 0x3FA97B4B, 0x24760DEB */
+		-3.65315727442169155270e-02, /* 0xBFA2B444, 0x2C6A6C2F */
+		1.62858201153657823623e-02, /* 0x3F90AD3A, 0xE322DA11 */
+	};
+	double w, s1, s2, z;
+	uint32_t ix = high_word(x), sign = ix >> 31;
+	int id;
+	ix &= 0x7fffffff;
+	if (ix >= 0x44100000) { /* |x| >= 2^66 */
+		if (isnan(x))
+			return x;
+		return sign ? -atanhi[3] : atanhi[3];
+	}
+	if (ix < 0x3fdc0000) { /* |x| < 0.4375 */
+		if (ix < 0x3e400000) /* |x| < 2^-27 */
+			return x;
+		id = -1;
+	} else {
+		x = fabs(x);
+		if (ix < 0x3ff30000) { /* |x| < 1.1875 */
+			if (ix < 0x3fe60000) { /* 7/16 <= |x| < 11/16 */
+				id = 0;
+				x = (2.0 * x - 1.0) / (2.0 + x);
+			} else { /* 11/16 <= |x| < 19/16 */
+				id = 1;
+				x = (x - 1.0) / (x + 1.0);
+			}
+		} else if (ix < 0x40038000) { /* |x| < 2.4375 */
+			id = 2;
+			x = (x - 1.5) / (1.0 + 1.5 * x);
+		} else { /* 2.4375 <= |x| < 2^66 */
+			id = 3;
+			x = -1.0 / x;
+		}
+	}
+	z = x * x;
+	w = z * z;
+	/* Break the sum from i=0 to 10 of aT[i]z**(i+1) into odd and even polynomials. */
+	s1 = z * (aT[0] + w * (aT[2] + w * (aT[4] + w * (aT[6] + w * (aT[8] + w * aT[10])))));
+	s2 = w * (aT[1] + w * (aT[3] + w * (aT[5] + w * (aT[7] + w * aT[9]))));
+	if (id < 0)
+		return x - x * (s1 + s2);
+	z = atanhi[id] - (x * (s1 + s2) - atanlo[id] - x);
+	return sign ? -z : z;
+}
+
+double atan2(double y, double x) {
+	static const double
+	pi = 3.1415926535897931160E+00, /* 0x400921FB, 0x54442D18 */
+	pi_lo = 1.2246467991473531772E-16; /* 0x3CA1A626, 0x33145C07 */
+	double z;
+	uint32_t m, lx, ly, ix, iy;
+
+	if (isnan(x) || isnan(y))
+		return x + y;
+	ix = high_word(x);
+	lx = low_word(x);
+	iy = high_word(y);
+	ly = low_word(y);
+	if (((ix - 0x3ff00000) | lx) == 0) /* x = 1.0 */
+		return atan(y);
+	m = ((iy >> 31) & 1) | ((ix >> 30) & 2); /* 2*sign(x)+sign(y) */
+	ix &= 0x7fffffff;
+	iy &= 0x7fffffff;
+
+	if ((iy | ly) == 0) { /* y = 0 */
+		switch (m) {
+			case 0: case 1: return y; /* atan(+-0,+anything)=+-0 */
+			case 2: return pi; /* atan(+0,-anything) = pi */
+			default: return -pi; /* atan(-0,-anything) =-pi */
+		}
+	}
+	if ((ix | lx) == 0) /* x = 0 */
+		return m & 1 ? -pio2_hi : pio2_hi;
+	if (ix == 0x7ff00000) { /* x is INF */
+		if (iy == 0x7ff00000) {
+			switch (m) {
+				case 0: return pi / 4; /* atan(+INF,+INF) */
+				case 1: return -pi / 4; /* atan(-INF,+INF) */
+				case 2: return 3 * pi / 4; /* atan(+INF,-INF) */
+				default: return -3 * pi / 4; /* atan(-INF,-INF) */
+			}
+		} else {
+			switch (m) {
+				case 0: return 0.0; /* atan(+...,+INF) */
+				case 1: return -0.0; /* atan(-...,+INF) */
+				case 2: return pi; /* atan(+...,-INF) */
+				default: return -pi; /* atan(-...,-INF) */
+			}
+		}
+	}
+	/* |y/x| > 0x1p64 */
+	if (ix + (64 << 20) < iy || iy == 0x7ff00000)
+		return m & 1 ? -pio2_hi : pio2_hi;
+
+	/* z = atan(|y/x|) without spurious underflow. */
+	if ((m & 2) && iy + (64 << 20) < ix) /* |y/x| < 0x1p-64, x<0 */
+		z = 0;
+	else
+		z = atan(fabs(y / x));
+	switch (m) {
+		case 0: return z; /* atan(+,+) */
+		case 1: return -z; /* atan(-,+) */
+		case 2: return pi - (z - pi_lo); /* atan(+,-) */
+		default: return (z - pi_lo) - pi; /* atan(-,-) */
+	}
+}
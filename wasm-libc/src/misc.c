@@ -0,0 +1,61 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Process control, errors, locales, time and non-local jumps. */
+#include <errno.h>
+#include <locale.h>
+#include <setjmp.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+
+int errno;
+
+_Noreturn void abort(void) {
+	__builtin_trap();
+}
+
+_Noreturn void exit(int status) {
+	(void)status;
+	__builtin_trap();
+}
+
+char *getenv(const char *name) {
+	(void)name;
+	return NULL;
+}
+
+int abs(int x) {
+	return x < 0 ? -x : x;
+}
+
+char *setlocale(int category, const char *locale) {
+	(void)category;
+	if (locale == NULL || locale[0] == '\0' || strcmp(locale, "C") == 0)
+		return "C";
+	return NULL;
+}
+
+struct lconv *localeconv(void) {
+	static struct lconv c_locale = { ".", "", "" };
+	return &c_locale;
+}
+
+time_t time(time_t *t) {
+	if (t != NULL)
+		*t = (time_t)-1;
+	return (time_t)-1;
+}
+
+clock_t clock(void) {
+	return (clock_t)-1;
+}
+
+int setjmp(jmp_buf env) {
+	(void)env;
+	return 0;
+}
+
+_Noreturn void longjmp(jmp_buf env, int val) {
+	(void)env;
+	(void)val;
+	__builtin_trap();
+}
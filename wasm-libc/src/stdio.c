@@ -0,0 +1,605 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** Standard streams and formatted output. */
+#include <errno.h>
+#include <stdarg.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "internal.h"
+
+struct lunka_file {
+	int fd;
+	int eof;
+	int error;
+};
+
+static FILE streams[3] = { { 0, 0, 0 }, { 1, 0, 0 }, { 2, 0, 0 } };
+FILE *stdin = &streams[0];
+FILE *stdout = &streams[1];
+FILE *stderr = &streams[2];
+
+__attribute__((weak)) void lunka_libc_write(int fd, const char *s, size_t n) {
+	(void)fd;
+	(void)s;
+	(void)n;
+}
+
+FILE *fopen(const char *path, const char *mode) {
+	(void)path;
+	(void)mode;
+	errno = ENOENT;
+	return NULL;
+}
+
+FILE *freopen(const char *path, const char *mode, FILE *f) {
+	(void)path;
+	(void)mode;
+	(void)f;
+	errno = ENOENT;
+	return NULL;
+}
+
+int fclose(FILE *f) {
+	(void)f;
+	return 0;
+}
+
+int fflush(FILE *f) {
+	(void)f;
+	return 0;
+}
+
+int getc(FILE *f) {
+	f->eof = 1;
+	return EOF;
+}
+
+int fgetc(FILE *f) {
+	return getc(f);
+}
+
+int ungetc(int c, FILE *f) {
+	(void)c;
+	(void)f;
+	return EOF;
+}
+
+char *fgets(char *s, int n, FILE *f) {
+	(void)s;
+	(void)n;
+	f->eof = 1;
+	return NULL;
+}
+
+size_t fread(void *p, size_t size, size_t n, FILE *f) {
+	(void)p;
+	(void)size;
+	(void)n;
+	f->eof = 1;
+	return 0;
+}
+
+size_t fwrite(const void *p, size_t size, size_t n, FILE *f) {
+	if (f->fd == 0) {
+		f->error = 1;
+		errno = EBADF;
+		return 0;
+	}
+	if (size != 0 && n != 0)
+		lunka_libc_write(f->fd, p, size * n);
+	return n;
+}
+
+int fputs(const char *s, FILE *f) {
+	size_t n = strlen(s);
+	return fwrite(s, 1, n, f) == n ? 0 : EOF;
+}
+
+int feof(FILE *f) {
+	return f->eof;
+}
+
+int ferror(FILE *f) {
+	return f->error;
+}
+
+void clearerr(FILE *f) {
+	f->eof = 0;
+	f->error = 0;
+}
+
+/* Destination of formatted output. */
+typedef struct sink {
+	void (*put)(struct sink *s, const char *p, size_t n);
+	size_t count;
+} sink;
+
+static void out(sink *s, const char *p, size_t n) {
+	s->put(s, p, n);
+	s->count += n;
+}
+
+static void pad(sink *s, char c, size_t n) {
+	char buf[16];
+	memset(buf, c, sizeof(buf));
+	for (; n > sizeof(buf); n -= sizeof(buf))
+		out(s, buf, sizeof(buf));
+	out(s, buf, n);
+}
+
+/* Conversion specification. */
+typedef struct spec {
+	int left, plus, space, alt, zero;
+	size_t width;
+	int precision;
+	char conv;
+} spec;
+
+/* Write the padding before a field of `len` characters that starts with `prefix`, and the prefix. */
+static void field_start(sink *s, const spec *sp, const char *prefix, size_t len) {
+	size_t total = strlen(prefix) + len;
+	size_t fill = sp->width > total ? sp->width - total : 0;
+	if (!sp->left && !sp->zero)
+		pad(s, ' ', fill);
+	out(s, prefix, strlen(prefix));
+	if (!sp->left && sp->zero)
+		pad(s, '0', fill);
+}
+
+/* Write the padding after a field of `len` characters that starts with `prefix`. */
+static void field_end(sink *s, const spec *sp, const char *prefix, size_t len) {
+	size_t total = strlen(prefix) + len;
+	if (sp->left && sp->width > total)
+		pad(s, ' ', sp->width - total);
+}
+
+static void format_text(sink *s, const spec *sp, const char *p, size_t n) {
+	spec plain = *sp;
+	plain.zero = 0;
+	field_start(s, &plain, "", n);
+	out(s, p, n);
+	field_end(s, &plain, "", n);
+}
+
+static void format_integer(sink *s, spec sp, uintmax_t v, int negative) {
+	char buf[3 * sizeof(uintmax_t)];
+	const char *digits = sp.conv == 'X' ? "0123456789ABCDEF" : "0123456789abcdef";
+	unsigned base = sp.conv == 'o' ? 8 : sp.conv == 'x' || sp.conv == 'X' || sp.conv == 'p' ? 16 : 10;
+	size_t n = sizeof(buf);
+	for (uintmax_t w = v; w != 0; w /= base)
+		buf[--n] = digits[w % base];
+	size_t len = sizeof(buf) - n;
+
+	const char *prefix = "";
+	if (negative)
+		prefix = "-";
+	else if (sp.conv == 'd' || sp.conv == 'i')
+		prefix = sp.plus ? "+" : sp.space ? " " : "";
+	else if (sp.conv == 'p' || (sp.alt && v != 0 && base == 16))
+		prefix = sp.conv == 'X' ? "0X" : "0x";
+
+	size_t zeros = 0;
+	if (sp.precision >= 0) {
+		sp.zero = 0;
+		if ((size_t)sp.precision > len)
+			zeros = (size_t)sp.precision - len;
+	} else if (v == 0) {
+		zeros = 1;
+	}
+	if (sp.alt && base == 8 && zeros == 0 && (len == 0 || buf[n] != '0'))
+		zeros = 1;
+
+	field_start(s, &sp, prefix, zeros + len);
+	pad(s, '0', zeros);
+	out(s, buf + n, len);
+	field_end(s, &sp, prefix, zeros + len);
+}
+
+/* Round the digits `d` of 0.DIGITS * 10^*point to the first `keep` of them, half to even. */
+static void round_digits(char *d, int *n, int *point, int keep) {
+	if (keep >= *n)
+		return;
+	if (keep < 0) {
+		*n = 0;
+		return;
+	}
+	char r = d[keep];
+	int up = r > '5' || (r == '5' && (keep + 1 < *n || (keep > 0 && ((d[keep - 1] - '0') & 1))));
+	*n = keep;
+	if (up) {
+		int i = keep - 1;
+		while (i >= 0 && d[i] == '9')
+			i--;
+		if (i < 0) {
+			d[0] = '1';
+			*n = 1;
+			(*point)++;
+		} else {
+			d[i]++;
+			*n = i + 1;
+		}
+	} else {
+		while (*n > 0 && d[*n - 1] == '0')
+			(*n)--;
+	}
+}
+
+static char digit_at(const char *d, int n, int i) {
+	return i >= 0 && i < n ? d[i] : '0';
+}
+
+/* Return the number of digits of the exponent `e` when it's written with at least `min_digits` of them. */
+static size_t exponent_len(int e, size_t min_digits) {
+	size_t len = 0;
+	for (int a = e < 0 ? -e : e; a != 0; a /= 10)
+		len++;
+	return len < min_digits ? min_digits : len;
+}
+
+static void format_exponent(sink *s, char marker, int e, int min_digits) {
+	char buf[8];
+	size_t n = sizeof(buf);
+	int a = e < 0 ? -e : e;
+	for (int i = 0; a != 0 || i < min_digits; i++, a /= 10)
+		buf[--n] = (char)('0' + a % 10);
+	buf[--n] = e < 0 ? '-' : '+';
+	buf[--n] = marker;
+	out(s, buf + n, sizeof(buf) - n);
+}
+
+/* Write `n` digits of 0.DIGITS * 10^point as fixed-point with `precision` digits after the point. */
+static void fixed_body(sink *s, const char *d, int n, int point, int precision, int alt) {
+	if (point <= 0) {
+		out(s, "0", 1);
+	} else {
+		for (int i = 0; i < point; i++) {
+			char c = digit_at(d, n, i);
+			out(s, &c, 1);
+		}
+	}
+	if (precision > 0 || alt)
+		out(s, ".", 1);
+	for (int i = 0; i < precision; i++) {
+		char c = digit_at(d, n, point + i);
+		out(s, &c, 1);
+	}
+}
+
+static size_t fixed_len(int point, int precision, int alt) {
+	return (size_t)(point > 0 ? point : 1) + (precision > 0 || alt ? 1 + (size_t)precision : 0);
+}
+
+static void format_hex_float(sink *s, spec sp, double x, const char *prefix0) {
+	uint64_t bits = lunka_libc_bits(x);
+	int exponent = (int)(bits >> 52 & 0x7ff);
+	uint64_t mant = bits & (((uint64_t)1 << 52) - 1);
+	int lead = exponent != 0, e = exponent == 0 ? (mant == 0 ? 0 : -1022) : exponent - 1023;
+	int upper = sp.conv == 'A';
+	const char *digits = upper ? "0123456789ABCDEF" : "0123456789abcdef";
+
+	int precision = sp.precision;
+	if (precision < 0) {
+		precision = 13;
+		while (precision > 0 && ((mant >> (4 * (13 - precision))) & 0xf) == 0)
+			precision--;
+	} else if (precision < 13) {
+		int shift = 4 * (13 - precision);
+		uint64_t rem = mant & (((uint64_t)1 << shift) - 1), half = (uint64_t)1 << (shift - 1);
+		mant >>= shift;
+		if (rem > half || (rem == half && ((precision == 0 ? (uint64_t)lead : mant) & 1)))
+			mant++;
+		if (mant >> (4 * precision)) {
+			mant = 0;
+			lead++;
+		}
+		mant <<= shift;
+	}
+
+	char prefix[4];
+	strcpy(prefix, prefix0);
+	strcat(prefix, upper ? "0X" : "0x");
+	size_t len = 1 + (precision > 0 || sp.alt ? 1 + (size_t)precision : 0) + 2 + exponent_len(e, 1);
+
+	field_start(s, &sp, prefix, len);
+	char c = (char)('0' + lead);
+	out(s, &c, 1);
+	if (precision > 0 || sp.alt)
+		out(s, ".", 1);
+	for (int i = 0; i < precision; i++) {
+		c = i < 13 ? digits[(mant >> (4 * (12 - i))) & 0xf] : '0';
+		out(s, &c, 1);
+	}
+	format_exponent(s, upper ? 'P' : 'p', e, 1);
+	field_end(s, &sp, prefix, len);
+}
+
+static void format_float(sink *s, spec sp, double x) {
+	int negative = (lunka_libc_bits(x) >> 63) != 0;
+	if (negative)
+		x = -x;
+	const char *prefix = negative ? "-" : sp.plus ? "+" : sp.space ? " " : "";
+	int upper = sp.conv == 'F' || sp.conv == 'E' || sp.conv == 'G' || sp.conv == 'A';
+
+	if (x != x || x - x != 0) {
+		const char *body = x != x ? (upper ? "NAN" : "nan") : (upper ? "INF" : "inf");
+		sp.zero = 0;
+		field_start(s, &sp, prefix, 3);
+		out(s, body, 3);
+		field_end(s, &sp, prefix, 3);
+		return;
+	}
+	char conv = (char)(sp.conv | 0x20);
+	if (conv == 'a') {
+		format_hex_float(s, sp, x, prefix);
+		return;
+	}
+
+	char d[LUNKA_DIGITS_MAX];
+	int n = 0, point = 1;
+	if (x != 0)
+		n = lunka_libc_digits(x, d, &point);
+	int precision = sp.precision < 0 ? 6 : sp.precision;
+	int exp_style = conv == 'e';
+
+	if (conv == 'g') {
+		int p = precision == 0 ? 1 : precision;
+		round_digits(d, &n, &point, p);
+		int e = n == 0 ? 0 : point - 1;
+		if (p > e && e >= -4) {
+			precision = p - 1 - e;
+			if (!sp.alt) {
+				int significant = n - point;
+				precision = significant < 0 ? 0 : significant < precision ? significant : precision;
+			}
+		} else {
+			exp_style = 1;
+			precision = p - 1;
+			if (!sp.alt && n - 1 < precision)
+				precision = n > 0 ? n - 1 : 0;
+		}
+	}
+
+	size_t len;
+	if (exp_style) {
+		if (conv != 'g')
+			round_digits(d, &n, &point, precision + 1);
+		int e = n == 0 ? 0 : point - 1;
+		len = 1 + (precision > 0 || sp.alt ? 1 + (size_t)precision : 0) + 2 + exponent_len(e, 2);
+		field_start(s, &sp, prefix, len);
+		char c = digit_at(d, n, 0);
+		out(s, &c, 1);
+		if (precision > 0 || sp.alt)
+			out(s, ".", 1);
+		for (int i = 1; i <= precision; i++) {
+			c = digit_at(d, n, i);
+			out(s, &c, 1);
+		}
+		format_exponent(s, upper ? 'E' : 'e', e, 2);
+	} else {
+		if (conv != 'g')
+			round_digits(d, &n, &point, point + precision);
+		if (n == 0)
+			point = 1;
+		len = fixed_len(point, precision, sp.alt);
+		field_start(s, &sp, prefix, len);
+		fixed_body(s, d, n, point, precision, sp.alt);
+	}
+	field_end(s, &sp, prefix, len);
+}
+
+static void format(sink *s, const char *f, va_list ap) {
+	while (*f != '\0') {
+		if (*f != '%') {
+			const char *start = f;
+			while (*f != '\0' && *f != '%')
+				f++;
+			out(s, start, (size_t)(f - start));
+			continue;
+		}
+		f++;
+
+		spec sp = { 0, 0, 0, 0, 0, 0, -1, 0 };
+		for (;; f++) {
+			if (*f == '-') sp.left = 1;
+			else if (*f == '+') sp.plus = 1;
+			else if (*f == ' ') sp.space = 1;
+			else if (*f == '#') sp.alt = 1;
+			else if (*f == '0') sp.zero = 1;
+			else break;
+		}
+		if (*f == '*') {
+			int w = va_arg(ap, int);
+			if (w < 0) {
+				sp.left = 1;
+				w = -w;
+			}
+			sp.width = (size_t)w;
+			f++;
+		} else {
+			for (; *f >= '0' && *f <= '9'; f++)
+				sp.width = sp.width * 10 + (size_t)(*f - '0');
+		}
+		if (*f == '.') {
+			f++;
+			sp.precision = 0;
+			if (*f == '*') {
+				sp.precision = va_arg(ap, int);
+				f++;
+			} else {
+				for (; *f >= '0' && *f <= '9'; f++)
+					sp.precision = sp.precision * 10 + (*f - '0');
+			}
+		}
+		if (sp.left)
+			sp.zero = 0;
+
+		/* Size of the argument: 'H' for char, 'h' for short, 'l' for long, 'q' for long long, 'j', 'z' and 't'.
+		** `long double` isn't supported, as converting it needs a runtime library on WebAssembly. */
+		char size = 0;
+		switch (*f) {
+			case 'h':
+				size = *++f == 'h' ? (f++, 'H') : 'h';
+				break;
+			case 'l':
+				size = *++f == 'l' ? (f++, 'q') : 'l';
+				break;
+			case 'j': case 'z': case 't':
+				size = *f++;
+				break;
+		}
+
+		sp.conv = *f;
+		if (*f != '\0')
+			f++;
+		switch (sp.conv) {
+			case 'd': case 'i': {
+				intmax_t v;
+				switch (size) {
+					case 'H': v = (signed char)va_arg(ap, int); break;
+					case 'h': v = (short)va_arg(ap, int); break;
+					case 'l': v = va_arg(ap, long); break;
+					case 'q': v = va_arg(ap, long long); break;
+					case 'j': v = va_arg(ap, intmax_t); break;
+					case 'z': case 't': v = va_arg(ap, ptrdiff_t); break;
+					default: v = va_arg(ap, int); break;
+				}
+				uintmax_t u = v < 0 ? -(uintmax_t)v : (uintmax_t)v;
+				format_integer(s, sp, u, v < 0);
+				break;
+			}
+			case 'u': case 'o': case 'x': case 'X': {
+				uintmax_t v;
+				switch (size) {
+					case 'H': v = (unsigned char)va_arg(ap, unsigned); break;
+					case 'h': v = (unsigned short)va_arg(ap, unsigned); break;
+					case 'l': v = va_arg(ap, unsigned long); break;
+					case 'q': v = va_arg(ap, unsigned long long); break;
+					case 'j': v = va_arg(ap, uintmax_t); break;
+					case 'z': case 't': v = va_arg(ap, size_t); break;
+					default: v = va_arg(ap, unsigned); break;
+				}
+				format_integer(s, sp, v, 0);
+				break;
+			}
+			case 'p': {
+				void *p = va_arg(ap, void *);
+				if (p == NULL) {
+					format_text(s, &sp, "(nil)", 5);
+				} else {
+					sp.precision = -1;
+					format_integer(s, sp, (uintptr_t)p, 0);
+				}
+				break;
+			}
+			case 'f': case 'F': case 'e': case 'E': case 'g': case 'G': case 'a': case 'A': {
+				format_float(s, sp, va_arg(ap, double));
+				break;
+			}
+			case 'c': {
+				char c = (char)va_arg(ap, int);
+				format_text(s, &sp, &c, 1);
+				break;
+			}
+			case 's': {
+				const char *p = va_arg(ap, const char *);
+				if (p == NULL)
+					p = "(null)";
+				size_t n = 0;
+				while ((sp.precision < 0 || n < (size_t)sp.precision) && p[n] != '\0')
+					n++;
+				format_text(s, &sp, p, n);
+				break;
+			}
+			case 'n':
+				*va_arg(ap, int *) = (int)s->count;
+				break;
+			case '%':
+				out(s, "%", 1);
+				break;
+			default:
+				out(s, "%", 1);
+				if (sp.conv != '\0')
+					out(s, &sp.conv, 1);
+				break;
+		}
+	}
+}
+
+typedef struct buffer_sink {
+	sink base;
+	char *p;
+	size_t left;
+} buffer_sink;
+
+static void buffer_put(sink *s, const char *p, size_t n) {
+	buffer_sink *b = (buffer_sink *)s;
+	size_t m = n < b->left ? n : b->left;
+	memcpy(b->p, p, m);
+	b->p += m;
+	b->left -= m;
+}
+
+int vsnprintf(char *s, size_t n, const char *format_string, va_list ap) {
+	buffer_sink b = { { buffer_put, 0 }, s, n != 0 ? n - 1 : 0 };
+	format(&b.base, format_string, ap);
+	if (n != 0)
+		*b.p = '\0';
+	return (int)b.base.count;
+}
+
+int snprintf(char *s, size_t n, const char *f, ...) {
+	va_list ap;
+	va_start(ap, f);
+	int r = vsnprintf(s, n, f, ap);
+	va_end(ap);
+	return r;
+}
+
+typedef struct file_sink {
+	sink base;
+	FILE *f;
+	size_t used;
+	char buf[256];
+} file_sink;
+
+static void file_flush(file_sink *fs) {
+	fwrite(fs->buf, 1, fs->used, fs->f);
+	fs->used = 0;
+}
+
+static void file_put(sink *s, const char *p, size_t n) {
+	file_sink *fs = (file_sink *)s;
+	for (; n != 0; p++, n--) {
+		if (fs->used == sizeof(fs->buf))
+			file_flush(fs);
+		fs->buf[fs->used++] = *p;
+	}
+}
+
+int vfprintf(FILE *f, const char *format_string, va_list ap) {
+	file_sink fs;
+	fs.base.put = file_put;
+	fs.base.count = 0;
+	fs.f = f;
+	fs.used = 0;
+	format(&fs.base, format_string, ap);
+	file_flush(&fs);
+	return f->error ? -1 : (int)fs.base.count;
+}
+
+int fprintf(FILE *f, const char *format_string, ...) {
+	va_list ap;
+	va_start(ap, format_string);
+	int r = vfprintf(f, format_string, ap);
+	va_end(ap);
+	return r;
+}
+
+int printf(const char *format_string, ...) {
+	va_list ap;
+	va_start(ap, format_string);
+	int r = vfprintf(stdout, format_string, ap);
+	va_end(ap);
+	return r;
+}
@@ -0,0 +1,172 @@
+/* Minimal C library for freestanding WebAssembly, see `Build::wasm_libc`.
+** String functions and character classification for the "C" locale. */
+#include <ctype.h>
+#include <string.h>
+
+#include "internal.h"
+
+void *memchr(const void *p, int c, size_t n) {
+	const unsigned char *s = p;
+	for (; n != 0; s++, n--) {
+		if (*s == (unsigned char)c)
+			return (void *)s;
+	}
+	return NULL;
+}
+
+int memcmp(const void *a, const void *b, size_t n) {
+	const unsigned char *x = a, *y = b;
+	for (; n != 0; x++, y++, n--) {
+		if (*x != *y)
+			return *x - *y;
+	}
+	return 0;
+}
+
+LUNKA_NO_BUILTIN void *memcpy(void *restrict dst, const void *restrict src, size_t n) {
+	unsigned char *d = dst;
+	const unsigned char *s = src;
+	while (n-- != 0)
+		*d++ = *s++;
+	return dst;
+}
+
+LUNKA_NO_BUILTIN void *memmove(void *dst, const void *src, size_t n) {
+	unsigned char *d = dst;
+	const unsigned char *s = src;
+	if (d < s) {
+		while (n-- != 0)
+			*d++ = *s++;
+	} else if (d > s) {
+		while (n-- != 0)
+			d[n] = s[n];
+	}
+	return dst;
+}
+
+LUNKA_NO_BUILTIN void *memset(void *p, int c, size_t n) {
+	unsigned char *d = p;
+	while (n-- != 0)
+		*d++ = (unsigned char)c;
+	return p;
+}
+
+char *strcat(char *restrict dst, const char *restrict src) {
+	strcpy(dst + strlen(dst), src);
+	return dst;
+}
+
+char *strchr(const char *s, int c) {
+	for (;; s++) {
+		if (*s == (char)c)
+			return (char *)s;
+		if (*s == '\0')
+			return NULL;
+	}
+}
+
+int strcmp(const char *a, const char *b) {
+	const unsigned char *x = (const unsigned char *)a, *y = (const unsigned char *)b;
+	for (; *x == *y && *x != '\0'; x++, y++) {}
+	return *x - *y;
+}
+
+int strcoll(const char *a, const char *b) {
+	return strcmp(a, b);
+}
+
+char *strcpy(char *restrict dst, const char *restrict src) {
+	char *d = dst;
+	while ((*d++ = *src++) != '\0') {}
+	return dst;
+}
+
+size_t strcspn(const char *s, const char *reject) {
+	size_t n = 0;
+	for (; s[n] != '\0' && strchr(reject, s[n]) == NULL; n++) {}
+	return n;
+}
+
+char *strerror(int n) {
+	switch (n) {
+		case 0: return "No error";
+		case 2: return "No such file or directory";
+		case 5: return "I/O error";
+		case 9: return "Bad file descriptor";
+		case 12: return "Out of memory";
+		case 22: return "Invalid argument";
+		case 33: return "Argument out of domain";
+		case 34: return "Result not representable";
+		case 38: return "Function not implemented";
+		default: return "Unknown error";
+	}
+}
+
+size_t strlen(const char *s) {
+	size_t n = 0;
+	while (s[n] != '\0')
+		n++;
+	return n;
+}
+
+int strncmp(const char *a, const char *b, size_t n) {
+	const unsigned char *x = (const unsigned char *)a, *y = (const unsigned char *)b;
+	for (; n != 0; x++, y++, n--) {
+		if (*x != *y || *x == '\0')
+			return *x - *y;
+	}
+	return 0;
+}
+
+char *strncpy(char *restrict dst, const char *restrict src, size_t n) {
+	size_t i = 0;
+	for (; i < n && src[i] != '\0'; i++)
+		dst[i] = src[i];
+	for (; i < n; i++)
+		dst[i] = '\0';
+	return dst;
+}
+
+char *strpbrk(const char *s, const char *accept) {
+	s += strcspn(s, accept);
+	return *s != '\0' ? (char *)s : NULL;
+}
+
+char *strrchr(const char *s, int c) {
+	const char *last = NULL;
+	for (;; s++) {
+		if (*s == (char)c)
+			last = s;
+		if (*s == '\0')
+			return (char *)last;
+	}
+}
+
+size_t strspn(const char *s, const char *accept) {
+	size_t n = 0;
+	for (; s[n] != '\0' && strchr(accept, s[n]) != NULL; n++) {}
+	return n;
+}
+
+char *strstr(const char *s, const char *find) {
+	size_t n = strlen(find);
+	for (; *s != '\0'; s++) {
+		if (strncmp(s, find, n) == 0)
+			return (char *)s;
+	}
+	return n == 0 ? (char *)s : NULL;
+}
+
+int isalnum(int c) { return isalpha(c) || isdigit(c); }
+int isalpha(int c) { return islower(c) || isupper(c); }
+int iscntrl(int c) { return (c >= 0 && c < 0x20) || c == 0x7f; }
+int isdigit(int c) { return c >= '0' && c <= '9'; }
+int isgraph(int c) { return c > 0x20 && c < 0x7f; }
+int islower(int c) { return c >= 'a' && c <= 'z'; }
+int isprint(int c) { return c >= 0x20 && c < 0x7f; }
+int ispunct(int c) { return isgraph(c) && !isalnum(c); }
+int isspace(int c) { return c == ' ' || (c >= '\t' && c <= '\r'); }
+int isupper(int c) { return c >= 'A' && c <= 'Z'; }
+int isxdigit(int c) { return isdigit(c) || ((c | 0x20) >= 'a' && (c | 0x20) <= 'f'); }
+int tolower(int c) { return isupper(c) ? c | 0x20 : c; }
+int toupper(int c) { return islower(c) ? c & ~0x20 : c; }